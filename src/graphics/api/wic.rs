@@ -1,6 +1,11 @@
+use std::path::Path;
+use windows::core::PCWSTR;
+use windows::Win32::Foundation::GENERIC_READ;
+use windows::Win32::Graphics::Gdi::{HBITMAP, HPALETTE};
 use windows::Win32::Graphics::Imaging::{
     IWICImagingFactory, IWICBitmap, GUID_WICPixelFormat32bppPBGRA, CLSID_WICImagingFactory,
-    WICBitmapDitherTypeNone, WICBitmapPaletteTypeCustom,
+    WICBitmapDitherTypeNone, WICBitmapPaletteTypeCustom, WICBitmapUsePremultipliedAlpha,
+    WICDecodeMetadataCacheOnDemand,
 };
 use windows::Win32::Graphics::Direct2D::{ID2D1DeviceContext, ID2D1Bitmap};
 use windows::Win32::System::Com::{CoCreateInstance, CLSCTX_INPROC_SERVER};
@@ -45,3 +50,71 @@ pub fn create_bitmap_from_hicon(
         Ok(d2d_bitmap)
     }
 }
+
+/// HBITMAP (シェルのサムネイルなど) から Direct2D ビットマップを作成するよ！
+/// HBITMAP 自体は呼び出し側で削除してね。
+pub fn create_bitmap_from_hbitmap(
+    context: &ID2D1DeviceContext,
+    wic_factory: &IWICImagingFactory,
+    hbitmap: HBITMAP,
+) -> Result<ID2D1Bitmap, windows::core::Error> {
+    unsafe {
+        let wic_bitmap: IWICBitmap =
+            wic_factory.CreateBitmapFromHBITMAP(hbitmap, HPALETTE::default(), WICBitmapUsePremultipliedAlpha)?;
+
+        let converter = wic_factory.CreateFormatConverter()?;
+        converter.Initialize(
+            &wic_bitmap,
+            &GUID_WICPixelFormat32bppPBGRA,
+            WICBitmapDitherTypeNone,
+            None,
+            0.0,
+            WICBitmapPaletteTypeCustom,
+        )?;
+
+        context.CreateBitmapFromWicBitmap(&converter, None)
+    }
+}
+
+/// 画像ファイル (GIF / ICO など) の全フレームを Direct2D ビットマップとして読み込むよ！
+/// ICO は「サイズ違いの同じ絵」が入っていることが多いから, 1枚目と同じサイズのフレームだけを残すね。
+pub fn create_bitmaps_from_file(
+    context: &ID2D1DeviceContext,
+    wic_factory: &IWICImagingFactory,
+    path: &Path,
+) -> Result<Vec<ID2D1Bitmap>, windows::core::Error> {
+    let wide_path = crate::win32::api::utils::to_wide(&path.to_string_lossy());
+    unsafe {
+        let decoder = wic_factory.CreateDecoderFromFilename(
+            PCWSTR::from_raw(wide_path.as_ptr()),
+            None,
+            GENERIC_READ,
+            WICDecodeMetadataCacheOnDemand,
+        )?;
+
+        let mut first_size = None;
+        let mut bitmaps = Vec::new();
+        for index in 0..decoder.GetFrameCount()? {
+            let frame = decoder.GetFrame(index)?;
+
+            let (mut w, mut h) = (0u32, 0u32);
+            frame.GetSize(&mut w, &mut h)?;
+            if *first_size.get_or_insert((w, h)) != (w, h) {
+                continue;
+            }
+
+            let converter = wic_factory.CreateFormatConverter()?;
+            converter.Initialize(
+                &frame,
+                &GUID_WICPixelFormat32bppPBGRA,
+                WICBitmapDitherTypeNone,
+                None,
+                0.0,
+                WICBitmapPaletteTypeCustom,
+            )?;
+            bitmaps.push(context.CreateBitmapFromWicBitmap(&converter, None)?);
+        }
+
+        Ok(bitmaps)
+    }
+}
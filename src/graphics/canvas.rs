@@ -19,6 +19,7 @@ pub struct Canvas {
     pub comp_visual: IDCompositionVisual,
     buffer_width: u32,
     buffer_height: u32,
+    target_ready: bool, // 描画先 (バックバッファの D2D ビットマップ) を設定できているか
 }
 
 impl Canvas {
@@ -56,6 +57,7 @@ impl Canvas {
             comp_visual,
             buffer_width,
             buffer_height,
+            target_ready: false,
         };
 
         canvas.setup_render_target()?;
@@ -69,6 +71,7 @@ impl Canvas {
             self.d2d_context.SetTarget(&d2d_bitmap);
             self.d2d_context.SetDpi(96.0, 96.0);
         }
+        self.target_ready = true;
         Ok(())
     }
 
@@ -86,6 +89,8 @@ impl Canvas {
         let new_buffer_width = width + 300;
         let new_buffer_height = height + 300;
 
+        // 途中で失敗したら描画先がない状態になるから, 次の描画で作り直しを試すよ
+        self.target_ready = false;
         unsafe {
             self.d2d_context.SetTarget(None);
             self.swap_chain.ResizeBuffers(
@@ -103,17 +108,36 @@ impl Canvas {
         Ok(())
     }
 
-    pub fn begin_draw(&self) {
+    /// 描画を始めるよ。描画先が用意できないときは false を返すから, そのフレームは飛ばしてね。
+    /// (ドライバーの一時的な不調や RDP の再接続のあと, 次の再描画でまた作り直しを試すんだ)
+    pub fn begin_draw(&mut self) -> bool {
+        if !self.target_ready {
+            if let Err(e) = self.setup_render_target() {
+                log::error!("Render target is not available. Skipping this frame: {}", e);
+                return false;
+            }
+            log::info!("Render target recovered.");
+        }
         unsafe {
             self.d2d_context.BeginDraw();
             // バッファ全体をクリアするよ
             self.d2d_context.Clear(None);
         }
+        true
     }
 
     /// 描画を確定して画面に反映するよ。
     /// sync_interval: 1 で VSync 同期, 0 で即座に反映。
-    pub fn end_draw(&self, sync_interval: u32) -> Result<(), windows::core::Error> {
+    /// 失敗したときは描画先を作り直すように印を付けておくよ。
+    pub fn end_draw(&mut self, sync_interval: u32) -> Result<(), windows::core::Error> {
+        let result = self.present(sync_interval);
+        if result.is_err() {
+            self.target_ready = false;
+        }
+        result
+    }
+
+    fn present(&self, sync_interval: u32) -> Result<(), windows::core::Error> {
         unsafe {
             // 1. Direct2D の描画完了
             self.d2d_context.EndDraw(None, None)?;
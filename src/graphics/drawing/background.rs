@@ -1,6 +1,10 @@
+use windows::core::ComInterface;
+use windows::Foundation::Numerics::Matrix3x2;
 use windows::Win32::Graphics::Direct2D::{
-    ID2D1DeviceContext, D2D1_ROUNDED_RECT, ID2D1SolidColorBrush,
+    ID2D1Bitmap, ID2D1DeviceContext, ID2D1LinearGradientBrush, ID2D1RenderTarget, D2D1_ROUNDED_RECT,
+    ID2D1SolidColorBrush,
 };
+use windows::Win32::Graphics::Direct2D::Common::D2D_POINT_2F;
 
 /// 矩形の背景と枠線を描画するよ！
 /// 描画に必要なリソースは外部 (Resources) から提供される前提だよ。
@@ -30,3 +34,76 @@ pub fn draw_rounded_rect(
         }
     }
 }
+
+/// 上から下へのグラデーションで角丸矩形を塗りつぶすよ！
+/// ブラシは Resources でキャッシュしたものを使って, ここでは向きと透明度だけ合わせるね。
+pub fn fill_gradient_rounded_rect(
+    context: &ID2D1DeviceContext,
+    rect: &windows::Win32::Graphics::Direct2D::Common::D2D_RECT_F,
+    brush: &ID2D1LinearGradientBrush,
+    opacity: f32,
+    radius: f32,
+) {
+    let rounded_rect = D2D1_ROUNDED_RECT { rect: *rect, radiusX: radius, radiusY: radius };
+
+    unsafe {
+        brush.SetStartPoint(D2D_POINT_2F { x: rect.left, y: rect.top });
+        brush.SetEndPoint(D2D_POINT_2F { x: rect.left, y: rect.bottom });
+        brush.SetOpacity(opacity);
+        context.FillRoundedRectangle(&rounded_rect, brush);
+    }
+}
+
+/// 画像で角丸矩形を塗りつぶすよ！
+/// 縦横比は保ったまま, 矩形を覆うように拡大して中央に合わせるね。
+pub fn fill_image_rounded_rect(
+    context: &ID2D1DeviceContext,
+    rect: &windows::Win32::Graphics::Direct2D::Common::D2D_RECT_F,
+    bitmap: &ID2D1Bitmap,
+    opacity: f32,
+    radius: f32,
+) -> Result<(), windows::core::Error> {
+    let rounded_rect = D2D1_ROUNDED_RECT { rect: *rect, radiusX: radius, radiusY: radius };
+
+    unsafe {
+        let size = bitmap.GetSize();
+        if size.width <= 0.0 || size.height <= 0.0 {
+            return Ok(());
+        }
+        let width = rect.right - rect.left;
+        let height = rect.bottom - rect.top;
+        let scale = (width / size.width).max(height / size.height);
+        let transform = Matrix3x2 {
+            M11: scale,
+            M12: 0.0,
+            M21: 0.0,
+            M22: scale,
+            M31: rect.left + (width - size.width * scale) / 2.0,
+            M32: rect.top + (height - size.height * scale) / 2.0,
+        };
+
+        let rt: ID2D1RenderTarget = context.cast()?;
+        let brush = rt.CreateBitmapBrush(bitmap, None, None)?;
+        brush.SetTransform(&transform);
+        brush.SetOpacity(opacity);
+        context.FillRoundedRectangle(&rounded_rect, &brush);
+    }
+    Ok(())
+}
+
+/// 角丸矩形の枠線だけを描画するよ。太さが 0 なら何も描かないよ。
+pub fn draw_rounded_border(
+    context: &ID2D1DeviceContext,
+    rect: &windows::Win32::Graphics::Direct2D::Common::D2D_RECT_F,
+    brush: &ID2D1SolidColorBrush,
+    width: f32,
+    radius: f32,
+) {
+    if width <= 0.0 {
+        return;
+    }
+    let rounded_rect = D2D1_ROUNDED_RECT { rect: *rect, radiusX: radius, radiusY: radius };
+    unsafe {
+        context.DrawRoundedRectangle(&rounded_rect, brush, width, None);
+    }
+}
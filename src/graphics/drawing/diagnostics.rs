@@ -0,0 +1,64 @@
+use std::time::{Duration, Instant};
+use windows::Win32::Graphics::Direct2D::ID2D1DeviceContext;
+use windows::Win32::Graphics::Direct2D::Common::D2D_RECT_F;
+use crate::graphics::drawing::{background, label, resources::DrawingResources};
+use crate::settings::models::FontWeight;
+
+/// 描画のタイミングを記録して, FPS などを出すための計測器だよ！
+/// (開発者向けの診断オーバーレイ用)
+#[derive(Default)]
+pub struct FrameStats {
+    pub redraw_count: u64,
+    last_frame_at: Option<Instant>,
+    frame_interval: Duration, // 前のフレームからの間隔
+    draw_time: Duration,      // 前のフレームの描画にかかった時間
+}
+
+impl FrameStats {
+    /// 描画の開始時に呼んでね。前回からの間隔を記録するよ。
+    pub fn begin_frame(&mut self) -> Instant {
+        let now = Instant::now();
+        if let Some(last) = self.last_frame_at {
+            self.frame_interval = now - last;
+        }
+        self.last_frame_at = Some(now);
+        self.redraw_count += 1;
+        now
+    }
+
+    /// 描画の終了時に, begin_frame が返した時刻を渡してね。
+    pub fn end_frame(&mut self, started_at: Instant) {
+        self.draw_time = started_at.elapsed();
+    }
+
+    pub fn fps(&self) -> f32 {
+        let secs = self.frame_interval.as_secs_f32();
+        if secs > 0.0 { 1.0 / secs } else { 0.0 }
+    }
+}
+
+/// 左上に FPS・描画時間・再描画回数・アイコン数を小さく表示するよ。
+pub fn draw_overlay(
+    context: &ID2D1DeviceContext,
+    stats: &FrameStats,
+    icon_count: usize,
+    resources: &mut DrawingResources,
+) -> Result<(), windows::core::Error> {
+    let text = format!(
+        "{:.1} fps | {:.2} ms | #{} | icons {}",
+        stats.fps(),
+        stats.draw_time.as_secs_f64() * 1000.0,
+        stats.redraw_count,
+        icon_count,
+    );
+
+    let rect = D2D_RECT_F { left: 4.0, top: 4.0, right: 260.0, bottom: 22.0 };
+    let bg_brush = resources.get_brush(context, "#000000AA")?;
+    let text_brush = resources.get_brush(context, "#00FF00FF")?;
+    background::draw_rounded_rect(context, &rect, &bg_brush, None, 0.0, 3.0);
+
+    let format = resources.get_header_text_format("Consolas", 11.0, FontWeight::Regular)?;
+    let text_rect = D2D_RECT_F { left: rect.left + 4.0, ..rect };
+    label::draw_text(context, &text, &text_rect, &text_brush, &format);
+    Ok(())
+}
@@ -3,22 +3,59 @@ use windows::Win32::Graphics::Direct2D::Common::{D2D_RECT_F, D2D_POINT_2F};
 use crate::graphics::drawing::resources::DrawingResources;
 
 /// 操作説明テキストを定数として定義
-const OPERATION_INSTRUCTIONS: [&str; 15] = [
+const OPERATION_INSTRUCTIONS: &[&str] = &[
     "## 操作説明",
     "### ■ Create Groups:",
     "  - Right-click: トレイアイコンを右クリックしてメニューを表示し New Group.",
     "  - Drag & Drop: ファイルをドラッグ＆ドロップしてグループに簡単に追加できます。",
+    "  - Ctrl + Drop Folder: フォルダの代わりに, 中身のファイルとフォルダを1つずつ追加します (200 個まで)。",
+    "  - Drop on Header: 見出しにドロップするとそのセクションへ。空白部分へのドロップは最後のセクションに追加されます。",
     "### ■ Icons:",
     "  - Left-double-click: アプリケーションが起動またはファイルが開きます。",
+    "    (リンク先が見つからない「!」付きのアイコンは, 新しい場所を選んでつなぎ直せます)",
     "  - Right-click: そのファイルがあるフォルダが開きます。",
-    "  - Ctrl + Right-click: そのアイコンを削除します。",
+    "  - Shift + Right-click: そのファイルのフルパスをクリップボードにコピーします。",
+    "  - Shift + Left-double-click: アイコンの表示名を入力, Enter で確定 (空にするとファイル名に戻ります)。",
+    "  - Launcher: トレイアイコンを左クリックで全アイコン一覧。文字入力で絞り込み, クリックか Enter で起動します。",
+    "  - Ctrl + D: カーソルの下のアイコンを複製します。",
+    "  - Ctrl + Right-click / Delete: そのアイコンを削除します (Delete はカーソルを合わせて)。",
+    "  - Mouse wheel: アイコンが収まりきらないときは縦にスクロールします。",
+    "  - Hover: 名前が省略されているアイコンにカーソルを止めると, 全部の名前とパスを吹き出しで表示します。",
+    "  - Ctrl + Enter: そのグループのアイコンを全部, 順番に開きます。",
+    "  - Arrow keys: グループ内のアイコンのフォーカスを移動し, Enter でフォーカス中のアイコンを開きます。",
+    "  - Drag on empty space: 範囲選択。Delete で削除, Enter で開く, Ctrl + X → 別グループで Ctrl + V で移動。",
     "### ■ Customization:",
     "  - Move: Ctrl + ドラッグ でグループを移動します。",
-    "  - Resize: Shift + ドラッグ でグループのサイズを変更します。",
+    "  - Resize: Shift + ドラッグ でグループのサイズを変更します (押した場所に近い辺や角が動きます)。",
+    "  - Title: F2 (またはタイトル行をクリック) で名前を入力, Enter で確定, Esc で取り消し。",
+    "  - Fit: 何もない場所をダブルクリックすると, アイコンが収まる大きさになります。",
     "  - Color: Ctrl + V でカラーコード (#FF0000) や「#Random」を貼り付け。",
+    "  - Color Picker: 何もない場所を右クリックすると色の選択ダイアログで背景色を選べます。",
     "  - Transparency: Alt + ドラッグ で透明度を調整します。",
+    "  - Border: Ctrl + Shift + ホイール で枠線の太さを調整します (0 で枠線なし)。",
+    "  - Window Opacity: Alt + ホイール でアイコンも含めたグループ全体の透明度を調整します。",
+    "  - Color History: Ctrl + Z で前の色に戻し, Ctrl + Y で進めます。",
+    "  - Size Presets: Ctrl + S で size_presets の大きさを順に切り替え (最後は元の大きさに戻ります)。",
+    "  - Hide All: Ctrl + Alt + D で全グループを隠す / 表示する (どのアプリを使っていても効きます)。",
+    "  - Always on Top: Ctrl + T でそのグループだけを常に最前面に (もう一度押すと最背面に戻ります)。",
+    "  - Lock: Ctrl + L で移動・リサイズ・アイコンの編集・グループの削除をまとめてロック / 解除 (ロック中は右下に鍵の印)。",
+    "  - Collapse: Ctrl + M でタイトル行だけの細い帯に折りたたみ (もう一度押すと元の高さに戻ります)。",
+    "  - Solo: Ctrl + Shift + S でカーソルの下のグループだけを表示 (もう一度押すと元に戻ります)。",
+    "  - Background: Ctrl + B で 単色 / グラデーション / 画像 を切り替え。「bgimage:パス」を Ctrl + V で背景画像。",
+    "  - Section: アイコンにカーソルを合わせ「section:名前」を Ctrl + V で見出し付きセクションへ。",
+    "  - Icon Image: アイコンにカーソルを合わせ「icon:画像のパス」を Ctrl + V で好きな画像に (「icon:」で解除)。",
+    "  - Tidy: 「tidy:」を Ctrl + V で順番はそのままに詰めて並べ直し (「tidy:flat」で見出しも外す)。",
+    "  - Lock: 「lock:move」「lock:resize」「lock:icons」を Ctrl + V でそれぞれのロックを切り替え (「lock:all」で全部, 「lock:」で解除)。",
+    "  - Font Size: 「fontsize:14」を Ctrl + V でそのグループだけ文字サイズを変更 (「fontsize:」で解除)。",
+    "  - Font: 「font:Yu Gothic UI」を Ctrl + V でそのグループだけフォントを変更 (「font:」で解除)。",
+    "  - List Layout: 「layout:list」を Ctrl + V で1行ずつのリスト表示に (「layout:grid」で戻す)。",
+    "  - Row Align: 「align:center」を Ctrl + V で埋まりきらない行を中央寄せ (「align:left」で戻す)。",
+    "  - Icon Only: 「labels:off」を Ctrl + V で名前を隠してアイコンだけを詰めて表示 (「labels:on」で戻す)。",
+    "  - Auto Sort: 「autosort:name」「autosort:ext」を Ctrl + V で自動整列 (「autosort:」で解除)。",
+    "  - Sort: 「sort:name」「sort:name_desc」「sort:ext」を Ctrl + V で今のアイコンを一度だけ並べ替え。",
     "### ■ Delete Groups:",
     "  - Ctrl + Right-click: グループの何もない場所を右クリックして削除。",
+    "  - Ctrl + Shift + Z: 直前に削除したグループかアイコンを元に戻します (デスクトップ上で)。",
 ];
 
 /// ヘルプ（操作ガイド）を描画する専用の関数だよ！
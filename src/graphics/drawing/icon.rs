@@ -1,20 +1,194 @@
+use std::path::Path;
 use windows::Win32::Graphics::Direct2D::{ID2D1DeviceContext, ID2D1Bitmap};
-use windows::Win32::Graphics::Direct2D::Common::D2D_RECT_F;
+use windows::Win32::Graphics::Direct2D::Common::{D2D1_COLOR_F, D2D_RECT_F};
+use crate::graphics::drawing::{background, label, resources::DrawingResources};
+use crate::graphics::layout;
+use crate::settings::models::{IconFilterQuality, PlaceholderStyle};
+use crate::ui::group::model::MAX_FOLDER_COUNT;
 
 /// アイコン (ビットマップ) を描画するよ！
+/// keep_aspect が true なら, 正方形でない画像も縦横比を保って rect の中央に収めるね。
+/// 拡大・縮小の補間方法は quality で選べるよ。
 pub fn draw_icon(
     context: &ID2D1DeviceContext,
     bitmap: &ID2D1Bitmap,
     rect: &D2D_RECT_F,
     opacity: f32,
+    keep_aspect: bool,
+    quality: IconFilterQuality,
 ) {
+    use windows::Win32::Graphics::Direct2D::{
+        D2D1_INTERPOLATION_MODE_HIGH_QUALITY_CUBIC, D2D1_INTERPOLATION_MODE_LINEAR, D2D1_INTERPOLATION_MODE_NEAREST_NEIGHBOR,
+    };
+
+    let interpolation = match quality {
+        IconFilterQuality::Nearest => D2D1_INTERPOLATION_MODE_NEAREST_NEIGHBOR,
+        IconFilterQuality::Bilinear => D2D1_INTERPOLATION_MODE_LINEAR,
+        IconFilterQuality::Bicubic => D2D1_INTERPOLATION_MODE_HIGH_QUALITY_CUBIC,
+    };
     unsafe {
-        context.DrawBitmap(
+        let rect = if keep_aspect {
+            let size = bitmap.GetSize();
+            layout::fit_rect(rect, size.width, size.height)
+        } else {
+            *rect
+        };
+        context.DrawBitmap2(
             bitmap,
-            Some(rect),
+            Some(&rect),
             opacity,
-            windows::Win32::Graphics::Direct2D::D2D1_BITMAP_INTERPOLATION_MODE_LINEAR,
+            interpolation,
             None, // 描画範囲全体 (Source Rect)
+            None,
         );
     }
 }
+
+/// アイコンを表示できないときの代わりを描画するよ！
+/// 失敗したことは分かるように, でも目立ちすぎないようにするのが目的だよ。
+pub fn draw_placeholder_icon(
+    context: &ID2D1DeviceContext,
+    rect: &D2D_RECT_F,
+    style: PlaceholderStyle,
+    image: Option<&Path>,
+    bg_color: D2D1_COLOR_F,
+    font_family: &str,
+    resources: &mut DrawingResources,
+) -> Result<(), windows::core::Error> {
+    match style {
+        PlaceholderStyle::Inverted => {
+            // 背景色の反転色で四角を描画
+            let (ir, ig, ib) = layout::invert_color(bg_color.r, bg_color.g, bg_color.b);
+            let inv_hex = format!("#{:02X}{:02X}{:02X}FF", (ir * 255.0) as u8, (ig * 255.0) as u8, (ib * 255.0) as u8);
+            let inv_brush = resources.get_brush(context, &inv_hex)?;
+            background::draw_rounded_rect(context, rect, &inv_brush, None, 0.0, 4.0);
+            return Ok(());
+        }
+        PlaceholderStyle::Image => {
+            if let Some(bitmap) = image.and_then(|path| resources.get_image(context, path)) {
+                draw_icon(context, &bitmap, rect, 1.0, true, IconFilterQuality::default());
+                return Ok(());
+            }
+            // 画像が読めないときは記号で代用するよ
+        }
+        PlaceholderStyle::Glyph => {}
+    }
+
+    draw_glyph_tile(context, rect, "?", font_family, resources)
+}
+
+/// ファイルを持たない URL のアイコンを, 地球の記号で描くよ！
+pub fn draw_link_icon(
+    context: &ID2D1DeviceContext,
+    rect: &D2D_RECT_F,
+    font_family: &str,
+    resources: &mut DrawingResources,
+) -> Result<(), windows::core::Error> {
+    draw_glyph_tile(context, rect, "\u{1F310}", font_family, resources)
+}
+
+/// 角丸の四角の真ん中に記号を1つ描くよ。
+fn draw_glyph_tile(
+    context: &ID2D1DeviceContext,
+    rect: &D2D_RECT_F,
+    glyph: &str,
+    font_family: &str,
+    resources: &mut DrawingResources,
+) -> Result<(), windows::core::Error> {
+    // どんな背景色でも馴染むように, 半透明のグレーで描くよ
+    let fill_brush = resources.get_brush(context, "#80808033")?;
+    let border_brush = resources.get_brush(context, "#80808099")?;
+    let glyph_brush = resources.get_brush(context, "#808080FF")?;
+    let glyph_size = ((rect.bottom - rect.top) * 0.6).max(1.0);
+    let format = resources.get_glyph_text_format(font_family, glyph_size)?;
+
+    background::draw_rounded_rect(context, rect, &fill_brush, Some(&border_brush), 1.0, 4.0);
+    label::draw_text(context, glyph, rect, &glyph_brush, &format);
+    Ok(())
+}
+
+/// リンク先のファイルが見つからないと分かるように, アイコンの右下に赤い「!」の印を描くよ！
+pub fn draw_missing_badge(
+    context: &ID2D1DeviceContext,
+    rect: &D2D_RECT_F,
+    font_family: &str,
+    resources: &mut DrawingResources,
+) -> Result<(), windows::core::Error> {
+    let radius = ((rect.right - rect.left) * 0.16).max(4.0);
+    let center = windows::Win32::Graphics::Direct2D::Common::D2D_POINT_2F {
+        x: rect.right - radius,
+        y: rect.bottom - radius,
+    };
+    let ellipse = windows::Win32::Graphics::Direct2D::D2D1_ELLIPSE { point: center, radiusX: radius, radiusY: radius };
+    let fill_brush = resources.get_brush(context, "#D83B01FF")?;
+    let border_brush = resources.get_brush(context, "#FFFFFFFF")?;
+    let mark_brush = resources.get_brush(context, "#FFFFFFFF")?;
+    let format = resources.get_glyph_text_format(font_family, radius * 1.5)?;
+    let mark_rect = D2D_RECT_F {
+        left: center.x - radius,
+        top: center.y - radius,
+        right: center.x + radius,
+        bottom: center.y + radius,
+    };
+    unsafe {
+        context.FillEllipse(&ellipse, &fill_brush);
+        context.DrawEllipse(&ellipse, &border_brush, 1.5, None);
+    }
+    label::draw_text(context, "!", &mark_rect, &mark_brush, &format);
+    Ok(())
+}
+
+/// フォルダの中身の数を, アイコンの右上に小さな角丸のバッジで描くよ！
+/// 数えるのをやめた (MAX_FOLDER_COUNT に届いた) ときは「999+」のように出すね。
+pub fn draw_folder_count_badge(
+    context: &ID2D1DeviceContext,
+    rect: &D2D_RECT_F,
+    count: usize,
+    font_family: &str,
+    resources: &mut DrawingResources,
+) -> Result<(), windows::core::Error> {
+    let text = if count >= MAX_FOLDER_COUNT {
+        format!("{}+", MAX_FOLDER_COUNT - 1)
+    } else {
+        count.to_string()
+    };
+    let height = ((rect.bottom - rect.top) * 0.3).max(10.0);
+    let width = (text.chars().count() as f32 * height * 0.55 + height * 0.5).max(height);
+    let badge_rect = D2D_RECT_F {
+        left: rect.right - width * 0.75,
+        top: rect.top - height * 0.25,
+        right: rect.right + width * 0.25,
+        bottom: rect.top + height * 0.75,
+    };
+    let fill_brush = resources.get_brush(context, "#505050E6")?;
+    let border_brush = resources.get_brush(context, "#FFFFFFFF")?;
+    let text_brush = resources.get_brush(context, "#FFFFFFFF")?;
+    let format = resources.get_glyph_text_format(font_family, height * 0.7)?;
+    background::draw_rounded_rect(context, &badge_rect, &fill_brush, Some(&border_brush), 1.0, height / 2.0);
+    label::draw_text(context, &text, &badge_rect, &text_brush, &format);
+    Ok(())
+}
+
+/// ネットワーク上のファイルだと分かるように, アイコンの左下に小さな丸い印を描くよ！
+pub fn draw_network_badge(
+    context: &ID2D1DeviceContext,
+    rect: &D2D_RECT_F,
+    resources: &mut DrawingResources,
+) -> Result<(), windows::core::Error> {
+    let radius = ((rect.right - rect.left) * 0.14).max(3.0);
+    let ellipse = windows::Win32::Graphics::Direct2D::D2D1_ELLIPSE {
+        point: windows::Win32::Graphics::Direct2D::Common::D2D_POINT_2F {
+            x: rect.left + radius,
+            y: rect.bottom - radius,
+        },
+        radiusX: radius,
+        radiusY: radius,
+    };
+    let fill_brush = resources.get_brush(context, "#2D7DD2FF")?;
+    let border_brush = resources.get_brush(context, "#FFFFFFFF")?;
+    unsafe {
+        context.FillEllipse(&ellipse, &fill_brush);
+        context.DrawEllipse(&ellipse, &border_brush, 1.5, None);
+    }
+    Ok(())
+}
@@ -1,8 +1,12 @@
 use windows::Win32::Graphics::Direct2D::{ID2D1DeviceContext, ID2D1SolidColorBrush};
 use windows::Win32::Graphics::Direct2D::Common::D2D_RECT_F;
-use windows::Win32::Graphics::DirectWrite::IDWriteTextFormat;
+use windows::Win32::Graphics::DirectWrite::{IDWriteFactory1, IDWriteTextFormat, DWRITE_TEXT_METRICS, DWRITE_WORD_WRAPPING_NO_WRAP};
+
+/// 測るときの「十分に大きい」幅と高さだよ。
+const UNBOUNDED: f32 = 100_000.0;
 
 /// ラベル (テキスト) を描画するよ！
+/// 絵文字はカラーフォントのまま描くね (フォントに無い文字はフォーマットのフォールバックに任せるよ)。
 pub fn draw_text(
     context: &ID2D1DeviceContext,
     text: &str,
@@ -19,8 +23,33 @@ pub fn draw_text(
             format,
             rect,
             brush,
-            windows::Win32::Graphics::Direct2D::D2D1_DRAW_TEXT_OPTIONS_NONE,
+            windows::Win32::Graphics::Direct2D::D2D1_DRAW_TEXT_OPTIONS_ENABLE_COLOR_FONT,
             windows::Win32::Graphics::DirectWrite::DWRITE_MEASURING_MODE_NATURAL,
         );
     }
 }
+
+/// text を rect に描いたときに, 省略記号で切られてしまうかどうかだよ。
+/// 1行のフォーマットなら幅で, 折り返すフォーマットなら高さで比べるね。
+pub fn is_truncated(
+    factory: &IDWriteFactory1,
+    text: &str,
+    rect: &D2D_RECT_F,
+    format: &IDWriteTextFormat,
+) -> bool {
+    let wide_text: Vec<u16> = text.encode_utf16().collect();
+    let (width, height) = (rect.right - rect.left, rect.bottom - rect.top);
+
+    unsafe {
+        let wraps = format.GetWordWrapping() != DWRITE_WORD_WRAPPING_NO_WRAP;
+        let max_width = if wraps { width } else { UNBOUNDED };
+        let Ok(layout) = factory.CreateTextLayout(&wide_text, format, max_width, UNBOUNDED) else {
+            return false;
+        };
+        let mut metrics = DWRITE_TEXT_METRICS::default();
+        if layout.GetMetrics(&mut metrics).is_err() {
+            return false;
+        }
+        if wraps { metrics.height > height + 0.5 } else { metrics.width > width + 0.5 }
+    }
+}
@@ -4,5 +4,6 @@ pub mod label;
 pub mod painter;
 pub mod resources;
 pub mod help;
+pub mod diagnostics;
 
 pub use resources::DrawingResources;
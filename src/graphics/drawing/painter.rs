@@ -1,8 +1,9 @@
-use windows::Win32::Graphics::Direct2D::ID2D1DeviceContext;
-use windows::Win32::Graphics::Direct2D::Common::D2D_RECT_F;
-use windows::Win32::UI::WindowsAndMessaging::DestroyIcon;
+use windows::Win32::Graphics::Direct2D::{ID2D1DeviceContext, D2D1_ANTIALIAS_MODE_PER_PRIMITIVE};
+use windows::Win32::Graphics::Direct2D::Common::{D2D1_COLOR_F, D2D_RECT_F, D2D_POINT_2F};
+use windows::Win32::Graphics::DirectWrite::{DWRITE_TEXT_ALIGNMENT_LEADING, DWRITE_TEXT_METRICS};
 use crate::graphics::drawing::{background, label, icon, resources::DrawingResources};
 use crate::graphics::layout;
+use crate::settings::models::{BackgroundMode, LayoutMode};
 use crate::ui::group::model::GroupModel;
 use crate::win32::api::shell;
 
@@ -29,66 +30,400 @@ pub fn draw_group(
     let border_brush = resources.get_brush(context, border_color_hex)?; 
 
     unsafe {
-        bg_brush.SetOpacity(model.opacity);
-        border_brush.SetOpacity(model.opacity * 0.5);
+        bg_brush.SetOpacity(model.render_opacity());
+        border_brush.SetOpacity(model.render_opacity() * 0.5);
     }
 
-    background::draw_rounded_rect(context, &bg_rect, &bg_brush, Some(&border_brush), 1.5, 8.0);
+    // 背景のモードに合わせて塗るよ (画像が読めないときは単色に戻すね)
+    let bg_image = match model.bg_mode {
+        BackgroundMode::Image => model.bg_image.as_ref().and_then(|path| resources.get_image(context, path)),
+        _ => None,
+    };
+    if let Some(bitmap) = bg_image {
+        background::fill_image_rounded_rect(context, &bg_rect, &bitmap, model.render_opacity(), 8.0)?;
+        background::draw_rounded_border(context, &bg_rect, &border_brush, model.border_width, 8.0);
+    } else if model.bg_mode == BackgroundMode::Gradient {
+        let (sr, sg, sb) = layout::shade_color(bg_color.r, bg_color.g, bg_color.b);
+        let shaded = D2D1_COLOR_F { r: sr, g: sg, b: sb, a: bg_color.a };
+        let gradient_brush = resources.get_gradient_brush(context, bg_color, shaded)?;
+        background::fill_gradient_rounded_rect(context, &bg_rect, &gradient_brush, model.render_opacity(), 8.0);
+        background::draw_rounded_border(context, &bg_rect, &border_brush, model.border_width, 8.0);
+    } else {
+        background::draw_rounded_rect(context, &bg_rect, &bg_brush, Some(&border_brush), model.border_width, 8.0);
+    }
+
+    // アイコンを起動した直後は, 背景に白を薄く重ねて明るくするよ
+    if model.launch_flash > 0.0 {
+        let alpha = (model.launch_flash * 0.35 * 255.0).round() as u8;
+        let flash_brush = resources.get_brush(context, &format!("#FFFFFF{:02X}", alpha))?;
+        background::draw_rounded_rect(context, &bg_rect, &flash_brush, None, 0.0, 8.0);
+    }
+
+    // キーボード操作の対象 (フォーカス中) なら, 枠を目立つ色で重ねるよ
+    if model.focused {
+        let focus_brush = resources.get_brush(context, if is_dark { "#66B2FFCC" } else { "#0066CCCC" })?;
+        let inset = D2D_RECT_F { left: 1.0, top: 1.0, right: width - 1.0, bottom: height - 1.0 };
+        background::draw_rounded_border(context, &inset, &focus_brush, 2.0, 8.0);
+    }
+
+    // ファイルのドラッグが上に来ているなら, ここに落ちると分かるように太い枠で囲むよ
+    if model.drop_target {
+        let drop_brush = resources.get_brush(context, if is_dark { "#66B2FFFF" } else { "#0066CCFF" })?;
+        let inset = D2D_RECT_F { left: 1.5, top: 1.5, right: width - 1.5, bottom: height - 1.5 };
+        background::draw_rounded_border(context, &inset, &drop_brush, 3.0, 8.0);
+    }
+
+    // 1.5 タイトル行 (編集中は入力途中の文字列とキャレットを出すよ)
+    let (font_family, font_size, title_weight, label_weight, params) = {
+        let settings = crate::settings::manager::get_settings_reader();
+        resources.set_fallback_font_family(&settings.app.fallback_font_family);
+        (
+            model.font_family(&settings.app).to_string(),
+            model.font_size(&settings.app),
+            settings.app.title_font_weight,
+            settings.app.label_font_weight,
+            model.layout_params(&settings.app),
+        )
+    };
+    if let Some(title_rect) = layout::title_rect(width, &params) {
+        let title_brush = resources.get_brush(context, text_color_hex)?;
+        let title_format = resources.get_header_text_format(&font_family, font_size, title_weight)?;
+        let text = match model.name_draft.as_ref().filter(|_| model.is_editing_title()) {
+            Some(draft) => {
+                let edit_brush = resources.get_brush(context, if is_dark { "#FFFFFF22" } else { "#00000011" })?;
+                background::draw_rounded_rect(context, &title_rect, &edit_brush, Some(&border_brush), 1.0, 4.0);
+                format!("{}|", draft)
+            }
+            None => model.title.clone(),
+        };
+        label::draw_text(context, &text, &title_rect, &title_brush, &title_format);
+        unsafe {
+            context.DrawLine(
+                D2D_POINT_2F { x: title_rect.left, y: title_rect.bottom - 1.0 },
+                D2D_POINT_2F { x: title_rect.right, y: title_rect.bottom - 1.0 },
+                &border_brush,
+                1.0,
+                None,
+            );
+        }
+    }
 
     // 2. アイコンとラベルの描画
-    if !model.icons.is_empty() {
+    // スクロールしたときにタイトル行へはみ出さないように, 中身の領域だけに描くよ
+    let content_top = layout::title_rect(width, &params).map_or(layout::PADDING, |rect| rect.bottom);
+    unsafe {
+        context.PushAxisAlignedClip(
+            &D2D_RECT_F { left: 0.0, top: content_top, right: width, bottom: height },
+            D2D1_ANTIALIAS_MODE_PER_PRIMITIVE,
+        );
+    }
+    let mut tooltip_text = None;
+    // 折りたたんでいるときはタイトル行だけだよ
+    if !model.icons.is_empty() && !model.collapsed {
         let settings = crate::settings::manager::get_settings_reader();
-        let layouts = layout::calculate_grid_layout(width, model.icons.len(), model.icon_size, settings.app.font_size, 1.0);
+        let mut group_layout = layout::calculate_group_layout(width, &model.sections(), &params);
+        group_layout.apply_scroll(model.scroll_offset, height);
+        let layouts = &group_layout.items;
         let icon_label_brush = resources.get_brush(context, text_color_hex)?;
         
-        let format = resources.get_text_format(&settings.app.font_family, settings.app.font_size)?;
+        // リスト表示の名前は, アイコンの右に左寄せで1行に出すよ (グリッド表示は max_label_lines 行まで折り返すね)
+        let format = match params.mode {
+            LayoutMode::Grid if params.label_lines > 1 => resources.get_wrapped_text_format(&font_family, font_size, label_weight)?,
+            LayoutMode::Grid => resources.get_text_format(&font_family, font_size, label_weight)?,
+            LayoutMode::List => resources.get_header_text_format(&font_family, font_size, label_weight)?,
+        };
+        let header_format = resources.get_header_text_format(&font_family, font_size, title_weight)?;
+        // 名前を出さないグループは, アイコンだけを描いて名前は吹き出しに任せるよ
+        let show_labels = params.label_lines > 0;
+        let list_separators = settings.app.list_separators;
+        let placeholder_style = settings.app.placeholder_style;
+        let placeholder_image = settings.app.placeholder_image.clone();
+        let keep_icon_aspect = settings.app.keep_icon_aspect;
+        let icon_filter_quality = settings.app.icon_filter_quality;
+        let use_thumbnails = settings.app.use_thumbnails;
         drop(settings);
+
+        // リスト状 (1列) のときは, 行の間に薄い区切り線を引くよ
+        if list_separators && group_layout.columns == 1 {
+            let separator_brush = resources.get_brush(context, if is_dark { "#FFFFFF1A" } else { "#0000001A" })?;
+            let stroke = params.scale_factor; // 1px を DPI に合わせて太くするよ
+            let sections = model.sections();
+            for i in 1..layouts.len() {
+                // 見出しには線があるから, 同じセクション内の行の間だけに引くね
+                if sections[i] != sections[i - 1] {
+                    continue;
+                }
+                // ホバーや実行中のハイライトに重ならないようにするよ
+                let highlighted = |idx: usize| model.hovered_index == Some(idx) || model.executing_index == Some(idx);
+                if highlighted(i) || highlighted(i - 1) {
+                    continue;
+                }
+                let y = layouts[i].hit_rect.top;
+                unsafe {
+                    context.DrawLine(
+                        D2D_POINT_2F { x: layout::PADDING * 2.0, y },
+                        D2D_POINT_2F { x: width - layout::PADDING * 2.0, y },
+                        &separator_brush,
+                        stroke,
+                        None,
+                    );
+                }
+            }
+        }
+
+        // セクション見出し: 名前と, その下に控えめな区切り線を引くよ
+        for header in &group_layout.headers {
+            label::draw_text(context, &header.name, &header.rect, &icon_label_brush, &header_format);
+            unsafe {
+                context.DrawLine(
+                    D2D_POINT_2F { x: header.rect.left, y: header.rect.bottom - 1.0 },
+                    D2D_POINT_2F { x: header.rect.right, y: header.rect.bottom - 1.0 },
+                    &border_brush,
+                    1.0,
+                    None,
+                );
+            }
+        }
         
-        let highlight_bg_brush = resources.get_brush(context, if is_dark { "#FFFFFF22" } else { "#00000011" })?; 
-        let highlight_border_brush = resources.get_brush(context, if is_dark { "#FFFFFF66" } else { "#00000033" })?;
+        // ホバーと実行中の色は, 設定で決まっていればそれを, 空なら背景に合わせた色を使うよ
+        let (hover_fill, hover_border, executing) = {
+            let settings = crate::settings::manager::get_settings_reader();
+            (
+                settings.app.hover_fill_color.clone(),
+                settings.app.hover_border_color.clone(),
+                settings.app.executing_color.clone(),
+            )
+        };
+        let highlight_bg_brush = resources.get_brush(context, color_or(&hover_fill, if is_dark { "#FFFFFF22" } else { "#00000011" }))?; 
+        let highlight_border_brush = resources.get_brush(context, color_or(&hover_border, if is_dark { "#FFFFFF66" } else { "#00000033" }))?;
         
-        let executing_bg_brush = resources.get_brush(context, if is_dark { "#FFFFFF66" } else { "#00000044" })?;
-        let executing_border_brush = resources.get_brush(context, if is_dark { "#FFFFFFFF" } else { "#00000088" })?;
+        let executing_bg_brush = resources.get_brush(context, color_or(&executing, if is_dark { "#FFFFFF66" } else { "#00000044" }))?;
+        let executing_border_brush = resources.get_brush(context, color_or(&executing, if is_dark { "#FFFFFFFF" } else { "#00000088" }))?;
 
-        for (i, icon_state) in model.icons.iter().enumerate() {
+        let selected_bg_brush = resources.get_brush(context, "#3399FF44")?;
+        let selected_border_brush = resources.get_brush(context, "#3399FFAA")?;
+        let focus_ring_brush = resources.get_brush(context, if is_dark { "#66B2FFFF" } else { "#0066CCFF" })?;
+
+        // ドラッグ中のアイコンは他のアイコンより手前に来るように, 最後に描くよ
+        let dragged_index = model.icon_drag.map(|(index, _)| index).filter(|&index| index < model.icons.len());
+        let draw_order = (0..model.icons.len()).filter(|&i| Some(i) != dragged_index).chain(dragged_index);
+
+        for i in draw_order {
+            let icon_state = &model.icons[i];
             if let Some(layout) = layouts.get(i) {
-                
+                // ドラッグ中のアイコンは, 半透明にしてカーソルの位置に描くよ
+                let ghost = model.icon_drag.filter(|&(index, _)| index == i).map(|(_, (x, y))| layout.centered_at(x, y));
+                let layout = ghost.as_ref().unwrap_or(layout);
+                let icon_opacity = if ghost.is_some() { 0.6 } else { 1.0 };
+
                 // ホバーや実行中のハイライト描画
                 if model.executing_index == Some(i) {
                     background::draw_rounded_rect(
                         context, &layout.hit_rect, &executing_bg_brush, Some(&executing_border_brush), 1.5, 4.0,
                     );
+                } else if model.selected.contains(&i) {
+                    background::draw_rounded_rect(
+                        context, &layout.hit_rect, &selected_bg_brush, Some(&selected_border_brush), 1.0, 4.0,
+                    );
                 } else if model.hovered_index == Some(i) {
                     background::draw_rounded_rect(
                         context, &layout.hit_rect, &highlight_bg_brush, Some(&highlight_border_brush), 1.0, 4.0,
                     );
                 }
+                // 矢印キーで選んでいるアイコンは, ホバーとは別にはっきりした枠で囲むよ
+                if model.focused_icon == Some(i) && ghost.is_none() {
+                    background::draw_rounded_border(context, &layout.hit_rect, &focus_ring_brush, 2.0, 4.0);
+                }
+
+                // 名前を編集中のアイコンは, 入力途中の文字を枠付きで出すよ
+                let label_text = match model.name_draft.as_ref().filter(|_| model.renaming_icon == Some(i)) {
+                    Some(draft) => {
+                        let edit_brush = resources.get_brush(context, if is_dark { "#FFFFFF22" } else { "#00000011" })?;
+                        background::draw_rounded_rect(context, &layout.text_rect, &edit_brush, Some(&border_brush), 1.0, 4.0);
+                        format!("{}|", draft)
+                    }
+                    None => icon_state.label().to_string(),
+                };
 
-                if icon_state.exists {
-                    // アイコンが存在する場合の通常描画
-                    if let Some(hicon) = shell::get_icon_for_path(&icon_state.path) {
-                        if let Ok(bitmap) = resources.get_icon_bitmap(context, hicon) {
-                            icon::draw_icon(context, &bitmap, &layout.icon_rect, 1.0);
+                // カーソルが止まっているアイコンの名前が省略されていたら (出していなければいつでも), 吹き出しに全部出すよ
+                if model.tooltip.is_some_and(|(index, _)| index == i)
+                    && ghost.is_none()
+                    && model.renaming_icon != Some(i)
+                    && (!show_labels || label::is_truncated(&resources.dwrite_factory, &label_text, &layout.text_rect, &format))
+                {
+                    tooltip_text = Some(format!("{}\n{}", label_text, icon_state.launch_target().display()));
+                }
+
+                // ホバー中のアニメーション画像はフレームを順番に描くよ
+                let animated_frame = if icon_state.exists && model.hovered_index == Some(i) && icon_state.is_animatable() {
+                    resources.get_animation_frame(context, &icon_state.path, model.animation_frame)
+                } else {
+                    None
+                };
+
+                if let Some(bitmap) = animated_frame {
+                    icon::draw_icon(context, &bitmap, &layout.icon_rect, icon_opacity, keep_icon_aspect, icon_filter_quality);
+                    if show_labels {
+                        label::draw_text(context, &label_text, &layout.text_rect, &icon_label_brush, &format);
+                    }
+                } else if icon_state.exists {
+                    // アイコンが存在する場合の通常描画 (自分で指定した画像があればそれを優先するよ)
+                    // 画像や動画はサムネイルを出せるなら, それを普通のアイコンより優先するね
+                    // ネットワーク上のファイルは, 固まらないように別スレッドで取り終わったアイコンだけを使うよ
+                    let custom_icon = match &icon_state.custom_icon_path {
+                        Some(path) => resources.get_image(context, path),
+                        None if icon_state.is_web_link() => None,
+                        None if icon_state.is_network => resources.get_loaded_icon(&icon_state.path),
+                        None if use_thumbnails && shell::has_thumbnail(&icon_state.path) => {
+                            resources.get_thumbnail(context, &icon_state.path)
                         }
-                        unsafe { DestroyIcon(hicon).ok(); }
+                        None => None,
+                    };
+                    let mut drawn = false;
+                    if let Some(bitmap) = custom_icon {
+                        icon::draw_icon(context, &bitmap, &layout.icon_rect, icon_opacity, keep_icon_aspect, icon_filter_quality);
+                        drawn = true;
+                    } else if icon_state.is_web_link() {
+                        // URL そのものには取ってこれるアイコンがないから, 地球の記号で描くよ
+                        icon::draw_link_icon(context, &layout.icon_rect, &font_family, resources)?;
+                        drawn = true;
+                    } else if icon_state.is_network {
+                        // まだ届いていない (か取れなかった) ネットワークのアイコンは, ここで取りに行かないよ
+                    } else if let Some(bitmap) = resources.get_file_icon(context, &icon_state.path, icon_state.modified) {
+                        icon::draw_icon(context, &bitmap, &layout.icon_rect, icon_opacity, keep_icon_aspect, icon_filter_quality);
+                        drawn = true;
+                    }
+                    // アイコンを取得できなかったときは代わりのものを描くよ
+                    if !drawn {
+                        icon::draw_placeholder_icon(
+                            context, &layout.icon_rect, placeholder_style, placeholder_image.as_deref(), bg_color, &font_family, resources,
+                        )?;
+                    }
+                    if show_labels {
+                        label::draw_text(context, &label_text, &layout.text_rect, &icon_label_brush, &format);
                     }
-                    label::draw_text(context, &icon_state.name, &layout.text_rect, &icon_label_brush, &format);
                 } else {
-                    // 存在しないアイコン: 背景色の反転色で四角を描画
-                    let (ir, ig, ib) = layout::invert_color(bg_color.r, bg_color.g, bg_color.b);
-                    let inv_hex = format!("#{:02X}{:02X}{:02X}FF", (ir * 255.0) as u8, (ig * 255.0) as u8, (ib * 255.0) as u8);
-                    let inv_brush = resources.get_brush(context, &inv_hex)?;
-                    
-                    background::draw_rounded_rect(context, &layout.icon_rect, &inv_brush, None, 0.0, 4.0);
+                    // 存在しないアイコン: 代わりのアイコンを描画
+                    icon::draw_placeholder_icon(
+                        context, &layout.icon_rect, placeholder_style, placeholder_image.as_deref(), bg_color, &font_family, resources,
+                    )?;
                     
+                    icon::draw_missing_badge(context, &layout.icon_rect, &font_family, resources)?;
+
                     // 警告色のラベルで強調
-                    let err_color_hex = layout::get_error_text_color(is_dark);
-                    let err_brush = resources.get_brush(context, err_color_hex)?;
-                    label::draw_text(context, &icon_state.name, &layout.text_rect, &err_brush, &format);
+                    if show_labels {
+                        let err_color_hex = layout::get_error_text_color(is_dark);
+                        let err_brush = resources.get_brush(context, err_color_hex)?;
+                        label::draw_text(context, &label_text, &layout.text_rect, &err_brush, &format);
+                    }
+                }
+
+                // ネットワーク上のファイルには, 左下に小さな印を付けるよ
+                if icon_state.is_network {
+                    icon::draw_network_badge(context, &layout.icon_rect, resources)?;
+                }
+
+                // フォルダには, 右上に中身の数を出すよ (数えるのはアイコンの状態を作るときと, 時々だけだね)
+                if let Some(count) = icon_state.folder_count {
+                    icon::draw_folder_count_badge(context, &layout.icon_rect, count, &font_family, resources)?;
                 }
             }
         }
     }
 
+    unsafe { context.PopAxisAlignedClip(); }
+
+    // 3. 範囲選択の矩形
+    if let Some((left, top, right, bottom)) = model.selection_rect {
+        let fill_brush = resources.get_brush(context, "#3399FF22")?;
+        let border = resources.get_brush(context, "#3399FFCC")?;
+        let rect = D2D_RECT_F { left, top, right, bottom };
+        background::draw_rounded_rect(context, &rect, &fill_brush, Some(&border), 1.0, 0.0);
+    }
+
+    // 3.5 アイコン数のバッジ (描くだけで, クリックなどの判定には関わらないよ)
+    if crate::settings::manager::get_settings_reader().app.show_count_badge {
+        let count = model.icons.len().to_string();
+        let badge_height = font_size * 1.6;
+        let badge_width = (count.chars().count() as f32 * font_size * 0.7 + font_size).max(badge_height);
+        let rect = D2D_RECT_F {
+            left: width - layout::PADDING - badge_width,
+            top: layout::PADDING,
+            right: width - layout::PADDING,
+            bottom: layout::PADDING + badge_height,
+        };
+        let badge_brush = resources.get_brush(context, if is_dark { "#FFFFFF33" } else { "#00000022" })?;
+        let text_brush = resources.get_brush(context, text_color_hex)?;
+        let format = resources.get_text_format(&font_family, font_size, label_weight)?;
+        background::draw_rounded_rect(context, &rect, &badge_brush, None, 0.0, badge_height / 2.0);
+        label::draw_text(context, &count, &rect, &text_brush, &format);
+    }
+
+    // 3.8 省略された名前の吹き出し (カーソルの右下に出して, はみ出すなら内側に寄せるよ)
+    if let (Some(text), Some((_, (x, y)))) = (tooltip_text, model.tooltip) {
+        let format = resources.get_wrapped_text_format(&font_family, font_size, label_weight)?;
+        let padding = layout::PADDING;
+        let max_width = (width - padding * 4.0).max(font_size);
+        let wide_text: Vec<u16> = text.encode_utf16().collect();
+        let text_layout = unsafe { resources.dwrite_factory.CreateTextLayout(&wide_text, &format, max_width, height)? };
+        let mut metrics = DWRITE_TEXT_METRICS::default();
+        unsafe {
+            text_layout.SetTextAlignment(DWRITE_TEXT_ALIGNMENT_LEADING)?;
+            text_layout.GetMetrics(&mut metrics)?;
+        }
+
+        let (box_width, box_height) = (metrics.width + padding * 2.0, metrics.height + padding * 2.0);
+        let left = (x + 12.0).min(width - box_width).max(0.0);
+        let top = if y + 20.0 + box_height <= height { y + 20.0 } else { (y - box_height - 4.0).max(0.0) };
+        let rect = D2D_RECT_F { left, top, right: left + box_width, bottom: top + box_height };
+        let box_brush = resources.get_brush(context, "#000000DD")?;
+        let text_brush = resources.get_brush(context, "#FFFFFFFF")?;
+        background::draw_rounded_rect(context, &rect, &box_brush, None, 0.0, 4.0);
+        unsafe {
+            context.DrawTextLayout(
+                D2D_POINT_2F { x: left + padding, y: top + padding },
+                &text_layout,
+                &text_brush,
+                windows::Win32::Graphics::Direct2D::D2D1_DRAW_TEXT_OPTIONS_ENABLE_COLOR_FONT,
+            );
+        }
+    }
+
+    // 3.9 ロック中の印 (右下の隅に小さな鍵を出すよ)
+    if !model.lock.is_empty() {
+        let size = font_size * 1.6;
+        let rect = D2D_RECT_F {
+            left: width - layout::PADDING - size,
+            top: height - layout::PADDING - size,
+            right: width - layout::PADDING,
+            bottom: height - layout::PADDING,
+        };
+        let lock_brush = resources.get_brush(context, text_color_hex)?;
+        let format = resources.get_text_format(&font_family, font_size, label_weight)?;
+        label::draw_text(context, "\u{1F512}", &rect, &lock_brush, &format);
+    }
+
+    // 4. 一時的なメッセージ (背景モードの切り替えなど) を下端に重ねるよ
+    if let Some(toast) = &model.toast {
+        let format = resources.get_text_format(&font_family, font_size, label_weight)?;
+        let box_height = font_size * 2.0;
+
+        let rect = D2D_RECT_F {
+            left: layout::PADDING * 2.0,
+            top: (height - box_height - layout::PADDING * 2.0).max(0.0),
+            right: width - layout::PADDING * 2.0,
+            bottom: height - layout::PADDING * 2.0,
+        };
+        let box_brush = resources.get_brush(context, "#000000AA")?;
+        let text_brush = resources.get_brush(context, "#FFFFFFFF")?;
+        background::draw_rounded_rect(context, &rect, &box_brush, None, 0.0, 4.0);
+        label::draw_text(context, toast, &rect, &text_brush, &format);
+    }
+
     Ok(())
 }
+
+/// 設定の色 (custom) が空なら, 代わりの色 (fallback) を使うよ。
+fn color_or<'a>(custom: &'a str, fallback: &'a str) -> &'a str {
+    if custom.is_empty() { fallback } else { custom }
+}
@@ -1,42 +1,129 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{Receiver, Sender};
+use std::time::SystemTime;
 use windows::core::ComInterface;
 use windows::Win32::Graphics::{
-    Direct2D::{ID2D1DeviceContext, ID2D1SolidColorBrush, ID2D1RenderTarget, ID2D1Bitmap},
-    Direct2D::Common::{D2D1_COLOR_F},
-    DirectWrite::{IDWriteTextFormat, IDWriteFactory1, DWRITE_FONT_WEIGHT_NORMAL, DWRITE_FONT_STYLE_NORMAL, DWRITE_FONT_STRETCH_NORMAL},
+    Direct2D::{
+        ID2D1DeviceContext, ID2D1SolidColorBrush, ID2D1RenderTarget, ID2D1Bitmap, ID2D1LinearGradientBrush,
+        D2D1_EXTEND_MODE_CLAMP, D2D1_GAMMA_2_2, D2D1_GRADIENT_STOP, D2D1_LINEAR_GRADIENT_BRUSH_PROPERTIES,
+    },
+    Direct2D::Common::{D2D1_COLOR_F, D2D_POINT_2F},
+    DirectWrite::{IDWriteTextFormat, IDWriteTextFormat1, IDWriteFactory1, IDWriteFactory2, IDWriteFontFallback, DWRITE_UNICODE_RANGE, DWRITE_FONT_WEIGHT, DWRITE_FONT_WEIGHT_NORMAL, DWRITE_FONT_STYLE_NORMAL, DWRITE_FONT_STRETCH_NORMAL},
     Imaging::IWICImagingFactory,
 };
+use windows::Win32::Foundation::HWND;
 use windows::Win32::UI::WindowsAndMessaging::HICON;
 use crate::graphics::api::wic;
+use crate::win32::api::shell::{self, LoadedIcon};
+use crate::settings::models::FontWeight;
 
 /// 描画リソース (ブラシやテキストフォーマット, ビットマップ) を管理するよ！
 /// リソースの生成とキャッシュに責任を持つよ。
 pub struct DrawingResources {
     brushes: HashMap<String, ID2D1SolidColorBrush>,
-    bitmaps: HashMap<usize, ID2D1Bitmap>,
+    gradient: Option<([u32; 8], ID2D1LinearGradientBrush)>, // 最後に作ったグラデーション (2色をキーにするよ)
+    file_icons: HashMap<PathBuf, (Option<SystemTime>, Option<ID2D1Bitmap>)>, // (取ったときの更新日時, アイコン) をパスごとに
+    loading_icons: HashSet<PathBuf>, // 別スレッドで取っている途中のアイコン (キーは file_icons と同じ)
+    icon_sender: Sender<LoadedIcon>, // 別スレッドに渡す送り口 (取り始めるたびに複製するよ)
+    icon_receiver: Receiver<LoadedIcon>,
+    animations: HashMap<PathBuf, Vec<ID2D1Bitmap>>, // ファイルから読み込んだ画像の全フレーム (読み込み失敗時は空)
+    thumbnails: HashMap<PathBuf, Option<ID2D1Bitmap>>, // シェルから取ったサムネイル (取れなかったら None)
     pub dwrite_factory: IDWriteFactory1,
     wic_factory: IWICImagingFactory,
     text_format: Option<IDWriteTextFormat>,
     help_text_format: Option<IDWriteTextFormat>,
+    header_text_formats: Vec<(String, f32, FontWeight, IDWriteTextFormat)>, // (フォント名, サイズ, 太さ, フォーマット)
+    glyph_text_format: Option<(String, f32, IDWriteTextFormat)>, // 代わりのアイコンに描く記号用
+    wrapped_text_format: Option<(String, f32, FontWeight, IDWriteTextFormat)>, // 2行まで折り返すアイコン名用
     current_font_family: String,
     current_font_size: f32,
+    current_font_weight: FontWeight,
+    fallback_font_family: String,
+    font_fallback: Option<IDWriteFontFallback>, // fallback_font_family を先に試すフォールバック (作れなければ OS 任せ)
 }
 
+/// 見出し用のテキストフォーマットをいくつまで覚えておくかだよ。
+const HEADER_TEXT_FORMAT_CACHE: usize = 4;
+
+/// fallback_font_family に任せる文字の範囲 (記号・絵文字) だよ。
+const FALLBACK_RANGES: [DWRITE_UNICODE_RANGE; 3] = [
+    DWRITE_UNICODE_RANGE { first: 0x2190, last: 0x2BFF },   // 矢印・記号・装飾記号
+    DWRITE_UNICODE_RANGE { first: 0xFE0E, last: 0xFE0F },   // 異体字セレクタ (絵文字表示の指定)
+    DWRITE_UNICODE_RANGE { first: 0x1F000, last: 0x1FAFF }, // 絵文字
+];
+
 impl DrawingResources {
     /// エンジンが保持するファクトリを受け取って初期化するよ。
     pub fn new(dwrite_factory: IDWriteFactory1, wic_factory: IWICImagingFactory) -> Self {
+        let (icon_sender, icon_receiver) = std::sync::mpsc::channel();
         Self {
             brushes: HashMap::new(),
-            bitmaps: HashMap::new(),
+            gradient: None,
+            file_icons: HashMap::new(),
+            loading_icons: HashSet::new(),
+            icon_sender,
+            icon_receiver,
+            animations: HashMap::new(),
+            thumbnails: HashMap::new(),
             dwrite_factory,
             wic_factory,
             text_format: None,
             help_text_format: None,
+            header_text_formats: Vec::new(),
+            glyph_text_format: None,
+            wrapped_text_format: None,
             current_font_family: String::new(),
             current_font_size: 0.0,
+            current_font_weight: FontWeight::default(),
+            fallback_font_family: String::new(),
+            font_fallback: None,
         }
     }
 // ... (中略)
+    /// font_family に無い文字 (絵文字など) を描くときに, 先に試すフォントを設定するよ。
+    /// 変わったときは作り済みのテキストフォーマットを捨てて, 次に取得するときに付け直すね。
+    pub fn set_fallback_font_family(&mut self, family: &str) {
+        if self.fallback_font_family == family {
+            return;
+        }
+        self.fallback_font_family = family.to_string();
+        self.font_fallback = if family.is_empty() { None } else { self.create_font_fallback(family) };
+        self.text_format = None;
+        self.header_text_formats.clear();
+        self.wrapped_text_format = None;
+    }
+
+    /// family を記号・絵文字の範囲で先に試して, それ以外は OS の既定のフォールバックに任せるよ。
+    fn create_font_fallback(&self, family: &str) -> Option<IDWriteFontFallback> {
+        let family_wide = crate::win32::api::utils::to_wide(family);
+        let result: Result<IDWriteFontFallback, windows::core::Error> = unsafe {
+            self.dwrite_factory.cast::<IDWriteFactory2>().and_then(|factory| {
+                let builder = factory.CreateFontFallbackBuilder()?;
+                builder.AddMapping(
+                    &FALLBACK_RANGES,
+                    &[family_wide.as_ptr()],
+                    None,
+                    windows::core::PCWSTR::null(),
+                    windows::core::PCWSTR::null(),
+                    1.0,
+                )?;
+                builder.AddMappings(&factory.GetSystemFontFallback()?)?;
+                builder.CreateFontFallback()
+            })
+        };
+        result
+            .map_err(|e| log::warn!("Failed to create font fallback for {}: {}", family, e))
+            .ok()
+    }
+
+    /// テキストフォーマットに, 設定済みのフォールバックを付けるよ (古い OS で付けられなくても気にしないよ)。
+    fn apply_font_fallback(&self, format: &IDWriteTextFormat) {
+        if let Some(fallback) = &self.font_fallback && let Ok(format1) = format.cast::<IDWriteTextFormat1>() {
+            unsafe { let _ = format1.SetFontFallback(fallback); }
+        }
+    }
+
     /// ヘルプ用のテキストフォーマットを取得するよ (折り返しあり)
     pub fn get_help_text_format(&mut self, font_family: &str, font_size: f32) -> Result<IDWriteTextFormat, windows::core::Error> {
         if let Some(format) = &self.help_text_format {
@@ -68,6 +155,136 @@ impl DrawingResources {
         Ok(format)
     }
 
+    /// セクション見出し用のテキストフォーマットを取得するよ (左寄せ・1行・省略記号あり)
+    /// リスト表示のラベルにも使うから, 太さ違いをいくつか覚えておくね。
+    pub fn get_header_text_format(
+        &mut self,
+        font_family: &str,
+        font_size: f32,
+        font_weight: FontWeight,
+    ) -> Result<IDWriteTextFormat, windows::core::Error> {
+        if let Some((_, _, _, format)) = self
+            .header_text_formats
+            .iter()
+            .find(|(family, size, weight, _)| family == font_family && *size == font_size && *weight == font_weight)
+        {
+            return Ok(format.clone());
+        }
+
+        let family_wide = crate::win32::api::utils::to_wide(font_family);
+        let format: IDWriteTextFormat = unsafe {
+            let f = self.dwrite_factory.CreateTextFormat(
+                windows::core::PCWSTR::from_raw(family_wide.as_ptr()),
+                None,
+                DWRITE_FONT_WEIGHT(font_weight.value()),
+                DWRITE_FONT_STYLE_NORMAL,
+                DWRITE_FONT_STRETCH_NORMAL,
+                font_size,
+                windows::core::w!("ja-jp"),
+            )?;
+            f.SetTextAlignment(windows::Win32::Graphics::DirectWrite::DWRITE_TEXT_ALIGNMENT_LEADING)?;
+            f.SetParagraphAlignment(windows::Win32::Graphics::DirectWrite::DWRITE_PARAGRAPH_ALIGNMENT_CENTER)?;
+            f.SetWordWrapping(windows::Win32::Graphics::DirectWrite::DWRITE_WORD_WRAPPING_NO_WRAP)?;
+
+            let trimming = windows::Win32::Graphics::DirectWrite::DWRITE_TRIMMING {
+                granularity: windows::Win32::Graphics::DirectWrite::DWRITE_TRIMMING_GRANULARITY_CHARACTER,
+                delimiter: 0,
+                delimiterCount: 0,
+            };
+            let sign = self.dwrite_factory.CreateEllipsisTrimmingSign(&f)?;
+            f.SetTrimming(&trimming, Some(&sign))?;
+
+            f
+        };
+
+        self.apply_font_fallback(&format);
+        // 使うのはタイトル用とラベル用くらいだから, 古いものから捨てるよ
+        if self.header_text_formats.len() >= HEADER_TEXT_FORMAT_CACHE {
+            self.header_text_formats.remove(0);
+        }
+        self.header_text_formats.push((font_family.to_string(), font_size, font_weight, format.clone()));
+        Ok(format)
+    }
+
+    /// アイコン名を2行まで折り返して描くためのテキストフォーマットを取得するよ (中央寄せ・上詰め)
+    /// 単語の区切りが無いファイル名でも, 入りきる文字のところで折り返すね。
+    /// 2行目にも収まらないときは, 2行目の最後だけ省略記号にするよ。
+    pub fn get_wrapped_text_format(
+        &mut self,
+        font_family: &str,
+        font_size: f32,
+        font_weight: FontWeight,
+    ) -> Result<IDWriteTextFormat, windows::core::Error> {
+        if let Some((family, size, weight, format)) = &self.wrapped_text_format
+            && family == font_family && *size == font_size && *weight == font_weight
+        {
+            return Ok(format.clone());
+        }
+
+        let family_wide = crate::win32::api::utils::to_wide(font_family);
+        let format: IDWriteTextFormat = unsafe {
+            let f = self.dwrite_factory.CreateTextFormat(
+                windows::core::PCWSTR::from_raw(family_wide.as_ptr()),
+                None,
+                DWRITE_FONT_WEIGHT(font_weight.value()),
+                DWRITE_FONT_STYLE_NORMAL,
+                DWRITE_FONT_STRETCH_NORMAL,
+                font_size,
+                windows::core::w!("ja-jp"),
+            )?;
+            f.SetTextAlignment(windows::Win32::Graphics::DirectWrite::DWRITE_TEXT_ALIGNMENT_CENTER)?;
+            f.SetParagraphAlignment(windows::Win32::Graphics::DirectWrite::DWRITE_PARAGRAPH_ALIGNMENT_NEAR)?;
+            f.SetWordWrapping(windows::Win32::Graphics::DirectWrite::DWRITE_WORD_WRAPPING_CHARACTER)?;
+            // 行の高さをレイアウトの1行分 (フォントサイズの 1.5 倍) にそろえて, 2行がちょうど収まるようにするよ
+            f.SetLineSpacing(
+                windows::Win32::Graphics::DirectWrite::DWRITE_LINE_SPACING_METHOD_UNIFORM,
+                font_size * crate::graphics::layout::LABEL_LINE_HEIGHT_RATIO,
+                font_size * 1.15,
+            )?;
+
+            // 折り返しても入りきらないときは, 見えている最後の行を省略記号で終えるよ
+            let trimming = windows::Win32::Graphics::DirectWrite::DWRITE_TRIMMING {
+                granularity: windows::Win32::Graphics::DirectWrite::DWRITE_TRIMMING_GRANULARITY_CHARACTER,
+                delimiter: 0,
+                delimiterCount: 0,
+            };
+            let sign = self.dwrite_factory.CreateEllipsisTrimmingSign(&f)?;
+            f.SetTrimming(&trimming, Some(&sign))?;
+
+            f
+        };
+
+        self.apply_font_fallback(&format);
+        self.wrapped_text_format = Some((font_family.to_string(), font_size, font_weight, format.clone()));
+        Ok(format)
+    }
+
+    /// アイコンの代わりに描く記号 (「?」) 用のテキストフォーマットを取得するよ (中央寄せ・太字)
+    pub fn get_glyph_text_format(&mut self, font_family: &str, font_size: f32) -> Result<IDWriteTextFormat, windows::core::Error> {
+        if let Some((family, size, format)) = &self.glyph_text_format && family == font_family && *size == font_size {
+            return Ok(format.clone());
+        }
+
+        let family_wide = crate::win32::api::utils::to_wide(font_family);
+        let format: IDWriteTextFormat = unsafe {
+            let f = self.dwrite_factory.CreateTextFormat(
+                windows::core::PCWSTR::from_raw(family_wide.as_ptr()),
+                None,
+                windows::Win32::Graphics::DirectWrite::DWRITE_FONT_WEIGHT_BOLD,
+                DWRITE_FONT_STYLE_NORMAL,
+                DWRITE_FONT_STRETCH_NORMAL,
+                font_size,
+                windows::core::w!("ja-jp"),
+            )?;
+            f.SetTextAlignment(windows::Win32::Graphics::DirectWrite::DWRITE_TEXT_ALIGNMENT_CENTER)?;
+            f.SetParagraphAlignment(windows::Win32::Graphics::DirectWrite::DWRITE_PARAGRAPH_ALIGNMENT_CENTER)?;
+            f
+        };
+
+        self.glyph_text_format = Some((font_family.to_string(), font_size, format.clone()));
+        Ok(format)
+    }
+
     /// 指定されたカラーコードからブラシを取得するよ。
     pub fn get_brush(
         &mut self,
@@ -88,11 +305,55 @@ impl DrawingResources {
         Ok(brush)
     }
 
+    /// 上の色から下の色へのグラデーションブラシを取得するよ。
+    /// 色が変わらない限り作り直さないから, ホバーなどの再描画では使い回すね。
+    pub fn get_gradient_brush(
+        &mut self,
+        context: &ID2D1DeviceContext,
+        top_color: D2D1_COLOR_F,
+        bottom_color: D2D1_COLOR_F,
+    ) -> Result<ID2D1LinearGradientBrush, windows::core::Error> {
+        let key = [
+            top_color.r, top_color.g, top_color.b, top_color.a,
+            bottom_color.r, bottom_color.g, bottom_color.b, bottom_color.a,
+        ]
+        .map(f32::to_bits);
+        if let Some((cached_key, brush)) = &self.gradient && *cached_key == key {
+            return Ok(brush.clone());
+        }
+
+        let stops = [
+            D2D1_GRADIENT_STOP { position: 0.0, color: top_color },
+            D2D1_GRADIENT_STOP { position: 1.0, color: bottom_color },
+        ];
+        // 向きは描画のたびに矩形に合わせて設定し直すよ
+        let properties = D2D1_LINEAR_GRADIENT_BRUSH_PROPERTIES {
+            startPoint: D2D_POINT_2F { x: 0.0, y: 0.0 },
+            endPoint: D2D_POINT_2F { x: 0.0, y: 1.0 },
+        };
+        let brush = unsafe {
+            // ID2D1DeviceContext 側の同名メソッドと区別するために, RenderTarget として呼ぶよ
+            let rt: ID2D1RenderTarget = context.cast()?;
+            let collection = rt.CreateGradientStopCollection(&stops, D2D1_GAMMA_2_2, D2D1_EXTEND_MODE_CLAMP)?;
+            rt.CreateLinearGradientBrush(&properties, None, &collection)?
+        };
+        self.gradient = Some((key, brush.clone()));
+        Ok(brush)
+    }
+
     /// デフォルトのテキストフォーマットを取得するよ。
-    pub fn get_text_format(&mut self, font_family: &str, font_size: f32) -> Result<IDWriteTextFormat, windows::core::Error> {
+    pub fn get_text_format(
+        &mut self,
+        font_family: &str,
+        font_size: f32,
+        font_weight: FontWeight,
+    ) -> Result<IDWriteTextFormat, windows::core::Error> {
         // フォント情報が変わっていない場合はキャッシュを返すよ
         if let Some(format) = &self.text_format {
-            if self.current_font_family == font_family && self.current_font_size == font_size {
+            if self.current_font_family == font_family
+                && self.current_font_size == font_size
+                && self.current_font_weight == font_weight
+            {
                 return Ok(format.clone());
             }
         }
@@ -103,7 +364,7 @@ impl DrawingResources {
             let f = self.dwrite_factory.CreateTextFormat(
                 windows::core::PCWSTR::from_raw(family_wide.as_ptr()),
                 None,
-                DWRITE_FONT_WEIGHT_NORMAL,
+                DWRITE_FONT_WEIGHT(font_weight.value()),
                 DWRITE_FONT_STYLE_NORMAL,
                 DWRITE_FONT_STRETCH_NORMAL,
                 font_size,
@@ -126,27 +387,143 @@ impl DrawingResources {
             f
         };
 
+        self.apply_font_fallback(&format);
         self.text_format = Some(format.clone());
         self.current_font_family = font_family.to_string();
         self.current_font_size = font_size;
+        self.current_font_weight = font_weight;
         Ok(format)
     }
 
-    /// HICON から ID2D1Bitmap を取得 (キャッシュ付き)
-    pub fn get_icon_bitmap(
+    /// ファイルのアイコンをシェルから取って ID2D1Bitmap にするよ (キャッシュ付き)。取れなければ None だよ。
+    /// 描画のたびにシェルへ問い合わせると重いから, パス (大文字小文字は区別しない) ごとに覚えておくね。
+    /// 更新日時 (modified) が覚えたときと違っていたら, アイコンも変わったかもしれないから取り直すよ。
+    /// 更新日時は描画のたびに調べると重いから, モデルが時々調べたものを渡してね。
+    /// 別スレッドで取っている途中なら, 待たずに None を返すよ。
+    pub fn get_file_icon(&mut self, context: &ID2D1DeviceContext, path: &Path, modified: Option<SystemTime>) -> Option<ID2D1Bitmap> {
+        let key = icon_cache_key(path);
+        if self.loading_icons.contains(&key) {
+            return None;
+        }
+        if let Some((cached_modified, bitmap)) = self.file_icons.get(&key) && *cached_modified == modified {
+            return bitmap.clone();
+        }
+
+        let bitmap = shell::get_icon_for_path(path).and_then(|hicon| self.icon_to_bitmap(context, path, hicon));
+        self.file_icons.insert(key, (modified, bitmap.clone()));
+        bitmap
+    }
+
+    /// paths のアイコンを別スレッドで取り始めるよ。届くたびに hwnd に WM_ICONS_LOADED が来るから,
+    /// receive_file_icons で取り込んでね。それまでは代わりのアイコンを描くことになるよ。
+    /// いま取っている途中のパスは, もう一度は頼まないよ。
+    pub fn load_file_icons(&mut self, hwnd: HWND, paths: Vec<PathBuf>) {
+        let paths: Vec<PathBuf> = paths
+            .into_iter()
+            .filter(|path| self.loading_icons.insert(icon_cache_key(path)))
+            .collect();
+        if paths.is_empty() {
+            return;
+        }
+        shell::load_icons_in_background(hwnd, paths, self.icon_sender.clone());
+    }
+
+    /// 別スレッドから届いたアイコンをキャッシュに取り込むよ。
+    /// 届いたパスと, そのときファイルがあったかどうかを返すね (ネットワーク上のファイルの存在確認の結果だよ)。
+    pub fn receive_file_icons(&mut self, context: &ID2D1DeviceContext) -> Vec<(PathBuf, bool)> {
+        let loaded: Vec<LoadedIcon> = self.icon_receiver.try_iter().collect();
+        let mut received = Vec::with_capacity(loaded.len());
+        for LoadedIcon { path, exists, modified, hicon } in loaded {
+            let key = icon_cache_key(&path);
+            self.loading_icons.remove(&key);
+            let bitmap = hicon.and_then(|hicon| self.icon_to_bitmap(context, &path, hicon));
+            self.file_icons.insert(key, (modified, bitmap));
+            received.push((path, exists));
+        }
+        received
+    }
+
+    /// 別スレッドで取り終わったアイコンだけを返すよ。まだ届いていなければ None で, ここでは取りに行かないね。
+    /// ネットワーク上のファイルは UI スレッドで触ると固まることがあるから, こっちを使ってね。
+    pub fn get_loaded_icon(&self, path: &Path) -> Option<ID2D1Bitmap> {
+        self.file_icons.get(&icon_cache_key(path)).and_then(|(_, bitmap)| bitmap.clone())
+    }
+
+    /// path のアイコンが (取れなかった場合も含めて) もう届いているかどうかだよ。
+    pub fn has_loaded_icon(&self, path: &Path) -> bool {
+        self.file_icons.contains_key(&icon_cache_key(path))
+    }
+
+    /// HICON をビットマップにして, HICON は片付けるよ。
+    fn icon_to_bitmap(&self, context: &ID2D1DeviceContext, path: &Path, hicon: HICON) -> Option<ID2D1Bitmap> {
+        let result = wic::create_bitmap_from_hicon(context, &self.wic_factory, hicon);
+        unsafe { windows::Win32::UI::WindowsAndMessaging::DestroyIcon(hicon).ok(); }
+        result
+            .map_err(|e| log::debug!("Failed to convert icon of {:?}: {}", path, e))
+            .ok()
+    }
+
+    /// アニメーション画像の frame 番目のビットマップを返すよ (フレーム数で折り返すね)。
+    /// 1フレームしかない画像や読み込めない画像は None だから, 通常のアイコン描画に任せてね。
+    pub fn get_animation_frame(
         &mut self,
         context: &ID2D1DeviceContext,
-        hicon: HICON,
-    ) -> Result<ID2D1Bitmap, windows::core::Error> {
-        let key = hicon.0 as usize;
-        if let Some(bitmap) = self.bitmaps.get(&key) {
-            return Ok(bitmap.clone());
+        path: &Path,
+        frame: usize,
+    ) -> Option<ID2D1Bitmap> {
+        let frames = self.image_frames(context, path);
+        if frames.len() < 2 {
+            return None;
         }
+        Some(frames[frame % frames.len()].clone())
+    }
 
-        let bitmap = wic::create_bitmap_from_hicon(context, &self.wic_factory, hicon)?;
-        self.bitmaps.insert(key, bitmap.clone());
-        Ok(bitmap)
+    /// アイコンや画像から作ったビットマップのキャッシュを捨てるよ。
+    /// 次の描画でシェルやファイルから取り直すことになるんだ。
+    pub fn clear_bitmaps(&mut self) {
+        self.file_icons.clear();
+        self.animations.clear();
+        self.thumbnails.clear();
     }
+
+    /// ファイルのサムネイル (画像や動画の中身) を返すよ。作れないファイルは None だよ。
+    /// シェルへの問い合わせは重いから, 結果は取れなかったことも含めてキャッシュするね。
+    pub fn get_thumbnail(&mut self, context: &ID2D1DeviceContext, path: &Path) -> Option<ID2D1Bitmap> {
+        const THUMBNAIL_SIZE: i32 = 256;
+        if let Some(bitmap) = self.thumbnails.get(path) {
+            return bitmap.clone();
+        }
+
+        let bitmap = shell::get_thumbnail_for_path(path, THUMBNAIL_SIZE).and_then(|hbitmap| {
+            let result = wic::create_bitmap_from_hbitmap(context, &self.wic_factory, hbitmap);
+            unsafe { windows::Win32::Graphics::Gdi::DeleteObject(hbitmap); }
+            result
+                .map_err(|e| log::debug!("Failed to convert thumbnail of {:?}: {}", path, e))
+                .ok()
+        });
+        self.thumbnails.insert(path.to_path_buf(), bitmap.clone());
+        bitmap
+    }
+
+    /// 画像ファイル (背景画像など) の最初のフレームを返すよ。読み込めなければ None だよ。
+    pub fn get_image(&mut self, context: &ID2D1DeviceContext, path: &Path) -> Option<ID2D1Bitmap> {
+        self.image_frames(context, path).first().cloned()
+    }
+
+    /// 画像ファイルを読み込んで, フレームをキャッシュするよ。
+    fn image_frames(&mut self, context: &ID2D1DeviceContext, path: &Path) -> &Vec<ID2D1Bitmap> {
+        self.animations.entry(path.to_path_buf()).or_insert_with(|| {
+            wic::create_bitmaps_from_file(context, &self.wic_factory, path).unwrap_or_else(|e| {
+                log::debug!("Failed to load image {:?}: {}", path, e);
+                Vec::new()
+            })
+        })
+    }
+}
+
+/// アイコンのキャッシュのキーだよ。Windows のパスは大文字小文字を区別しないからそろえておくね。
+fn icon_cache_key(path: &Path) -> PathBuf {
+    PathBuf::from(path.to_string_lossy().to_lowercase())
 }
 
 /// "#RRGGBBAA" または "#RRGGBB" 形式の文字列を D2D1_COLOR_F に変換するよ
@@ -1,4 +1,5 @@
 use windows::Win32::Graphics::Direct2D::Common::D2D_RECT_F;
+use crate::settings::models::{LayoutMode, RowAlign};
 
 /// アイコン1つあたりのレイアウト情報だよ！
 pub struct ItemLayout {
@@ -7,69 +8,372 @@ pub struct ItemLayout {
     pub hit_rect: D2D_RECT_F, // ホバー判定やドラッグ開始判定に使うよ
 }
 
+impl ItemLayout {
+    /// アイコンの中心が (x, y) に来るように, 全体を平行移動したものだよ (ドラッグ中の表示用)。
+    pub fn centered_at(&self, x: f32, y: f32) -> Self {
+        let dx = x - (self.icon_rect.left + self.icon_rect.right) / 2.0;
+        let dy = y - (self.icon_rect.top + self.icon_rect.bottom) / 2.0;
+        self.shifted(dx, dy)
+    }
+
+    /// 全体を (dx, dy) だけ平行移動したものだよ。
+    pub fn shifted(&self, dx: f32, dy: f32) -> Self {
+        let shift = |rect: &D2D_RECT_F| D2D_RECT_F {
+            left: rect.left + dx,
+            top: rect.top + dy,
+            right: rect.right + dx,
+            bottom: rect.bottom + dy,
+        };
+        Self { icon_rect: shift(&self.icon_rect), text_rect: shift(&self.text_rect), hit_rect: shift(&self.hit_rect) }
+    }
+}
+
+/// セクション見出し1つ分のレイアウト情報だよ！
+pub struct SectionHeaderLayout {
+    pub name: String,
+    pub rect: D2D_RECT_F,
+}
+
+/// グループ全体のレイアウト (アイコン + セクション見出し) だよ。
+pub struct GroupLayout {
+    pub items: Vec<ItemLayout>,
+    pub headers: Vec<SectionHeaderLayout>,
+    pub columns: usize, // 1 ならリスト状 (縦1列) に並んでいるよ
+    pub title: Option<D2D_RECT_F>, // グループのタイトル行 (タイトルが無ければ None)
+}
+
+/// レイアウト計算に必要なパラメータをまとめたものだよ。
+pub struct LayoutParams {
+    pub icon_size: f32,
+    pub font_size: f32,
+    pub scale_factor: f32, // 将来的に DPI スケーリングに対応するための予約
+    pub title_height: f32, // 先頭のタイトル行の高さ (0.0 ならタイトル行なし)
+    pub grid_gap: f32, // セルとセルの間の隙間
+    pub mode: LayoutMode, // グリッドに並べるか, 1行ずつのリストにするか
+    pub label_lines: u8, // グリッド表示のアイコン名を何行まで使うか (1 か 2, リスト表示は常に1行。0 なら名前を出さない)
+    pub row_align: RowAlign, // 埋まりきらない行の寄せ方 (グリッド表示だけ)
+}
+
 pub const PADDING: f32 = 4.0;
 pub const TEXT_HEIGHT_RATIO: f32 = 0.4; // アイコンサイズに対するテキスト高さの比率
+pub const HEADER_HEIGHT_RATIO: f32 = 1.8; // フォントサイズに対する見出し行の高さの比率
+pub const LABEL_LINE_HEIGHT_RATIO: f32 = 1.5; // フォントサイズに対するアイコン名1行分の高さの比率 (行間に余裕を持たせる)
+
+impl GroupLayout {
+    /// 指定座標にあるアイコンのインデックスを返すよ (見出し行はヒットしないよ)
+    /// スクロールしてタイトル行の下に隠れたアイコンもヒットしないよ。
+    pub fn hit_item(&self, x: f32, y: f32) -> Option<usize> {
+        if y < self.content_top() {
+            return None;
+        }
+        self.items.iter().position(|item| contains(&item.hit_rect, x, y))
+    }
+
+    /// アイコンや見出しを並べ始める高さ (タイトル行のすぐ下) だよ。
+    pub fn content_top(&self) -> f32 {
+        self.title.map_or(PADDING, |rect| rect.bottom)
+    }
+
+    /// 中身 (タイトル・見出し・アイコン) の一番下に余白を足した, 全部を表示するのに必要な高さだよ。
+    pub fn content_height(&self) -> f32 {
+        self.items
+            .iter()
+            .map(|item| item.hit_rect.bottom)
+            .chain(self.headers.iter().map(|h| h.rect.bottom))
+            .chain(self.title.map(|rect| rect.bottom))
+            .fold(0.0f32, f32::max)
+            + PADDING
+    }
+
+    /// window_height の窓で, どこまでスクロールできるかだよ (全部見えているなら 0.0)。
+    pub fn max_scroll(&self, window_height: f32) -> f32 {
+        (self.content_height() - window_height).max(0.0)
+    }
+
+    /// アイコンと見出しを offset だけ上にずらすよ (タイトル行はそのまま)。
+    /// offset は 0.0 ~ max_scroll に丸めて, 実際にずらした量を返すね。
+    /// 大きくして全部収まるようになったら, 自然に 0.0 に戻るよ。
+    pub fn apply_scroll(&mut self, offset: f32, window_height: f32) -> f32 {
+        let offset = offset.clamp(0.0, self.max_scroll(window_height));
+        if offset > 0.0 {
+            for item in &mut self.items {
+                for rect in [&mut item.icon_rect, &mut item.text_rect, &mut item.hit_rect] {
+                    rect.top -= offset;
+                    rect.bottom -= offset;
+                }
+            }
+            for header in &mut self.headers {
+                header.rect.top -= offset;
+                header.rect.bottom -= offset;
+            }
+        }
+        offset
+    }
+
+    /// index のアイコンから, 矢印キーの向き (dx, dy) にある隣のアイコンを返すよ。端なら None だよ。
+    /// 左右は並び順で前後に, 上下はすぐ上 (下) の行で横の位置が一番近いものに動くね。
+    pub fn neighbor(&self, index: usize, dx: i32, dy: i32) -> Option<usize> {
+        let current = self.items.get(index)?;
+        if dy == 0 {
+            let next = index.checked_add_signed(dx.signum() as isize)?;
+            return (next < self.items.len()).then_some(next);
+        }
+
+        let (x, y) = center(&current.hit_rect);
+        let candidates: Vec<(usize, f32, f32)> = self
+            .items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| {
+                let (cx, cy) = center(&item.hit_rect);
+                (i, cx, cy)
+            })
+            .filter(|&(_, _, cy)| (cy - y) * dy.signum() as f32 > 0.5)
+            .collect();
+        let row_y = candidates
+            .iter()
+            .map(|&(_, _, cy)| cy)
+            .min_by(|a, b| (a - y).abs().total_cmp(&(b - y).abs()))?;
+        candidates
+            .into_iter()
+            .filter(|&(_, _, cy)| (cy - row_y).abs() < 0.5)
+            .min_by(|a, b| (a.1 - x).abs().total_cmp(&(b.1 - x).abs()))
+            .map(|(i, _, _)| i)
+    }
+
+    /// 指定座標がタイトル行の上かどうかだよ
+    pub fn hit_title(&self, x: f32, y: f32) -> bool {
+        self.title.as_ref().is_some_and(|rect| contains(rect, x, y))
+    }
+
+    /// 指定座標にあるセクション見出しを返すよ
+    pub fn hit_header(&self, x: f32, y: f32) -> Option<&SectionHeaderLayout> {
+        self.headers.iter().find(|header| contains(&header.rect, x, y))
+    }
+}
+
+fn contains(rect: &D2D_RECT_F, x: f32, y: f32) -> bool {
+    x >= rect.left && x <= rect.right && y >= rect.top && y <= rect.bottom
+}
+
+fn center(rect: &D2D_RECT_F) -> (f32, f32) {
+    ((rect.left + rect.right) / 2.0, (rect.top + rect.bottom) / 2.0)
+}
+
+/// アイコン1つ分のセルの大きさ (幅, 高さ) だよ。
+/// アイコンサイズとフォントサイズに基づいて決まるんだ。隙間 (grid_gap) の分も含むよ。
+/// リスト表示では幅いっぱいの1行になるから, 高さだけが意味を持つよ。
+pub fn cell_size(params: &LayoutParams) -> (f32, f32) {
+    // 名前を出すときは, 名前が入るように左右に余白を持たせるよ
+    let cell_width = params.icon_size + if params.label_lines == 0 { 12.0 } else { 42.0 };
+    let text_height = label_height(params);
+    let cell_height = match params.mode {
+        LayoutMode::Grid => params.icon_size + text_height + 12.0,
+        LayoutMode::List => params.icon_size.max(text_height) + 8.0,
+    };
+    (cell_width + params.grid_gap, cell_height + params.grid_gap)
+}
+
+/// アイコン名を置く高さだよ。グリッド表示で2行にするなら2行分, 名前を出さないなら 0 だね。
+fn label_height(params: &LayoutParams) -> f32 {
+    let lines = match params.mode {
+        LayoutMode::Grid => params.label_lines.min(2),
+        LayoutMode::List => params.label_lines.min(1),
+    };
+    params.font_size * LABEL_LINE_HEIGHT_RATIO * lines as f32
+}
+
+/// タイトル行の矩形だよ。タイトル行がないときは None だよ。
+pub fn title_rect(window_width: f32, params: &LayoutParams) -> Option<D2D_RECT_F> {
+    if params.title_height <= 0.0 {
+        return None;
+    }
+    Some(D2D_RECT_F {
+        left: PADDING,
+        top: PADDING,
+        right: (window_width - PADDING).max(PADDING),
+        bottom: PADDING + params.title_height,
+    })
+}
 
 /// グリッド配置（リフロー対応）を計算するよ！
 /// window_width に合わせて列数を自動調整するんだ。
-pub fn calculate_grid_layout(
+/// セクションが切り替わるところでは行を改めて, 見出し行を差し込むよ。
+/// リスト表示なら1列だけにして, アイコンの右に名前を左寄せで置くね。
+pub fn calculate_group_layout(
     window_width: f32,
-    item_count: usize,
-    icon_size: f32,
-    font_size: f32,
-    _scale_factor: f32, // 将来的に DPI スケーリングに対応するための予約
-) -> Vec<ItemLayout> {
-    let mut layouts = Vec::with_capacity(item_count);
+    sections: &[Option<&str>],
+    params: &LayoutParams,
+) -> GroupLayout {
+    let icon_size = params.icon_size;
+    let mut items = Vec::with_capacity(sections.len());
+    let mut headers = Vec::new();
     
     // アイコンサイズとフォントサイズに基づいてセルサイズを決定するよ
-    let cell_width = icon_size + 42.0; // 左右に余白を持たせる
-    let text_height = font_size * 1.5; // 行間に余裕を持たせる
-    let cell_height = icon_size + text_height + 12.0;
+    let (cell_width, cell_height) = cell_size(params);
+    let text_height = label_height(params);
+    let header_height = params.font_size * HEADER_HEIGHT_RATIO;
+
+    // アイコン本体の大きさ (セルから隙間を除いた部分) だよ
+    let item_width = cell_width - params.grid_gap;
+    let item_height = cell_height - params.grid_gap;
+
+    // 1列に何個入るか計算 (最低1列)。最後の列の後ろには隙間はいらないよ
+    let cols = match params.mode {
+        LayoutMode::Grid => ((window_width - PADDING + params.grid_gap) / cell_width).floor().max(1.0) as usize,
+        LayoutMode::List => 1,
+    };
+    let list_width = (window_width - PADDING * 2.0).max(item_width);
 
-    // 1列に何個入るか計算 (最低1列)
-    let cols = ((window_width - PADDING) / cell_width).floor().max(1.0) as usize;
+    let mut col = 0;
+    let title = title_rect(window_width, params);
+    let mut y = title.map_or(PADDING, |rect| rect.bottom);
+    let mut current_section: Option<&str> = None;
+    let mut row_start = 0;
+
+    // 埋まりきらなかった行を, 空いたセルの半分だけ右にずらして真ん中に寄せるよ
+    let align_row = |items: &mut [ItemLayout], filled: usize| {
+        if params.row_align == RowAlign::Center && filled < cols {
+            let offset = (cols - filled) as f32 * cell_width / 2.0;
+            for item in items {
+                *item = item.shifted(offset, 0.0);
+            }
+        }
+    };
     
-    for i in 0..item_count {
-        let col = i % cols;
-        let row = i / cols;
-        
+    for (i, section) in sections.iter().enumerate() {
+        // セクションの切り替わり: 途中の行を閉じて見出しを置くよ
+        if i == 0 || *section != current_section {
+            if col != 0 {
+                align_row(&mut items[row_start..], col);
+                col = 0;
+                y += cell_height;
+            }
+            current_section = *section;
+            if let Some(name) = section {
+                headers.push(SectionHeaderLayout {
+                    name: name.to_string(),
+                    rect: D2D_RECT_F {
+                        left: PADDING,
+                        top: y,
+                        right: (window_width - PADDING).max(PADDING),
+                        bottom: y + header_height,
+                    },
+                });
+                y += header_height;
+            }
+        }
+
+        if col == 0 {
+            row_start = items.len();
+        }
         let x = PADDING + col as f32 * cell_width;
-        let y = PADDING + row as f32 * cell_height;
-        
-        // アイコンの矩形 (セル内中央上部)
-        let icon_x = x + (cell_width - icon_size) / 2.0;
-        let icon_y = y + 4.0;
-        let icon_rect = D2D_RECT_F {
-            left: icon_x,
-            top: icon_y,
-            right: icon_x + icon_size,
-            bottom: icon_y + icon_size,
-        };
-        
-        // テキストの矩形 (アイコンの下)
-        let text_rect = D2D_RECT_F {
-            left: x + 2.0,
-            top: icon_rect.bottom + 2.0,
-            right: x + cell_width - 2.0,
-            bottom: icon_rect.bottom + 2.0 + text_height,
-        };
-        
-        // ヒットテスト用の矩形 (セル全体)
-        let hit_rect = D2D_RECT_F {
-            left: x,
-            top: y,
-            right: x + cell_width,
-            bottom: y + cell_height,
+
+        let item = match params.mode {
+            LayoutMode::Grid => {
+                // アイコンの矩形 (セル内中央上部)
+                let icon_x = x + (item_width - icon_size) / 2.0;
+                let icon_y = y + 4.0;
+                let icon_rect = D2D_RECT_F {
+                    left: icon_x,
+                    top: icon_y,
+                    right: icon_x + icon_size,
+                    bottom: icon_y + icon_size,
+                };
+
+                // テキストの矩形 (アイコンの下)
+                let text_rect = D2D_RECT_F {
+                    left: x + 2.0,
+                    top: icon_rect.bottom + 2.0,
+                    right: x + item_width - 2.0,
+                    bottom: icon_rect.bottom + 2.0 + text_height,
+                };
+
+                // ヒットテスト用の矩形 (隙間を除いたセル全体)
+                let hit_rect = D2D_RECT_F {
+                    left: x,
+                    top: y,
+                    right: x + item_width,
+                    bottom: y + item_height,
+                };
+
+                ItemLayout { icon_rect, text_rect, hit_rect }
+            }
+            LayoutMode::List => {
+                // アイコンは行の左端に, 縦は真ん中に置くよ
+                let icon_x = x + 4.0;
+                let icon_y = y + (item_height - icon_size) / 2.0;
+                let icon_rect = D2D_RECT_F {
+                    left: icon_x,
+                    top: icon_y,
+                    right: icon_x + icon_size,
+                    bottom: icon_y + icon_size,
+                };
+
+                // テキストはアイコンの右から行の右端まで使えるよ
+                let text_rect = D2D_RECT_F {
+                    left: icon_rect.right + 8.0,
+                    top: y,
+                    right: (x + list_width - 4.0).max(icon_rect.right + 8.0),
+                    bottom: y + item_height,
+                };
+
+                // ヒットテスト用の矩形 (行全体)
+                let hit_rect = D2D_RECT_F {
+                    left: x,
+                    top: y,
+                    right: x + list_width,
+                    bottom: y + item_height,
+                };
+
+                ItemLayout { icon_rect, text_rect, hit_rect }
+            }
         };
-        
-        layouts.push(ItemLayout {
-            icon_rect,
-            text_rect,
-            hit_rect,
-        });
+        items.push(item);
+
+        col += 1;
+        if col == cols {
+            col = 0;
+            y += cell_height;
+        }
+    }
+    if col != 0 {
+        align_row(&mut items[row_start..], col);
     }
     
-    layouts
+    GroupLayout { items, headers, columns: cols, title }
+}
+
+/// ウィンドウの大きさを, セルがちょうど何個分か (と周りの余白) になるように丸めるよ。
+/// 列数・行数は一番近い整数にして, 最低でも 1 セル分は残すね。
+/// リスト表示は行が幅いっぱいに伸びるから, 幅はそのままにして高さだけ丸めるよ。
+pub fn snap_size(width: f32, height: f32, params: &LayoutParams) -> (f32, f32) {
+    let (cell_width, cell_height) = cell_size(params);
+    let top = title_rect(width, params).map_or(PADDING, |rect| rect.bottom);
+
+    let rows = ((height - top - PADDING + params.grid_gap) / cell_height).round().max(1.0);
+    let snapped_height = top + rows * cell_height - params.grid_gap + PADDING;
+    if params.mode == LayoutMode::List {
+        return (width, snapped_height);
+    }
+
+    let columns = ((width - PADDING * 2.0 + params.grid_gap) / cell_width).round().max(1.0);
+    (PADDING * 2.0 + columns * cell_width - params.grid_gap, snapped_height)
+}
+
+/// width x height の画像を, 縦横比を保ったまま bounds に収まるように縮小 (拡大) して,
+/// 中央に置いたときの矩形を返すよ。
+pub fn fit_rect(bounds: &D2D_RECT_F, width: f32, height: f32) -> D2D_RECT_F {
+    let bounds_width = bounds.right - bounds.left;
+    let bounds_height = bounds.bottom - bounds.top;
+    if width <= 0.0 || height <= 0.0 {
+        return *bounds;
+    }
+    let scale = (bounds_width / width).min(bounds_height / height);
+    let (draw_width, draw_height) = (width * scale, height * scale);
+    let left = bounds.left + (bounds_width - draw_width) / 2.0;
+    let top = bounds.top + (bounds_height - draw_height) / 2.0;
+    D2D_RECT_F { left, top, right: left + draw_width, bottom: top + draw_height }
 }
 
 /// 背景色から見やすいテキスト色を選択するための輝度計算
@@ -79,6 +383,16 @@ pub fn is_dark_color(r: f32, g: f32, b: f32) -> bool {
     luminance < 0.5
 }
 
+/// グラデーションの相手色を作るよ。暗い色は明るく, 明るい色は暗く寄せるね。
+pub fn shade_color(r: f32, g: f32, b: f32) -> (f32, f32, f32) {
+    const AMOUNT: f32 = 0.3;
+    if is_dark_color(r, g, b) {
+        (r + (1.0 - r) * AMOUNT, g + (1.0 - g) * AMOUNT, b + (1.0 - b) * AMOUNT)
+    } else {
+        (r * (1.0 - AMOUNT), g * (1.0 - AMOUNT), b * (1.0 - AMOUNT))
+    }
+}
+
 /// 色を反転させるよ (透明度は維持)
 pub fn invert_color(r: f32, g: f32, b: f32) -> (f32, f32, f32) {
     (1.0 - r, 1.0 - g, 1.0 - b)
@@ -92,3 +406,187 @@ pub fn get_error_text_color(is_dark_bg: bool) -> &'static str {
         "#CC0000FF" // 明るい背景には濃い赤
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn within(inner: &D2D_RECT_F, outer: &D2D_RECT_F) -> bool {
+        const EPS: f32 = 0.001;
+        inner.left >= outer.left - EPS
+            && inner.top >= outer.top - EPS
+            && inner.right <= outer.right + EPS
+            && inner.bottom <= outer.bottom + EPS
+    }
+
+    /// テストで使う基本の大きさだよ。違うところだけ ..params() で上書きしてね。
+    fn params() -> LayoutParams {
+        LayoutParams {
+            icon_size: 48.0,
+            font_size: 12.0,
+            scale_factor: 1.0,
+            title_height: 0.0,
+            grid_gap: 0.0,
+            mode: LayoutMode::Grid,
+            label_lines: 1,
+            row_align: RowAlign::Left,
+        }
+    }
+
+    #[test]
+    fn test_fit_rect_keeps_wide_image_inside_cell() {
+        // 横長のサムネイル (160x90) を 48x48 のセルに収めるよ
+        let cell = D2D_RECT_F { left: 10.0, top: 20.0, right: 58.0, bottom: 68.0 };
+        let rect = fit_rect(&cell, 160.0, 90.0);
+
+        assert!(within(&rect, &cell));
+        // 横幅いっぱいで, 縦は中央寄せになっているはずだよ
+        assert!((rect.right - rect.left - 48.0).abs() < 0.001);
+        assert!((rect.bottom - rect.top - 27.0).abs() < 0.001);
+        assert!(((rect.top - cell.top) - (cell.bottom - rect.bottom)).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_fit_rect_keeps_tall_image_inside_cell() {
+        let cell = D2D_RECT_F { left: 0.0, top: 0.0, right: 48.0, bottom: 48.0 };
+        let rect = fit_rect(&cell, 30.0, 120.0);
+
+        assert!(within(&rect, &cell));
+        assert!((rect.bottom - rect.top - 48.0).abs() < 0.001);
+        // 縦横比 (1:4) は保たれているはずだよ
+        assert!(((rect.bottom - rect.top) / (rect.right - rect.left) - 4.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_snap_size_fits_whole_cells() {
+        let params = LayoutParams { title_height: 20.0, grid_gap: 4.0, ..params() };
+        let (cell_width, cell_height) = cell_size(&params);
+        // 3 列と少し, 2 行と少しの大きさは, 3 列 2 行ちょうどに丸まるはずだよ
+        let (width, height) = snap_size(PADDING * 2.0 + cell_width * 3.2, PADDING * 2.0 + 20.0 + cell_height * 2.3, &params);
+
+        let snapped = calculate_group_layout(width, &[None; 6], &params);
+        assert_eq!(snapped.columns, 3);
+        let bottom = snapped.items.iter().map(|item| item.hit_rect.bottom).fold(0.0f32, f32::max);
+        assert!((bottom + PADDING - height).abs() < 0.001);
+        // もう一度丸めても変わらないよ
+        assert_eq!(snap_size(width, height, &params), (width, height));
+    }
+
+    #[test]
+    fn test_apply_scroll_clamps_to_content() {
+        let params = params();
+        let (_, cell_height) = cell_size(&params);
+        let mut group_layout = calculate_group_layout(10.0, &[None; 5], &params); // 1 列に 5 個
+        let window_height = cell_height * 2.0;
+        let max = group_layout.max_scroll(window_height);
+        assert!((max - (PADDING * 2.0 + cell_height * 3.0)).abs() < 0.001);
+
+        // 負の値や行き過ぎは丸めるよ
+        assert_eq!(calculate_group_layout(10.0, &[None; 5], &params).apply_scroll(-20.0, window_height), 0.0);
+        assert_eq!(group_layout.apply_scroll(max + 100.0, window_height), max);
+        let last = group_layout.items.last().expect("items");
+        assert!((last.hit_rect.bottom + PADDING - window_height).abs() < 0.001);
+
+        // 全部収まる大きさならスクロールしないよ
+        let mut roomy = calculate_group_layout(10.0, &[None; 5], &params);
+        assert_eq!(roomy.apply_scroll(50.0, 1000.0), 0.0);
+    }
+
+    #[test]
+    fn test_list_mode_stacks_one_item_per_row() {
+        let params = LayoutParams { icon_size: 32.0, mode: LayoutMode::List, label_lines: 2, ..params() };
+        let (_, cell_height) = cell_size(&params);
+        // 広い窓でも1列だよ
+        let group_layout = calculate_group_layout(400.0, &[None; 3], &params);
+        assert_eq!(group_layout.columns, 1);
+
+        for (i, item) in group_layout.items.iter().enumerate() {
+            assert!((item.hit_rect.top - (PADDING + cell_height * i as f32)).abs() < 0.001);
+            // 行は幅いっぱい, 名前はアイコンの右側だよ
+            assert!((item.hit_rect.right - (400.0 - PADDING)).abs() < 0.001);
+            assert!(item.text_rect.left > item.icon_rect.right);
+            assert!(within(&item.icon_rect, &item.hit_rect));
+        }
+        assert_eq!(group_layout.hit_item(300.0, PADDING + cell_height * 1.5), Some(1));
+    }
+
+    #[test]
+    fn test_neighbor_follows_arrow_keys() {
+        let params = params();
+        let (cell_width, _) = cell_size(&params);
+        // 3 列に 7 個: 0 1 2 / 3 4 5 / 6
+        let group_layout = calculate_group_layout(PADDING * 2.0 + cell_width * 3.0, &[None; 7], &params);
+        assert_eq!(group_layout.columns, 3);
+
+        assert_eq!(group_layout.neighbor(2, 1, 0), Some(3));
+        assert_eq!(group_layout.neighbor(0, -1, 0), None);
+        assert_eq!(group_layout.neighbor(6, 1, 0), None);
+        assert_eq!(group_layout.neighbor(1, 0, 1), Some(4));
+        assert_eq!(group_layout.neighbor(4, 0, -1), Some(1));
+        assert_eq!(group_layout.neighbor(1, 0, -1), None);
+        // 下の行が短いときは, 一番近いアイコンに動くよ
+        assert_eq!(group_layout.neighbor(5, 0, 1), Some(6));
+    }
+
+    #[test]
+    fn test_two_line_labels_make_grid_cells_taller() {
+        let one = params();
+        let two = LayoutParams { label_lines: 2, ..one };
+        let (_, one_height) = cell_size(&one);
+        let (_, two_height) = cell_size(&two);
+        assert!((two_height - one_height - 12.0 * LABEL_LINE_HEIGHT_RATIO).abs() < 0.001);
+
+        // 名前の枠は2行分で, セルの中に収まるよ
+        let item = &calculate_group_layout(200.0, &[None], &two).items[0];
+        assert!((item.text_rect.bottom - item.text_rect.top - 12.0 * LABEL_LINE_HEIGHT_RATIO * 2.0).abs() < 0.001);
+        assert!(within(&item.text_rect, &item.hit_rect));
+    }
+
+    #[test]
+    fn test_hidden_labels_pack_cells_tighter() {
+        let labeled = params();
+        let icon_only = LayoutParams { label_lines: 0, ..labeled };
+        let (labeled_width, labeled_height) = cell_size(&labeled);
+        let (width, height) = cell_size(&icon_only);
+        assert!(width < labeled_width);
+        assert!((labeled_height - height - 12.0 * LABEL_LINE_HEIGHT_RATIO).abs() < 0.001);
+
+        // 当たり判定もアイコンの大きさまで縮んで, 名前の場所は残らないよ
+        let layout = calculate_group_layout(200.0, &[None], &icon_only);
+        let item = &layout.items[0];
+        assert!((item.hit_rect.bottom - item.icon_rect.bottom - 8.0).abs() < 0.001);
+        assert_eq!(item.text_rect.bottom - item.text_rect.top, 0.0);
+    }
+
+    #[test]
+    fn test_centered_rows_shift_only_partial_rows() {
+        let left = params();
+        let center = LayoutParams { row_align: RowAlign::Center, ..left };
+        let (cell_width, _) = cell_size(&left);
+        let width = PADDING * 2.0 + cell_width * 3.0;
+        // 3 列に 4 個: 0 1 2 / 3, セクションが変わって 4
+        let sections = [None, None, None, None, Some("b")];
+        let packed = calculate_group_layout(width, &sections, &left);
+        let centered = calculate_group_layout(width, &sections, &center);
+
+        // 埋まっている行は動かないよ
+        for i in 0..3 {
+            assert_eq!(centered.items[i].hit_rect.left, packed.items[i].hit_rect.left);
+        }
+        // 1 個だけの行は, 空いた 2 セルの半分 (1 セル分) だけ右に寄るよ
+        for i in 3..5 {
+            assert!((centered.items[i].hit_rect.left - packed.items[i].hit_rect.left - cell_width).abs() < 0.001);
+        }
+        // クリックの判定もずれた位置でできるよ
+        let item = &centered.items[3].hit_rect;
+        assert_eq!(centered.hit_item((item.left + item.right) / 2.0, (item.top + item.bottom) / 2.0), Some(3));
+        assert_eq!(centered.hit_item(PADDING + 1.0, (item.top + item.bottom) / 2.0), None);
+    }
+
+    #[test]
+    fn test_fit_rect_with_empty_image_returns_cell() {
+        let cell = D2D_RECT_F { left: 0.0, top: 0.0, right: 48.0, bottom: 48.0 };
+        let rect = fit_rect(&cell, 0.0, 10.0);
+        assert_eq!((rect.left, rect.top, rect.right, rect.bottom), (0.0, 0.0, 48.0, 48.0));
+    }
+}
@@ -30,6 +30,11 @@ fn main() -> Result<(), windows::core::Error> {
                 }
                 i += 2;
             }
+            "--diagnostics" => {
+                // 開発者向け。設定ファイルには保存しないよ
+                manager::get_settings_writer().app.show_diagnostics = true;
+                i += 1;
+            }
             _ => i += 1,
         }
     }
@@ -40,25 +45,35 @@ fn main() -> Result<(), windows::core::Error> {
     }
 
     // 3. COM の初期化 (WIC や DirectComposition で必要)
+    // ドラッグ&ドロップの受け取り (RegisterDragDrop) には OLE の初期化が要るから, OleInitialize でまとめてやるよ
     unsafe {
-        windows::Win32::System::Com::CoInitializeEx(
-            None,
-            windows::Win32::System::Com::COINIT_APARTMENTTHREADED,
-        )?;
+        windows::Win32::System::Ole::OleInitialize(None)?;
     }
 
     // 3. グラフィックスエンジンの初期化 (全ウィンドウで共有)
     let engine = Rc::new(graphics::GraphicsEngine::new()?);
 
     // 3. メインウィンドウを作成 (非表示。常駐用)
-    let _window = win32::Window::new("DesktopGroupingClass", "Desktop Grouping Native")?;
+    // 作れなかったら常駐できないから, 知らせてから終わるよ
+    let _window = match win32::Window::new("DesktopGroupingClass", "Desktop Grouping (Main)") {
+        Ok(window) => window,
+        Err(e) => {
+            log::error!("Failed to create the main window: {}", e);
+            win32::api::dialog::show_error(&format!("Failed to start Desktop Grouping.\n\n{}", e));
+            manager::flush();
+            return Ok(());
+        }
+    };
 
     // 4. トレイアイコンを作成
-    let _tray = tray::tray_icon::create_tray();
+    let tray = tray::tray_icon::create_tray();
     log::info!("Tray icon created. Running in background...");
 
-    // 5. メッセージループを開始 (エンジンを渡す)
-    win32::run_message_loop(engine)?;
+    // 5. メッセージループを開始 (エンジンと, 保存状態を表示するためのトレイアイコンを渡す)
+    win32::run_message_loop(engine, &tray)?;
+
+    // まだ書き出していない変更があれば, 終わる前に保存するよ
+    manager::flush();
 
     log::info!("Application exiting.");
     Ok(())
@@ -0,0 +1,126 @@
+use std::path::{Path, PathBuf};
+
+/// 保存するときにパスの先頭を置き換える環境変数だよ。
+/// 同じパスに当てはまるものが複数あったら, 値が長い (より具体的な) 方を使うね。
+const CONTRACT_VARS: [&str; 7] = [
+    "LOCALAPPDATA",
+    "APPDATA",
+    "USERPROFILE",
+    "PUBLIC",
+    "ProgramFiles(x86)",
+    "ProgramFiles",
+    "SystemRoot",
+];
+
+/// パスの中の `%USERPROFILE%` のような環境変数を展開するよ！
+/// 知らない変数はそのまま残すから, 普通の絶対パスは何も変わらないよ。
+pub fn expand(path: &Path) -> PathBuf {
+    expand_with(path, |name| std::env::var(name).ok())
+}
+
+/// パスの先頭がよく使う環境変数の値と同じなら, `%APPDATA%\...` のような形に戻すよ。
+pub fn contract(path: &Path) -> PathBuf {
+    let vars: Vec<(&str, String)> = CONTRACT_VARS
+        .iter()
+        .filter_map(|&name| std::env::var(name).ok().map(|value| (name, value)))
+        .collect();
+    contract_with(path, &vars)
+}
+
+fn expand_with(path: &Path, lookup: impl Fn(&str) -> Option<String>) -> PathBuf {
+    let text = path.to_string_lossy();
+    if !text.contains('%') {
+        return path.to_path_buf();
+    }
+
+    let mut result = String::with_capacity(text.len());
+    let mut rest: &str = &text;
+    while let Some(start) = rest.find('%') {
+        result.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let Some(end) = after.find('%') else {
+            // 閉じる % が無いなら, 残りはそのままだよ
+            result.push_str(&rest[start..]);
+            rest = "";
+            break;
+        };
+        let name = &after[..end];
+        let value = if name.is_empty() { None } else { lookup(name) };
+        match value {
+            Some(value) => result.push_str(&value),
+            None => {
+                result.push('%');
+                result.push_str(name);
+                result.push('%');
+            }
+        }
+        rest = &after[end + 1..];
+    }
+    result.push_str(rest);
+    PathBuf::from(result)
+}
+
+fn contract_with(path: &Path, vars: &[(&str, String)]) -> PathBuf {
+    let text = path.to_string_lossy();
+    let best = vars
+        .iter()
+        .filter_map(|(name, value)| {
+            let value = value.trim_end_matches('\\');
+            if value.is_empty() {
+                return None;
+            }
+            // 大文字小文字は区別しないけど, フォルダ名の途中で切れないようにするよ
+            let head = text.get(..value.len())?;
+            let tail = &text[value.len()..];
+            (head.eq_ignore_ascii_case(value) && (tail.is_empty() || tail.starts_with('\\')))
+                .then_some((name, value.len(), tail))
+        })
+        .max_by_key(|&(_, len, _)| len);
+
+    match best {
+        Some((name, _, tail)) => PathBuf::from(format!("%{}%{}", name, tail)),
+        None => path.to_path_buf(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars() -> Vec<(&'static str, String)> {
+        vec![
+            ("USERPROFILE", r"C:\Users\alice".to_string()),
+            ("APPDATA", r"C:\Users\alice\AppData\Roaming".to_string()),
+            ("LOCALAPPDATA", r"C:\Users\alice\AppData\Local".to_string()),
+        ]
+    }
+
+    fn lookup(name: &str) -> Option<String> {
+        vars().into_iter().find(|(n, _)| n.eq_ignore_ascii_case(name)).map(|(_, v)| v)
+    }
+
+    #[test]
+    fn test_appdata_path_round_trips() {
+        // %APPDATA% で保存したパスが展開できて, 保存し直すと同じ形に戻るはずだよ
+        let stored = PathBuf::from(r"%APPDATA%\Tool\app.exe");
+        let expanded = expand_with(&stored, lookup);
+        assert_eq!(expanded, PathBuf::from(r"C:\Users\alice\AppData\Roaming\Tool\app.exe"));
+        assert_eq!(contract_with(&expanded, &vars()), stored);
+    }
+
+    #[test]
+    fn test_plain_paths_are_untouched() {
+        let plain = PathBuf::from(r"D:\Tools\app.exe");
+        assert_eq!(expand_with(&plain, lookup), plain);
+        assert_eq!(contract_with(&plain, &vars()), plain);
+        // フォルダ名の途中で一致しても置き換えないよ
+        let similar = PathBuf::from(r"C:\Users\alice2\app.exe");
+        assert_eq!(contract_with(&similar, &vars()), similar);
+    }
+
+    #[test]
+    fn test_unknown_variables_are_kept() {
+        let path = PathBuf::from(r"%NO_SUCH_VAR%\app.exe");
+        assert_eq!(expand_with(&path, lookup), path);
+    }
+}
@@ -1,4 +1,5 @@
-use std::sync::{LazyLock, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::sync::{LazyLock, Mutex, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::time::{Duration, Instant};
 use super::models::Settings;
 use super::storage;
 
@@ -29,10 +30,77 @@ pub fn get_settings_writer() -> RwLockWriteGuard<'static, Settings> {
         .expect("Failed to acquire write lock on settings")
 }
 
-/// 現在の状態をファイルに保存するよ！
+/// 最後に save() が呼ばれてから, これだけ次の変更がなければファイルに書き出すよ。
+/// ドラッグ中のように何度も save() が呼ばれても, 手を止めるまでは書き出さないんだ。
+const SAVE_QUIET: Duration = Duration::from_millis(500);
+
+/// ずっと変更が続いていても, 最初の save() からこれだけ経ったら一度書き出すよ。
+const SAVE_MAX_DELAY: Duration = Duration::from_millis(3000);
+
+/// まだファイルに書き出していない変更だよ。
+struct PendingSave {
+    first: Instant, // 最初に save() が呼ばれた時刻
+    last: Option<Instant>, // 最後に save() が呼ばれた時刻 (書き出しに失敗して残したときは None)
+}
+
+impl PendingSave {
+    /// 書き出す時間が来たかどうかだよ。
+    fn is_due(&self) -> bool {
+        self.last.is_some_and(|last| last.elapsed() >= SAVE_QUIET) || self.first.elapsed() >= SAVE_MAX_DELAY
+    }
+}
+
+/// まだファイルに書き出していない変更があれば, ここに入っているよ。
+static PENDING_SAVE: Mutex<Option<PendingSave>> = Mutex::new(None);
+
+/// 現在の状態を保存するよう頼むよ！
+/// すぐには書き出さずに, メッセージループの flush_if_due() でまとめて書き出すね。
 pub fn save() {
+    let now = Instant::now();
+    let mut pending = PENDING_SAVE.lock().expect("Failed to acquire pending save lock");
+    pending.get_or_insert(PendingSave { first: now, last: None }).last = Some(now);
+}
+
+/// まだ書き出していない変更があるかどうかだよ。
+pub fn is_dirty() -> bool {
+    PENDING_SAVE.lock().expect("Failed to acquire pending save lock").is_some()
+}
+
+/// 待ち時間が過ぎていたら, 変更をファイルに書き出すよ。
+pub fn flush_if_due() {
+    let due = PENDING_SAVE
+        .lock()
+        .expect("Failed to acquire pending save lock")
+        .as_ref()
+        .is_some_and(PendingSave::is_due);
+    if due {
+        flush();
+    }
+}
+
+/// 設定ファイルを読み直して, 今の設定と入れ替えるよ。読めなかったら今の設定のままだよ。
+/// ファイルの方を正しいものとして扱うから, まだ書き出していない変更は捨てるね。
+pub fn reload() -> Result<(), String> {
+    let settings = storage::reread_settings()?;
+    if PENDING_SAVE.lock().expect("Failed to acquire pending save lock").take().is_some() {
+        log::warn!("Discarding unsaved changes to reload the config file.");
+    }
+    *get_settings_writer() = settings;
+    Ok(())
+}
+
+/// 書き出していない変更があれば, 今すぐファイルに書き出すよ (トレイの Save Now や終了時)。
+pub fn flush() {
+    if PENDING_SAVE.lock().expect("Failed to acquire pending save lock").take().is_none() {
+        return;
+    }
     let settings = get_settings_reader();
     if let Err(e) = storage::save_settings(&*settings) {
         log::error!("Failed to save settings: {}", e);
+        // 書き出せなかった変更は残しておいて, 一番長い待ち時間のあとで flush_if_due() にもう一度試してもらうよ
+        PENDING_SAVE
+            .lock()
+            .expect("Failed to acquire pending save lock")
+            .get_or_insert(PendingSave { first: Instant::now(), last: None });
     }
 }
@@ -1,6 +1,7 @@
 pub mod models;
 pub mod storage;
 pub mod manager;
+pub mod env_path;
 
 pub use models::*;
 pub use manager::{get_settings_reader, get_settings_writer, save as save_settings};
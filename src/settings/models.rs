@@ -1,12 +1,213 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::PathBuf;
+use crate::settings::env_path;
 use windows::Win32::UI::WindowsAndMessaging::{GetSystemMetrics, SM_CXVIRTUALSCREEN, SM_CYVIRTUALSCREEN, SM_XVIRTUALSCREEN, SM_YVIRTUALSCREEN};
 
 /// 設定ファイルに永続化するためのアイコン情報。
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct PersistentIconInfo {
     pub path: PathBuf,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub section: Option<String>, // グループ内のセクション名 (見出し)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub custom_icon_path: Option<PathBuf>, // シェルのアイコンの代わりに表示する画像
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub custom_name: Option<String>, // ファイル名の代わりに表示する名前
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>, // インターネットショートカット (.url) や URL そのものが開く先
+}
+
+impl PersistentIconInfo {
+    /// パスの先頭を %APPDATA% などの環境変数に置き換えた形にするよ (use_env_paths 用)。
+    pub fn with_env_paths(mut self) -> Self {
+        self.path = env_path::contract(&self.path);
+        self.custom_icon_path = self.custom_icon_path.map(|p| env_path::contract(&p));
+        self
+    }
+}
+
+/// グループ内のアイコンを並べる基準。
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SortKey {
+    /// 表示名 (大文字小文字を区別しない)
+    Name,
+    /// 表示名の逆順
+    NameDesc,
+    /// フォルダが先で, あとは拡張子, 同じなら表示名
+    Extension,
+}
+
+impl SortKey {
+    /// `autosort:name` のような指定文字列から変換するよ。
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "name" => Some(Self::Name),
+            "name_desc" | "name-desc" | "desc" => Some(Self::NameDesc),
+            "ext" | "extension" => Some(Self::Extension),
+            _ => None,
+        }
+    }
+}
+
+/// グループの背景の塗り方。
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum BackgroundMode {
+    /// 背景色で単色に塗る (従来の動作)
+    #[default]
+    Solid,
+    /// 背景色から少し明るく (暗く) した色へのグラデーション
+    Gradient,
+    /// bg_image に指定した画像
+    Image,
+}
+
+/// グループ内のアイコンの並べ方。
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum LayoutMode {
+    /// アイコンの下に名前を置いて, 格子状に並べる (従来の動作)
+    #[default]
+    Grid,
+    /// 1行に1つずつ, 左にアイコン・右に名前を並べる
+    List,
+}
+
+/// グリッド表示で, 埋まりきらない行 (セクションの最後の行) をどこに寄せるか。
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum RowAlign {
+    /// 左に詰める (従来の動作)
+    #[default]
+    Left,
+    /// 真ん中に寄せる
+    Center,
+}
+
+impl BackgroundMode {
+    /// ショートカットで切り替えるときの次のモードだよ。画像が無ければ Image は飛ばすね。
+    pub fn next(self, has_image: bool) -> Self {
+        match self {
+            Self::Solid => Self::Gradient,
+            Self::Gradient if has_image => Self::Image,
+            Self::Gradient | Self::Image => Self::Solid,
+        }
+    }
+
+    /// オーバーレイに出す表示名だよ。
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Solid => "Solid",
+            Self::Gradient => "Gradient",
+            Self::Image => "Image",
+        }
+    }
+}
+
+/// 文字の太さ。フォントにその太さが無いときは, DirectWrite が近い太さで代用 (または太字を合成) してくれるよ。
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum FontWeight {
+    #[default]
+    Regular,
+    Medium,
+    Bold,
+}
+
+impl FontWeight {
+    /// OpenType の太さの値 (400 = 標準, 700 = 太字) だよ。
+    pub fn value(self) -> i32 {
+        match self {
+            Self::Regular => 400,
+            Self::Medium => 500,
+            Self::Bold => 700,
+        }
+    }
+}
+
+/// アイコンを拡大・縮小して描くときの補間方法。
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum IconFilterQuality {
+    /// 最近傍。一番軽くて, ドット絵のアイコンがくっきりするよ
+    Nearest,
+    /// 線形補間 (従来の動作)
+    #[default]
+    Bilinear,
+    /// 高品質なバイキュービック。縮小がきれいだけど, アイコンが多いと少し重いよ
+    Bicubic,
+}
+
+/// アイコンを表示できないとき (ファイルが無い・読み込み失敗) の代わりの見た目。
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PlaceholderStyle {
+    /// 控えめなグレーの枠に「?」
+    #[default]
+    Glyph,
+    /// 背景色の反転色で塗った四角 (以前の見た目)
+    Inverted,
+    /// placeholder_image に指定した画像 (読めなければ Glyph)
+    Image,
+}
+
+/// グループの何もない場所を左クリックしたときの動作。
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum EmptyClickAction {
+    /// 何もしない (従来の動作)
+    #[default]
+    None,
+    /// カーソルが離れるまで, グループを一時的に最前面へ出す
+    Raise,
+}
+
+/// アイコンを起動したときに, 枠の光り以外に返す手応え。
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum LaunchFeedback {
+    /// 何もしない (従来の動作)
+    #[default]
+    None,
+    /// グループの背景を一瞬明るくして, ふわっと戻す
+    Flash,
+    /// システムの既定のサウンドを鳴らす
+    Sound,
+    /// Flash と Sound の両方
+    Both,
+}
+
+impl LaunchFeedback {
+    pub fn flash(self) -> bool {
+        matches!(self, LaunchFeedback::Flash | LaunchFeedback::Both)
+    }
+
+    pub fn sound(self) -> bool {
+        matches!(self, LaunchFeedback::Sound | LaunchFeedback::Both)
+    }
+}
+
+/// グループをデスクトップのどの層に置くか。
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum DesktopPinMode {
+    /// HWND_BOTTOM で最背面に置く (従来の動作)
+    #[default]
+    Bottom,
+    /// デスクトップ (Progman / WorkerW) を所有者にして, デスクトップの層に固定する
+    Desktop,
+}
+
+/// グループを自動で並べて置く画面の端。
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DockEdge {
+    Left,
+    Right,
+    Top,
+    Bottom,
 }
 
 /// アプリケーション全体の共通設定。
@@ -15,29 +216,211 @@ pub struct PersistentIconInfo {
 pub struct AppSettings {
     pub font_size: f32,
     pub font_family: String,
+    pub fallback_font_family: String, // font_family に無い絵文字や記号を描くためのフォント (空なら OS 任せ)
+    pub title_font_weight: FontWeight, // タイトル行とセクション見出しの文字の太さ
+    pub label_font_weight: FontWeight, // アイコン名の文字の太さ
+    pub keep_on_desktop: bool, // グループを最背面に固定するか (false なら普通のウィンドウのように重なるよ)
+    pub desktop_pin_mode: DesktopPinMode,
+    pub list_separators: bool, // 1列表示のとき, 行の間に薄い区切り線を引くか
+    pub opaque_on_hover: bool, // カーソルが乗っている間だけグループを不透明にするか
+    pub empty_click_action: EmptyClickAction,
+    pub raise_on_interact: bool, // クリックしたグループを少しの間だけ最前面に出すか
+    pub launch_feedback: LaunchFeedback,
+    pub use_env_paths: bool, // アイコンのパスを %USERPROFILE% などの環境変数を使った形で保存するか
+    pub focus_follows_cursor: bool, // 最後にカーソルが乗ったグループを, 離れた後もキーボード操作の対象にするか
+    pub exit_when_empty: bool, // 最後のグループを閉じたらアプリも終了するか (既定ではトレイに残るよ)
+    pub confirm_icon_delete: bool, // アイコンを削除する前に確認するか
+    pub confirm_window_delete: bool, // 「閉じる」 (Alt + F4 など) でグループを削除する前に確認するか
+    pub auto_fit: bool, // アイコンの追加・削除のたびに, グループの大きさを中身に合わせるか
+    pub snap_size_to_grid: bool, // リサイズし終わったら, アイコンのセルがちょうど収まる大きさに丸めるか
+    pub snap_threshold: i32, // ドラッグで動かすとき, 画面や他のグループの端にこのピクセル数まで近づいたら吸い付くよ (0 で無効)
+    pub auto_dock: Option<DockEdge>, // グループをメイン画面のこの端に沿って順に並べるよ (None なら並べない)
+    pub use_thumbnails: bool, // 画像・動画ファイルはアイコンの代わりにサムネイルを表示するか (少し重いよ)
+    pub icon_filter_quality: IconFilterQuality,
+    pub keep_icon_aspect: bool, // 正方形でないアイコン (画像など) を縦横比を保って描くか
+    pub grid_gap: f32, // アイコンのセルとセルの間にあける隙間 (px)
+    pub max_label_lines: u8, // グリッド表示のアイコン名を何行まで折り返すか (1 か 2)
+    pub icon_size: f32, // 新しく作るグループのアイコンの論理サイズ (size: を空で貼るとこの大きさに戻るよ)
+    pub show_count_badge: bool, // グループの右上にアイコン数のバッジを出すか
+    pub hover_fill_color: String, // ホバー中のアイコンの塗りの色 ("#RRGGBBAA"。空なら背景に合わせて決めるよ)
+    pub hover_border_color: String, // ホバー中のアイコンの枠の色 (空なら背景に合わせて決めるよ)
+    pub executing_color: String, // 起動した直後のアイコンを光らせる色 (空なら背景に合わせて決めるよ)
+    pub placeholder_style: PlaceholderStyle,
+    pub placeholder_image: Option<PathBuf>, // placeholder_style が Image のときに使う画像
+    pub size_presets: Vec<(u32, u32)>, // Ctrl + S で順に切り替えるグループの大きさ (幅, 高さ)
+    pub keybindings: Keybindings,
+    /// 診断オーバーレイ (FPS など) を表示するか。起動引数 --diagnostics でのみ有効にして, 保存はしないよ。
+    #[serde(skip)]
+    pub show_diagnostics: bool,
 }
 
 impl AppSettings {
     pub fn validate(&mut self) {
         self.font_size = self.font_size.clamp(8.0, 72.0);
+        self.grid_gap = self.grid_gap.clamp(0.0, 64.0);
+        self.max_label_lines = self.max_label_lines.clamp(1, 2);
+        self.icon_size = self.icon_size.clamp(16.0, 256.0);
         if self.font_family.is_empty() {
             self.font_family = "Meiryo".to_string();
         }
+        // 読めない色は空に戻して, 背景に合わせた色を使うよ
+        for color in [&mut self.hover_fill_color, &mut self.hover_border_color, &mut self.executing_color] {
+            if !color.is_empty() && !is_hex_color(color) {
+                log::warn!("Ignoring invalid highlight color {:?}.", color);
+                color.clear();
+            }
+        }
+        self.size_presets.retain(|&(width, height)| {
+            let valid = width > 0 && height > 0;
+            if !valid {
+                log::warn!("Ignoring invalid size preset ({}, {}).", width, height);
+            }
+            valid
+        });
     }
 }
 
+/// "#RRGGBB" か "#RRGGBBAA" の形の色かどうかを調べるよ。
+fn is_hex_color(text: &str) -> bool {
+    text.strip_prefix('#')
+        .is_some_and(|hex| matches!(hex.len(), 6 | 8) && hex.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+/// キー操作の割り当て。"ctrl+shift+s" のように, 修飾キーとキーを + でつないで書くよ。
+/// マウス操作と組み合わせるものは "ctrl" のように修飾キーだけを書くんだ。
+/// 読めないものは既定の割り当てのままになるよ (ui::keymap を見てね)。
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct Keybindings {
+    // --- マウスと組み合わせる修飾キー ---
+    pub move_group: String, // + ドラッグでグループを移動
+    pub resize_group: String, // + ドラッグでグループの大きさを変更
+    pub adjust_opacity: String, // + ドラッグで透明度を調整
+    pub wheel_opacity: String, // + ホイールで透明度を調整
+    pub wheel_border_width: String, // + ホイールで枠線の太さを調整
+    pub wheel_window_opacity: String, // + ホイールでウィンドウ全体 (アイコンも) の不透明度を調整
+    pub delete_click: String, // + 右クリックでアイコン (空白ならグループ) を削除
+    pub rename_icon_click: String, // + ダブルクリックでアイコンの表示名を変更
+    pub copy_path_click: String, // + 右クリックでアイコンのパスをクリップボードへ
+    pub expand_folder_drop: String, // + フォルダをドロップで, フォルダの中身を1つずつ追加
+
+    // --- キーボードだけの操作 ---
+    pub paste: String, // 色やコマンドの貼り付け (切り取ったアイコンの移動)
+    pub undo_color: String,
+    pub redo_color: String,
+    pub cycle_background: String,
+    pub cycle_size_preset: String,
+    pub duplicate_icon: String,
+    pub rename: String,
+    pub cut: String,
+    pub delete_icon: String,
+    pub open_selected: String,
+    pub open_all: String, // グループのアイコンを全部, 順番に開く
+    pub solo: String,
+    pub restore_group: String,
+    pub toggle_topmost: String,
+    pub toggle_collapse: String, // タイトル行だけに折りたたむ / 広げる
+    pub toggle_lock: String, // 全部ロック / 全部解除
+    pub focus_left: String, // アイコンのフォーカスを動かす (open_selected でフォーカス中のアイコンを開く)
+    pub focus_right: String,
+    pub focus_up: String,
+    pub focus_down: String,
+
+    // --- どのアプリを使っていても反応するホットキー ---
+    pub toggle_all_groups: String, // 全グループを隠す / 表示する
+}
+
+impl Default for Keybindings {
+    fn default() -> Self {
+        Self {
+            move_group: "ctrl".to_string(),
+            resize_group: "shift".to_string(),
+            adjust_opacity: "alt".to_string(),
+            wheel_opacity: "ctrl".to_string(),
+            wheel_border_width: "ctrl+shift".to_string(),
+            wheel_window_opacity: "alt".to_string(),
+            delete_click: "ctrl".to_string(),
+            rename_icon_click: "shift".to_string(),
+            copy_path_click: "shift".to_string(),
+            expand_folder_drop: "ctrl".to_string(),
+            paste: "ctrl+v".to_string(),
+            undo_color: "ctrl+z".to_string(),
+            redo_color: "ctrl+y".to_string(),
+            cycle_background: "ctrl+b".to_string(),
+            cycle_size_preset: "ctrl+s".to_string(),
+            duplicate_icon: "ctrl+d".to_string(),
+            rename: "f2".to_string(),
+            cut: "ctrl+x".to_string(),
+            delete_icon: "delete".to_string(),
+            open_selected: "enter".to_string(),
+            open_all: "ctrl+enter".to_string(),
+            solo: "ctrl+shift+s".to_string(),
+            restore_group: "ctrl+shift+z".to_string(),
+            toggle_topmost: "ctrl+t".to_string(),
+            toggle_collapse: "ctrl+m".to_string(),
+            toggle_lock: "ctrl+l".to_string(),
+            focus_left: "left".to_string(),
+            focus_right: "right".to_string(),
+            focus_up: "up".to_string(),
+            focus_down: "down".to_string(),
+            toggle_all_groups: "ctrl+alt+d".to_string(),
+        }
+    }
+}
+
+/// グループごとのロック。移動・リサイズ・アイコンの編集をそれぞれ個別に止められるよ。
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(default)]
+pub struct LockFlags {
+    pub r#move: bool, // ドラッグでの移動
+    pub resize: bool, // リサイズ (プリセットや中身に合わせる操作も含むよ)
+    pub edit_icons: bool, // アイコンの追加・削除・並べ替え
+}
+
+impl LockFlags {
+    pub fn all() -> Self {
+        Self { r#move: true, resize: true, edit_icons: true }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// ウィンドウ全体の不透明度の下限だよ (約 10%)。
+pub const MIN_WINDOW_OPACITY: u8 = 26;
+
 /// 各グループ（子ウィンドウ）ごとの個別設定。
 #[derive(Debug, Deserialize, Serialize, Clone)]
 #[serde(default)]
 pub struct ChildSettings {
+    pub title: String, // グループのタイトル (空ならタイトル行なし)
     pub x: i32,
     pub y: i32,
     pub width: u32,
     pub height: u32,
     pub bg_color: String,
     pub opacity: f32, // 0.0 ~ 1.0
+    pub bg_mode: BackgroundMode,
+    pub bg_image: Option<PathBuf>, // 背景画像 (bg_mode が Image のときに使うよ)
     pub icon_size: f32, // アイコンの論理サイズ (デフォルト 48.0)
+    pub layout_mode: LayoutMode, // グリッド表示かリスト表示か
+    pub row_alignment: RowAlign, // 埋まりきらない行を左に詰めるか, 真ん中に寄せるか
+    pub show_labels: bool, // アイコン名を出すか (false ならアイコンだけの詰まった表示になるよ)
+    pub border_width: f32, // 枠線の太さ (0.0 なら枠線なし)
+    pub window_opacity: u8, // ウィンドウ全体の不透明度 (255 で不透明)。背景の opacity と掛け合わさるよ
+    pub collapsed: bool, // タイトル行だけの細い帯に折りたたんでいるか (height は帯の高さになるよ)
+    pub expanded_height: Option<u32>, // 折りたたむ前の高さ。広げるときにこの高さに戻すよ
+    pub font_size: Option<f32>, // このグループだけの文字サイズ (None なら AppSettings.font_size)
+    pub font_family: Option<String>, // このグループだけのフォント名 (None なら AppSettings.font_family)
     pub icons: Vec<PersistentIconInfo>,
+    pub auto_sort: Option<SortKey>, // 設定されていれば, 追加時にこの順で並べ替えるよ
+    pub lock: LockFlags,
+    /// 以前の形式の「全部ロック」だよ。読み込んだら lock に移して, 保存はしないよ。
+    #[serde(skip_serializing)]
+    pub locked: bool,
+    pub topmost: bool, // 常に最前面に出しておくか (keep_on_desktop より優先だよ)
+    pub z_order: u32, // 重なり順 (大きいほど手前)。起動したときにこの順で重ね直すよ
     
     // --- マルチモニター・高DPI対応のための追加フィールド ---
     pub monitor_name: Option<String>, 
@@ -49,9 +432,19 @@ pub struct ChildSettings {
 impl ChildSettings {
     pub fn validate(&mut self) {
         self.opacity = self.opacity.clamp(0.1, 1.0);
+        // 見えなくなって触れなくなると困るから, 少しは残すよ
+        self.window_opacity = self.window_opacity.max(MIN_WINDOW_OPACITY);
         self.icon_size = self.icon_size.clamp(16.0, 256.0);
+        self.font_size = self.font_size.map(|size| size.clamp(8.0, 72.0));
+        self.font_family = self.font_family.take().filter(|family| !family.trim().is_empty());
+        if std::mem::take(&mut self.locked) {
+            self.lock = LockFlags::all();
+        }
         self.width = self.width.max(50);
-        self.height = self.height.max(50);
+        // 折りたたんだ帯は 50 より低いことがあるから, そのままにするね
+        if !self.collapsed {
+            self.height = self.height.max(50);
+        }
 
         if self.bg_color.is_empty() || !self.bg_color.starts_with('#') {
             self.bg_color = "#FFFFFF99".to_string();
@@ -90,6 +483,20 @@ impl Settings {
             child.validate();
         }
     }
+
+    /// 新しく作るグループに付ける重なり順だよ。今あるどのグループよりも手前になるね。
+    pub fn next_z_order(&self) -> u32 {
+        self.children.values().map(|child| child.z_order + 1).max().unwrap_or(0)
+    }
+
+    /// グループを手前から奥の順に並べたものだよ (同じ重なり順なら ID 順)。
+    /// 1つずつ一番奥に置いていくと, 保存したときの重なりが再現できるんだ。
+    pub fn children_front_to_back(&self) -> Vec<(String, ChildSettings)> {
+        let mut children: Vec<(String, ChildSettings)> =
+            self.children.iter().map(|(id, child)| (id.clone(), child.clone())).collect();
+        children.sort_by(|(a_id, a), (b_id, b)| b.z_order.cmp(&a.z_order).then_with(|| a_id.cmp(b_id)));
+        children
+    }
 }
 
 // --- 各構造体のデフォルト値の実装 ---
@@ -99,6 +506,40 @@ impl Default for AppSettings {
         Self {
             font_size: 12.0,
             font_family: "Meiryo".to_string(),
+            fallback_font_family: "Segoe UI Emoji".to_string(),
+            title_font_weight: FontWeight::Bold,
+            label_font_weight: FontWeight::Regular,
+            keep_on_desktop: true,
+            desktop_pin_mode: DesktopPinMode::default(),
+            list_separators: false,
+            opaque_on_hover: false,
+            empty_click_action: EmptyClickAction::default(),
+            raise_on_interact: false,
+            launch_feedback: LaunchFeedback::default(),
+            use_env_paths: false,
+            focus_follows_cursor: false,
+            exit_when_empty: false,
+            confirm_icon_delete: false,
+            confirm_window_delete: true,
+            auto_fit: false,
+            snap_size_to_grid: false,
+            snap_threshold: 12,
+            auto_dock: None,
+            use_thumbnails: false,
+            icon_filter_quality: IconFilterQuality::default(),
+            keep_icon_aspect: true,
+            grid_gap: 0.0,
+            max_label_lines: 1,
+            icon_size: 48.0,
+            show_count_badge: false,
+            hover_fill_color: String::new(),
+            hover_border_color: String::new(),
+            executing_color: String::new(),
+            placeholder_style: PlaceholderStyle::default(),
+            placeholder_image: None,
+            size_presets: Vec::new(),
+            keybindings: Keybindings::default(),
+            show_diagnostics: false,
         }
     }
 }
@@ -106,14 +547,31 @@ impl Default for AppSettings {
 impl Default for ChildSettings {
     fn default() -> Self {
         Self {
+            title: String::new(),
             x: 100,
             y: 100,
             width: 300,
             height: 200,
             bg_color: "#FFFFFF99".to_string(),
             opacity: 1.0,
+            bg_mode: BackgroundMode::default(),
+            bg_image: None,
             icon_size: 48.0,
+            layout_mode: LayoutMode::default(),
+            row_alignment: RowAlign::default(),
+            show_labels: true,
+            border_width: 1.5,
+            window_opacity: 255,
+            collapsed: false,
+            expanded_height: None,
+            font_size: None,
+            font_family: None,
             icons: Vec::new(),
+            auto_sort: None,
+            lock: LockFlags::default(),
+            locked: false,
+            topmost: false,
+            z_order: 0,
             monitor_name: None,
             monitor_x: None,
             monitor_y: None,
@@ -1,13 +1,46 @@
 use std::{
     fs,
     io,
-    path::PathBuf,
+    path::{Path, PathBuf},
 };
-use super::models::Settings;
+use super::models::{ChildSettings, Settings};
 
-/// 設定ファイルの保存先ディレクトリを解決するよ！
+/// 設定ファイルの形式だよ。拡張子で見分けるね。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Toml,
+    Json,
+}
+
+impl ConfigFormat {
+    /// パスの拡張子から形式を決めるよ。`.json` 以外は TOML として扱うね。
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => Self::Json,
+            _ => Self::Toml,
+        }
+    }
+
+    /// 文字列から設定を読み込むよ。
+    pub fn parse(self, contents: &str) -> Result<Settings, String> {
+        match self {
+            Self::Toml => toml::from_str(contents).map_err(|e| e.to_string()),
+            Self::Json => serde_json::from_str(contents).map_err(|e| e.to_string()),
+        }
+    }
+
+    /// 設定を文字列に書き出すよ。
+    pub fn serialize(self, settings: &Settings) -> Result<String, String> {
+        match self {
+            Self::Toml => toml::to_string_pretty(settings).map_err(|e| e.to_string()),
+            Self::Json => serde_json::to_string_pretty(settings).map_err(|e| e.to_string()),
+        }
+    }
+}
+
+/// 設定ファイルの保存先ディレクトリを解決するよ！ (作成はしないよ)
 /// `%APPDATA%/DesktopGrouping` を使うように変更するね。
-fn get_settings_dir() -> io::Result<PathBuf> {
+pub fn resolve_settings_dir() -> io::Result<PathBuf> {
     // 実行ファイルの隣ではなく, 標準的な設定保存場所を取得するよ
     let mut path = if let Ok(appdata) = std::env::var("APPDATA") {
         PathBuf::from(appdata)
@@ -18,6 +51,12 @@ fn get_settings_dir() -> io::Result<PathBuf> {
     };
     
     path.push("DesktopGrouping");
+    Ok(path)
+}
+
+/// 設定ファイルの保存先ディレクトリを取得するよ！ なければ作成するね。
+fn get_settings_dir() -> io::Result<PathBuf> {
+    let path = resolve_settings_dir()?;
     
     // ディレクトリがなければ作成するよ！
     if !path.exists() {
@@ -27,9 +66,16 @@ fn get_settings_dir() -> io::Result<PathBuf> {
     Ok(path)
 }
 
-/// `config.toml` へのフルパスを取得するよ！
+/// 設定ファイルへのフルパスを取得するよ！
+/// 基本は `config.toml` だけど, `config.toml` が無くて `config.json` があるならそっちを使うね。
 pub fn get_config_path() -> io::Result<PathBuf> {
-    Ok(get_settings_dir()?.join("config.toml"))
+    let dir = get_settings_dir()?;
+    let toml_path = dir.join("config.toml");
+    let json_path = dir.join("config.json");
+    if !toml_path.exists() && json_path.exists() {
+        return Ok(json_path);
+    }
+    Ok(toml_path)
 }
 
 /// 設定ファイルを読み込むよ！
@@ -45,10 +91,14 @@ pub fn load_settings() -> Result<Settings, String> {
     let contents = fs::read_to_string(&config_path)
         .map_err(|e| format!("Failed to read config file: {}", e))?;
 
-    let mut settings: Settings = match toml::from_str(&contents) {
+    let format = ConfigFormat::from_path(&config_path);
+    let mut settings: Settings = match format.parse(&contents) {
         Ok(s) => s,
         Err(e) => {
-            let bad_path = config_path.with_extension("toml.bad");
+            let bad_path = config_path.with_extension(match format {
+                ConfigFormat::Toml => "toml.bad",
+                ConfigFormat::Json => "json.bad",
+            });
             log::error!("Failed to parse config file: {}. Backing up to {:?}", e, bad_path);
             let _ = fs::rename(&config_path, &bad_path); // 失敗ファイルを退避
             return Err(format!("Settings corruption detected. Original file saved as .bad"));
@@ -61,16 +111,28 @@ pub fn load_settings() -> Result<Settings, String> {
     Ok(settings)
 }
 
+/// 設定ファイルを読み直すよ (トレイの Reload Config)。
+/// 起動時と違って, 読めなくてもファイルは退避しないよ。手で直している途中かもしれないからね。
+pub fn reread_settings() -> Result<Settings, String> {
+    let config_path = get_config_path().map_err(|e| e.to_string())?;
+    let contents = fs::read_to_string(&config_path)
+        .map_err(|e| format!("Failed to read config file: {}", e))?;
+    let mut settings = ConfigFormat::from_path(&config_path).parse(&contents)?;
+    settings.validate();
+    Ok(settings)
+}
+
 /// 設定ファイルを安全に保存するよ！ (アトミック書き込み)
 pub fn save_settings(settings: &Settings) -> Result<(), String> {
     let config_path = get_config_path().map_err(|e| e.to_string())?;
     let tmp_path = config_path.with_extension("tmp");
 
-    let toml_string = toml::to_string_pretty(settings)
+    let contents = ConfigFormat::from_path(&config_path)
+        .serialize(settings)
         .map_err(|e| format!("Failed to serialize settings: {}", e))?;
 
     // 1. 一時ファイルに書き出す
-    fs::write(&tmp_path, toml_string)
+    fs::write(&tmp_path, contents)
         .map_err(|e| format!("Failed to write temporary config file: {}", e))?;
 
     // 2. 元のファイルにリネーム（アトミックな置き換え）
@@ -85,3 +147,126 @@ pub fn save_settings(settings: &Settings) -> Result<(), String> {
     log::debug!("Settings saved atomically to {:?}", config_path);
     Ok(())
 }
+
+/// グループ1つ分の設定を, 他の PC にも持っていける単独の TOML ファイルに書き出すよ (トレイの Export Group)。
+pub fn export_group(child: &ChildSettings, dest: &Path) -> Result<(), String> {
+    let contents = toml::to_string_pretty(child).map_err(|e| format!("Failed to serialize group: {}", e))?;
+    fs::write(dest, contents).map_err(|e| format!("Failed to write {:?}: {}", dest, e))?;
+    log::info!("Exported group to {:?}", dest);
+    Ok(())
+}
+
+/// export_group で書き出したファイルから, グループの設定を読み込むよ (トレイの Import Group)。
+/// ID はファイルに入っていないから, 呼び出し側で新しく付けてね。
+pub fn import_group(src: &Path) -> Result<ChildSettings, String> {
+    let contents = fs::read_to_string(src).map_err(|e| format!("Failed to read {:?}: {}", src, e))?;
+    let mut child: ChildSettings = toml::from_str(&contents).map_err(|e| e.to_string())?;
+    child.validate();
+    Ok(child)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::models::PersistentIconInfo;
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(ConfigFormat::from_path(Path::new("config.json")), ConfigFormat::Json);
+        assert_eq!(ConfigFormat::from_path(Path::new("config.JSON")), ConfigFormat::Json);
+        assert_eq!(ConfigFormat::from_path(Path::new("config.toml")), ConfigFormat::Toml);
+        assert_eq!(ConfigFormat::from_path(Path::new("config")), ConfigFormat::Toml);
+    }
+
+    #[test]
+    fn test_settings_round_trip_through_json() {
+        // グループを1つ持った設定を JSON に書き出して, 読み戻しても同じになるか確かめるよ！
+        let mut settings = Settings::default();
+        settings.app.font_size = 14.0;
+        settings.children.insert(
+            "1700000000000".to_string(),
+            ChildSettings {
+                bg_color: "#112233AA".to_string(),
+                icons: vec![PersistentIconInfo {
+                    path: PathBuf::from(r"C:\Tools\app.exe"),
+                    section: Some("Tools".to_string()),
+                    custom_icon_path: None,
+                    custom_name: Some("App".to_string()),
+                    url: None,
+                }],
+                ..Default::default()
+            },
+        );
+
+        let json = ConfigFormat::Json.serialize(&settings).expect("serialize");
+        let restored = ConfigFormat::Json.parse(&json).expect("parse");
+
+        assert_eq!(restored.app.font_size, 14.0);
+        let child = &restored.children["1700000000000"];
+        assert_eq!(child.bg_color, "#112233AA");
+        assert_eq!(child.icons.len(), 1);
+        assert_eq!(child.icons[0].path, PathBuf::from(r"C:\Tools\app.exe"));
+        assert_eq!(child.icons[0].section.as_deref(), Some("Tools"));
+        // 書き出し直しても同じ文字列になるはずだよ
+        assert_eq!(ConfigFormat::Json.serialize(&restored).expect("serialize again"), json);
+    }
+
+    #[test]
+    fn test_child_font_size_round_trip_through_toml() {
+        // グループごとの文字サイズは, 指定したものだけが保存されて読み戻せるはずだよ
+        let mut settings = Settings::default();
+        settings.children.insert(
+            "1".to_string(),
+            ChildSettings { font_size: Some(18.0), ..Default::default() },
+        );
+        settings.children.insert("2".to_string(), ChildSettings::default());
+
+        let toml = ConfigFormat::Toml.serialize(&settings).expect("serialize");
+        let restored = ConfigFormat::Toml.parse(&toml).expect("parse");
+
+        assert_eq!(restored.children["1"].font_size, Some(18.0));
+        assert_eq!(restored.children["2"].font_size, None);
+    }
+
+    #[test]
+    fn test_group_export_round_trip() {
+        // 書き出したグループを読み戻すと, アイコンも見た目もそのままのはずだよ
+        let child = ChildSettings {
+            title: "Tools".to_string(),
+            bg_color: "#112233AA".to_string(),
+            icons: vec![PersistentIconInfo {
+                path: PathBuf::from(r"%USERPROFILE%\Tools\app.exe"),
+                section: None,
+                custom_icon_path: None,
+                custom_name: Some("App".to_string()),
+                url: None,
+            }],
+            ..Default::default()
+        };
+        let path = std::env::temp_dir().join(format!("desktop_grouping_export_{}.toml", std::process::id()));
+
+        export_group(&child, &path).expect("export");
+        let restored = import_group(&path);
+        let _ = fs::remove_file(&path);
+        let restored = restored.expect("import");
+
+        assert_eq!(restored.title, "Tools");
+        assert_eq!(restored.bg_color, "#112233AA");
+        assert_eq!(restored.icons.len(), 1);
+        assert_eq!(restored.icons[0].path, PathBuf::from(r"%USERPROFILE%\Tools\app.exe"));
+        assert_eq!(restored.icons[0].custom_name.as_deref(), Some("App"));
+    }
+
+    #[test]
+    fn test_children_restore_from_front_to_back() {
+        // 重なり順が大きいほど手前だから, 先に開く (奥へ置いていく) ことになるよ
+        let mut settings = Settings::default();
+        settings.children.insert("a".to_string(), ChildSettings { z_order: 1, ..Default::default() });
+        settings.children.insert("b".to_string(), ChildSettings { z_order: 5, ..Default::default() });
+        settings.children.insert("c".to_string(), ChildSettings::default());
+
+        let ids: Vec<String> = settings.children_front_to_back().into_iter().map(|(id, _)| id).collect();
+        assert_eq!(ids, ["b", "a", "c"]);
+        assert_eq!(settings.next_z_order(), 6);
+    }
+}
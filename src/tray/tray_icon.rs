@@ -6,6 +6,7 @@ use tray_icon::{
 // トレイメニューのIDを定数化するよっ！٩(ˊᗜˋ*)و
 pub const MENU_ID_NEW_GROUP: &str = "1001";
 pub const MENU_ID_HELP: &str = "1003";
+pub const MENU_ID_SETTINGS: &str = "1014";
 pub const MENU_ID_OPEN_SETTINGS: &str = "1004";
 pub const MENU_ID_QUIT: &str = "1002";
 
@@ -24,13 +25,33 @@ pub fn create_tray() -> TrayIcon {
     // 「新しいグループを作る」メニュー項目だよ！クリックできるように true にしてるんだ♪
     let new_group = MenuItem::with_id("1001", "New Group", true, None);
     let help_item = MenuItem::with_id("1003", "Help", true, None); // Help メニュー項目
-    let open_settings = MenuItem::with_id("1004", "Open Settings Location", true, None);
+    let settings_item = MenuItem::with_id("1014", "Settings", true, None); // 設定ファイルをエディタで開く
+    let open_settings = MenuItem::with_id("1004", "Open Config Folder", true, None);
+    let refresh_icons = MenuItem::with_id("1005", "Refresh Icons", true, None); // アイコンを取り直す
+    let tidy_groups = MenuItem::with_id("1006", "Tidy Groups", true, None); // 全グループのアイコンを詰めて並べ直す
+    let dock_groups = MenuItem::with_id("1012", "Dock Groups", true, None); // 手で動かしたグループも端に並べ直す (auto_dock のとき)
+    let gather_groups = MenuItem::with_id("1013", "Gather Groups", true, None); // どの画面にも映っていないグループをメイン画面に集める
+    let find_duplicates = MenuItem::with_id("1007", "Find Duplicates", true, None); // 重複したアイコンを探す
+    let save_now = MenuItem::with_id("1008", "Save Now", true, None); // 保存待ちの変更をすぐに書き出す
+    let reload_config = MenuItem::with_id("1009", "Reload Config", true, None); // 手で直した設定ファイルを読み直す
+    let export_group = MenuItem::with_id("1010", "Export Group...", true, None); // 最後にカーソルを乗せたグループをファイルに書き出す
+    let import_group = MenuItem::with_id("1011", "Import Group...", true, None); // 書き出したグループを取り込む
     let quit_i = MenuItem::with_id("1002", "Quit", true, None);
 
     menu.append_items(&[
         &new_group,
         &help_item,
+        &settings_item,
         &open_settings,
+        &refresh_icons,
+        &tidy_groups,
+        &dock_groups,
+        &gather_groups,
+        &find_duplicates,
+        &save_now,
+        &reload_config,
+        &export_group,
+        &import_group,
         &PredefinedMenuItem::separator(),
         &quit_i,
     ])
@@ -41,6 +62,8 @@ pub fn create_tray() -> TrayIcon {
     return TrayIconBuilder::new()
         // さっき作ったメニューを、トレイアイコンにセット！
         .with_menu(Box::new(menu))
+        // 左クリックはランチャーを開くのに使うから, メニューは右クリックだけにするよ
+        .with_menu_on_left_click(false)
         // マウスを乗せた時に出る説明文（ツールチップ）も設定するよ！
         .with_tooltip("Desktop Grouping")
         // アプリのアイコンも忘れずに設定！リソースID 1番のアイコンを使うんだね！(・∀・)
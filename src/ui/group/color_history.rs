@@ -0,0 +1,80 @@
+use std::collections::VecDeque;
+
+/// 覚えておく色の数だよ。
+const MAX_HISTORY: usize = 16;
+
+/// 背景色と透明度の組だよ。
+#[derive(Clone, PartialEq)]
+pub struct ColorState {
+    pub bg_color_hex: String,
+    pub opacity: f32,
+}
+
+/// 背景色の変更履歴 (メモリ上だけ) だよ！
+/// #Random を試しているうちに元の色を見失っても, 行ったり来たりして戻せるようにするんだ。
+pub struct ColorHistory {
+    entries: VecDeque<ColorState>,
+    cursor: usize,    // 今表示している履歴の位置
+    coalescing: bool, // Alt + ドラッグ中のように, 連続した変更を1つにまとめている最中か
+}
+
+impl ColorHistory {
+    pub fn new(initial: ColorState) -> Self {
+        Self {
+            entries: VecDeque::from([initial]),
+            cursor: 0,
+            coalescing: false,
+        }
+    }
+
+    /// 新しい色を記録するよ。戻った先から変更したら, それより先の履歴は捨てるね。
+    pub fn record(&mut self, state: ColorState) {
+        self.coalescing = false;
+        self.entries.truncate(self.cursor + 1);
+        if self.entries.back() == Some(&state) {
+            return;
+        }
+        self.entries.push_back(state);
+        if self.entries.len() > MAX_HISTORY {
+            self.entries.pop_front();
+        }
+        self.cursor = self.entries.len() - 1;
+    }
+
+    /// 連続した変更 (透明度のドラッグ調整など) を, 1つの履歴にまとめて記録するよ。
+    pub fn record_coalesced(&mut self, state: ColorState) {
+        if self.coalescing && self.cursor + 1 == self.entries.len() {
+            if let Some(last) = self.entries.back_mut() {
+                *last = state;
+            }
+            return;
+        }
+        self.record(state);
+        self.coalescing = true;
+    }
+
+    /// 連続した変更の区切りだよ。ドラッグが終わったら呼んでね。
+    pub fn seal(&mut self) {
+        self.coalescing = false;
+    }
+
+    /// 1つ前の色を返すよ。
+    pub fn back(&mut self) -> Option<ColorState> {
+        self.coalescing = false;
+        if self.cursor == 0 {
+            return None;
+        }
+        self.cursor -= 1;
+        self.entries.get(self.cursor).cloned()
+    }
+
+    /// 1つ先の色を返すよ。
+    pub fn forward(&mut self) -> Option<ColorState> {
+        self.coalescing = false;
+        if self.cursor + 1 >= self.entries.len() {
+            return None;
+        }
+        self.cursor += 1;
+        self.entries.get(self.cursor).cloned()
+    }
+}
@@ -1,23 +1,89 @@
 use windows::Win32::Foundation::{POINT, RECT, HWND};
-use windows::Win32::UI::Input::KeyboardAndMouse::{GetKeyState, VK_CONTROL, VK_SHIFT, VK_MENU};
 use windows::Win32::UI::WindowsAndMessaging::{GetCursorPos, GetWindowRect};
-use crate::graphics::layout;
+use crate::graphics::layout::GroupLayout;
+use crate::ui::keymap::{KeyAction, Keymap, Modifiers};
 
 /// ユーザーの操作によって発生した抽象的なアクションだよ！
 pub enum InteractionAction {
     None,
     Move { dx: i32, dy: i32 },   // 前回のフレームからの移動量
-    Resize { dw: i32, dh: i32 }, // 前回のフレームからのリサイズ量
+    Resize { dx: i32, dy: i32, edges: (i32, i32) }, // 前回のフレームからのマウスの移動量と, 動かす辺 (resize_edges を見てね)
     ChangeOpacity { delta: f32 }, // 透明度の変化量 (不連続)
     ChangeOpacityContinuous { delta: f32 }, // 透明度の変化量 (連続)
+    ChangeBorderWidth { delta: f32 }, // 枠線の太さの変化量
+    ChangeWindowOpacity { delta: i16 }, // ウィンドウ全体の不透明度の変化量 (0 ~ 255 の段階で)
     ChangeIconSize { size: f32 }, // アイコンの論理サイズを直接指定
-    PasteColor,                  // クリップボードからの貼り付け要求 (色 or サイズ)
+    PasteColor,                  // クリップボードからの貼り付け要求 (色 or サイズ or セクション)
+    PickColor,                   // 色の選択ダイアログで背景色を選ぶ
+    SetSection { section: Option<String> }, // ホバー中のアイコンのセクションを変更
     ExecuteIcon { index: usize }, // アイコンの実行
     DeleteIcon { index: usize },  // アイコンの削除
+    DuplicateIcon { index: usize }, // アイコンを複製して, すぐ後ろに入れる
     OpenLocation { index: usize }, // ファイルの場所を開く
-    ReorderIcon { from: usize, to: usize }, // アイコンの並び替え
+    CopyIconPath { index: usize }, // アイコンのパスをクリップボードにコピーする
+    ReorderIcon { from: usize, to: usize }, // アイコンを to の位置へ移す (並び替え)
+    DragIcon { index: usize, target: Option<usize>, pos: (f32, f32) }, // アイコンをドラッグ中 (落とす先とカーソル位置)
     DeleteGroup,                 // グループ自体の削除
     HoverChanged { index: Option<usize> }, // ホバー対象の変更
+    StepColorHistory { forward: bool }, // 背景色の履歴を戻る / 進む
+    EmptyClick,                  // 何もない場所を (修飾キーなしで) 左クリック
+    UpdateSelection { rect: (f32, f32, f32, f32) }, // 範囲選択の矩形が変わった
+    FitToContent,                // グループの大きさをアイコンに合わせる
+    DeleteSelected,              // 選択中のアイコンをまとめて削除
+    ExecuteSelected,             // 選択中のアイコンをまとめて開く
+    ExecuteAll,                  // グループのアイコンを全部, 少しずつ間をあけて開く
+    BeginRename,                 // タイトルの編集を始める
+    BeginIconRename { index: usize }, // アイコンの表示名の編集を始める
+    CycleBackgroundMode,         // 背景の塗り方を 単色 → グラデーション → 画像 の順に切り替える
+    CycleSizePreset,             // グループの大きさを size_presets の順に切り替える (最後は元の大きさ)
+    ToggleTopmost,               // 常に最前面に出すかどうかを切り替える
+    ToggleCollapse,              // タイトル行だけの帯に折りたたむ / 元の高さに広げる
+    ToggleLock,                  // 全部ロックする / (どれかロックされていれば) 全部解除する
+    MoveIconFocus { dx: i32, dy: i32 }, // 矢印キーでアイコンのフォーカスを上下左右に動かす
+    Scroll { notches: f32 },     // アイコンの一覧を縦にスクロールする (正なら下へ, ホイール1目盛りで 1.0)
+}
+
+impl InteractionAction {
+    /// アイコンの追加・削除・並べ替えになる操作かどうかだよ (ロック中は止めるんだ)。
+    pub fn edits_icons(&self) -> bool {
+        matches!(
+            self,
+            InteractionAction::ReorderIcon { .. }
+                | InteractionAction::DeleteIcon { .. }
+                | InteractionAction::DuplicateIcon { .. }
+                | InteractionAction::DeleteSelected
+                | InteractionAction::SetSection { .. }
+                | InteractionAction::DeleteGroup
+        )
+    }
+}
+
+/// 範囲選択を始めるのに必要なドラッグ量 (px) だよ。クリックと区別するためなんだ。
+const SELECTION_DRAG_THRESHOLD: f32 = 4.0;
+
+/// リサイズを始めたときに, 角をつかんだことにする端からの距離 (px) だよ。
+/// 小さいグループでは大きさの 1/3 までにするね。
+const RESIZE_CORNER_MARGIN: f32 = 24.0;
+
+/// ウィンドウの中の (x, y) で押したときに, どの辺を動かすかを返すよ。
+/// (横, 縦) の順で, -1 なら左 (上) の辺, 1 なら右 (下) の辺, 0 ならその向きは動かさないよ。
+/// 角の近くなら2つの辺を, それ以外は一番近い辺を1つだけ動かすね。
+fn resize_edges(x: f32, y: f32, width: f32, height: f32) -> (i32, i32) {
+    let margin_x = RESIZE_CORNER_MARGIN.min(width / 3.0);
+    let margin_y = RESIZE_CORNER_MARGIN.min(height / 3.0);
+    let near = |pos: f32, size: f32, margin: f32| {
+        if pos < margin { -1 } else if pos > size - margin { 1 } else { 0 }
+    };
+    let edges = (near(x, width, margin_x), near(y, height, margin_y));
+    if edges != (0, 0) {
+        return edges;
+    }
+    // 真ん中あたりなら, 一番近い辺だよ
+    let distances = [(x, (-1, 0)), (width - x, (1, 0)), (y, (0, -1)), (height - y, (0, 1))];
+    distances
+        .into_iter()
+        .min_by(|a, b| a.0.total_cmp(&b.0))
+        .map_or((1, 1), |(_, edges)| edges)
 }
 
 /// ウィンドウとのインタラクション（ドラッグ、リサイズ等）を管理するよ。
@@ -25,9 +91,15 @@ pub struct InteractionHandler {
     last_screen_pos: Option<POINT>,
     is_dragging: bool,
     is_resizing: bool,
+    resize_edges: (i32, i32), // リサイズで動かす辺 (resize_edges を見てね)
     is_adjusting_opacity: bool,
     dragged_icon_index: Option<usize>, // 現在ドラッグされているアイコンのインデックス
+    icon_drag_origin: Option<(f32, f32)>, // アイコンの上で押した位置 (ドラッグの始点)
+    is_dragging_icon: bool, // アイコンを少し動かして, 本当にドラッグを始めたか
+    drop_target: Option<usize>, // 今離したら移す先のアイコン (None ならドラッグは取り消し)
     hovered_index: Option<usize>, // 現在ホバーされているアイコンのインデックス
+    selection_origin: Option<(f32, f32)>, // 空白部分で押した位置 (範囲選択の始点)
+    is_selecting: bool, // 範囲選択のドラッグ中か
 }
 
 impl InteractionHandler {
@@ -36,14 +108,26 @@ impl InteractionHandler {
             last_screen_pos: None,
             is_dragging: false,
             is_resizing: false,
+            resize_edges: (1, 1),
             is_adjusting_opacity: false,
             dragged_icon_index: None,
+            icon_drag_origin: None,
+            is_dragging_icon: false,
+            drop_target: None,
             hovered_index: None,
+            selection_origin: None,
+            is_selecting: false,
         }
     }
 
     /// マウス座標からアイコンのインデックスを特定するよ！
-    fn hit_test(hwnd: HWND, icon_count: usize, icon_size: f32, font_size: f32) -> Option<usize> {
+    fn hit_test(hwnd: HWND, layout: &GroupLayout) -> Option<usize> {
+        let (rel_x, rel_y) = Self::cursor_in_window(hwnd)?;
+        layout.hit_item(rel_x, rel_y)
+    }
+
+    /// カーソル位置をウィンドウ左上からの相対座標で返すよ。
+    pub fn cursor_in_window(hwnd: HWND) -> Option<(f32, f32)> {
         let mut pt = POINT::default();
         let mut rect = RECT::default();
         unsafe {
@@ -52,76 +136,101 @@ impl InteractionHandler {
             }
         }
 
-        let rel_x = (pt.x - rect.left) as f32;
-        let rel_y = (pt.y - rect.top) as f32;
-        let width = (rect.right - rect.left) as f32;
-
-        let layouts = layout::calculate_grid_layout(width, icon_count, icon_size, font_size, 1.0);
-        for (i, layout) in layouts.iter().enumerate() {
-            if rel_x >= layout.hit_rect.left && rel_x <= layout.hit_rect.right &&
-               rel_y >= layout.hit_rect.top && rel_y <= layout.hit_rect.bottom {
-                return Some(i);
-            }
-        }
-
-        None
+        Some(((pt.x - rect.left) as f32, (pt.y - rect.top) as f32))
     }
 
     /// マウスボタンが押されたときの処理だよ。
-    pub fn handle_lbutton_down(&mut self, hwnd: HWND, icon_count: usize, icon_size: f32, font_size: f32) {
+    pub fn handle_lbutton_down(&mut self, hwnd: HWND, layout: &GroupLayout) -> InteractionAction {
         let mut pt = POINT::default();
         unsafe {
             let _ = GetCursorPos(&mut pt);
         }
 
-        let is_ctrl = unsafe { (GetKeyState(VK_CONTROL.0 as i32) as u16 & 0x8000) != 0 };
-        let is_shift = unsafe { (GetKeyState(VK_SHIFT.0 as i32) as u16 & 0x8000) != 0 };
-        let is_alt = unsafe { (GetKeyState(VK_MENU.0 as i32) as u16 & 0x8000) != 0 };
+        // 修飾キーの割り当ては keybindings で変えられるよ (既定は Ctrl で移動, Shift でリサイズ, Alt で透明度)
+        let keymap = Keymap::current();
+        let modifiers = Modifiers::current();
 
-        if is_ctrl {
+        if modifiers.holds(keymap.move_group) {
             self.is_dragging = true;
-        } else if is_shift {
+        } else if modifiers.holds(keymap.resize_group) {
             self.is_resizing = true;
-        } else if is_alt {
+            // 押した場所に近い辺 (角) をつかむよ。普通のウィンドウの縁と同じ感覚だね
+            let mut rect = RECT::default();
+            unsafe { let _ = GetWindowRect(hwnd, &mut rect); }
+            let (width, height) = ((rect.right - rect.left) as f32, (rect.bottom - rect.top) as f32);
+            self.resize_edges = Self::cursor_in_window(hwnd)
+                .map_or((1, 1), |(x, y)| resize_edges(x, y, width, height));
+        } else if modifiers.holds(keymap.adjust_opacity) {
             self.is_adjusting_opacity = true;
         } else {
             // 修飾キーがない場合はアイコンのドラッグ（並び替え）を開始するよ
-            self.dragged_icon_index = Self::hit_test(hwnd, icon_count, icon_size, font_size);
+            self.dragged_icon_index = Self::hit_test(hwnd, layout);
+            if self.dragged_icon_index.is_some() {
+                self.icon_drag_origin = Self::cursor_in_window(hwnd);
+            }
         }
 
         self.last_screen_pos = Some(pt);
+
+        if !self.is_dragging && !self.is_resizing && !self.is_adjusting_opacity && self.dragged_icon_index.is_none() {
+            // タイトル行のクリックは名前の編集だよ
+            if Self::cursor_in_window(hwnd).is_some_and(|(x, y)| layout.hit_title(x, y)) {
+                return InteractionAction::BeginRename;
+            }
+            // 空白部分からのドラッグは範囲選択になるよ
+            self.selection_origin = Self::cursor_in_window(hwnd);
+            return InteractionAction::EmptyClick;
+        }
+        InteractionAction::None
     }
 
     /// ダブルクリックされたときの処理だよ。
-    pub fn handle_lbutton_dblclk(&self, hwnd: HWND, icon_count: usize, icon_size: f32, font_size: f32) -> InteractionAction {
-        if let Some(index) = Self::hit_test(hwnd, icon_count, icon_size, font_size) {
+    pub fn handle_lbutton_dblclk(&self, hwnd: HWND, layout: &GroupLayout) -> InteractionAction {
+        if let Some(index) = Self::hit_test(hwnd, layout) {
+            // 修飾キー付きなら起動しないで, 表示名を付け直すよ
+            if Modifiers::current().holds(Keymap::current().rename_icon_click) {
+                return InteractionAction::BeginIconRename { index };
+            }
             return InteractionAction::ExecuteIcon { index };
         }
-        InteractionAction::None
+        // 何もない場所のダブルクリックは, 大きさを中身に合わせるよ
+        InteractionAction::FitToContent
     }
 
     /// 右クリックされたときの処理だよ。
-    pub fn handle_rbutton_down(&self, _hwnd: HWND, _icon_count: usize, _icon_size: f32, _font_size: f32) -> InteractionAction {
+    pub fn handle_rbutton_down(&self, _hwnd: HWND, _layout: &GroupLayout) -> InteractionAction {
         // ダウン時は何もしないか, メニュー表示の準備のみ。
         InteractionAction::None
     }
 
     /// 右クリックが離されたときの処理だよ。
-    pub fn handle_rbutton_up(&self, hwnd: HWND, icon_count: usize, icon_size: f32, font_size: f32) -> InteractionAction {
-        use windows::Win32::UI::Input::KeyboardAndMouse::GetAsyncKeyState;
-        let is_ctrl = unsafe { (GetAsyncKeyState(VK_CONTROL.0 as i32) as u16 & 0x8000) != 0 };
-        let hit_index = Self::hit_test(hwnd, icon_count, icon_size, font_size);
+    pub fn handle_rbutton_up(&self, hwnd: HWND, layout: &GroupLayout) -> InteractionAction {
+        let keymap = Keymap::current();
+        let modifiers = Modifiers::current();
+        let is_delete = modifiers.holds(keymap.delete_click);
+        let hit_index = Self::hit_test(hwnd, layout);
 
-        match (hit_index, is_ctrl) {
+        match (hit_index, is_delete) {
             (Some(index), true) => InteractionAction::DeleteIcon { index },
             (None, true) => InteractionAction::DeleteGroup,
+            // 修飾キー付きなら, フォルダを開く代わりにパスをコピーするよ
+            (Some(index), false) if modifiers.holds(keymap.copy_path_click) => InteractionAction::CopyIconPath { index },
             (Some(index), false) => InteractionAction::OpenLocation { index },
-            _ => InteractionAction::None,
+            (None, false) => InteractionAction::PickColor,
         }
     }
 
+    /// マウスがウィンドウの外に出たとき, ホバーを解除するよ。
+    pub fn handle_mouse_leave(&mut self) -> InteractionAction {
+        if self.hovered_index.is_some() && self.dragged_icon_index.is_none() {
+            self.hovered_index = None;
+            return InteractionAction::HoverChanged { index: None };
+        }
+        InteractionAction::None
+    }
+
     /// マウスが動いたときの処理だよ。
-    pub fn handle_mouse_move(&mut self, hwnd: HWND, icon_count: usize, icon_size: f32, font_size: f32) -> InteractionAction {
+    pub fn handle_mouse_move(&mut self, hwnd: HWND, layout: &GroupLayout) -> InteractionAction {
         let mut pt = POINT::default();
         unsafe {
             if GetCursorPos(&mut pt).is_err() {
@@ -129,19 +238,43 @@ impl InteractionHandler {
             }
         }
 
+        // 0. 範囲選択 (少し動かしてから開始して, クリックと区別するよ)
+        if let Some((ox, oy)) = self.selection_origin {
+            if let Some((x, y)) = Self::cursor_in_window(hwnd)
+                && (self.is_selecting
+                    || (x - ox).abs() > SELECTION_DRAG_THRESHOLD
+                    || (y - oy).abs() > SELECTION_DRAG_THRESHOLD)
+            {
+                self.is_selecting = true;
+                return InteractionAction::UpdateSelection {
+                    rect: (ox.min(x), oy.min(y), ox.max(x), oy.max(y)),
+                };
+            }
+            return InteractionAction::None;
+        }
+
+        // 0.5. アイコンのドラッグ (これも少し動かしてから始めるよ。離したときに並び替えるんだ)
+        if let (Some(index), Some((ox, oy))) = (self.dragged_icon_index, self.icon_drag_origin) {
+            let Some((x, y)) = Self::cursor_in_window(hwnd) else {
+                return InteractionAction::None;
+            };
+            if !self.is_dragging_icon
+                && (x - ox).abs() <= SELECTION_DRAG_THRESHOLD
+                && (y - oy).abs() <= SELECTION_DRAG_THRESHOLD
+            {
+                return InteractionAction::None;
+            }
+            self.is_dragging_icon = true;
+            self.drop_target = layout.hit_item(x, y);
+            self.hovered_index = self.drop_target;
+            return InteractionAction::DragIcon { index, target: self.drop_target, pos: (x, y) };
+        }
+
         // 1. ホバー判定の更新
-        let new_hover = Self::hit_test(hwnd, icon_count, icon_size, font_size);
+        let new_hover = Self::hit_test(hwnd, layout);
         if new_hover != self.hovered_index {
             self.hovered_index = new_hover;
-            
-            // アイコンをドラッグ中かつ, 新しいアイコンの上にマウスが来たら並び替えを発行するよ
-            if let (Some(from), Some(to)) = (self.dragged_icon_index, new_hover) {
-                if from != to {
-                    self.dragged_icon_index = Some(to); // ドラッグ元を現在の位置に更新
-                    return InteractionAction::ReorderIcon { from, to };
-                }
-            }
-            
+
             // 他の操作を優先しつつ, ホバー変更を通知するよ
             if !self.is_dragging && !self.is_resizing && !self.is_adjusting_opacity && self.dragged_icon_index.is_none() {
                 return InteractionAction::HoverChanged { index: new_hover };
@@ -162,7 +295,7 @@ impl InteractionHandler {
             if self.is_dragging {
                 return InteractionAction::Move { dx, dy };
             } else if self.is_resizing {
-                return InteractionAction::Resize { dw: dx, dh: dy };
+                return InteractionAction::Resize { dx, dy, edges: self.resize_edges };
             } else if self.is_adjusting_opacity {
                 return InteractionAction::ChangeOpacityContinuous { delta: dx as f32 * 0.005 };
             }
@@ -171,21 +304,42 @@ impl InteractionHandler {
     }
 
     pub fn handle_mouse_wheel(&self, delta: i16) -> InteractionAction {
-        let is_ctrl = unsafe { (GetKeyState(VK_CONTROL.0 as i32) as u16 & 0x8000) != 0 };
-        if is_ctrl {
+        let keymap = Keymap::current();
+        let modifiers = Modifiers::current();
+        // 枠線の方が修飾キーが多い (既定では Ctrl + Shift) から, 先に調べるよ
+        if modifiers.holds(keymap.wheel_border_width) {
+            let step = 0.5;
+            return InteractionAction::ChangeBorderWidth { delta: if delta > 0 { step } else { -step } };
+        }
+        if modifiers.holds(keymap.wheel_window_opacity) {
+            let step = 13; // 約 5% ずつだよ
+            return InteractionAction::ChangeWindowOpacity { delta: if delta > 0 { step } else { -step } };
+        }
+        if modifiers.holds(keymap.wheel_opacity) {
             let step = 0.05;
             let delta_f = if delta > 0 { step } else { -step };
             return InteractionAction::ChangeOpacity { delta: delta_f };
         }
-        InteractionAction::None
+        // 修飾キーなしのホイールはスクロールだよ (奥に回すと上に戻るね)
+        InteractionAction::Scroll { notches: -(delta as f32) / 120.0 }
     }
 
     pub fn handle_keydown(&self, virtual_key: u16) -> InteractionAction {
-        let is_ctrl = unsafe { (GetKeyState(VK_CONTROL.0 as i32) as u16 & 0x8000) != 0 };
-        if is_ctrl && virtual_key == 'V' as u16 {
-            return InteractionAction::PasteColor;
+        match Keymap::current().action_for(Modifiers::current(), virtual_key as i32) {
+            Some(KeyAction::Paste) => InteractionAction::PasteColor,
+            _ => InteractionAction::None,
         }
-        InteractionAction::None
+    }
+
+    /// アイコンのドラッグを離したときに, 並び替える (元の位置, 移す先) を返すよ。
+    /// アイコンの上以外で離したときや, 元の場所に戻したときは None (取り消し) だよ。
+    pub fn take_icon_drop(&mut self) -> Option<(usize, usize)> {
+        if !std::mem::take(&mut self.is_dragging_icon) {
+            return None;
+        }
+        let from = self.dragged_icon_index?;
+        let to = self.drop_target.take()?;
+        (from != to).then_some((from, to))
     }
 
     pub fn handle_lbutton_up(&mut self) {
@@ -193,9 +347,17 @@ impl InteractionHandler {
         self.is_resizing = false;
         self.is_adjusting_opacity = false;
         self.dragged_icon_index = None;
+        self.icon_drag_origin = None;
+        self.is_dragging_icon = false;
+        self.drop_target = None;
         self.last_screen_pos = None;
+        self.selection_origin = None;
+        self.is_selecting = false;
     }
 
+    /// 空白部分を押したまま, まだドラッグしていない状態か (= ただのクリックになる) だよ。
+    pub fn is_pending_selection(&self) -> bool { self.selection_origin.is_some() && !self.is_selecting }
+
     pub fn is_dragging(&self) -> bool { self.is_dragging }
     pub fn is_resizing(&self) -> bool { self.is_resizing }
     pub fn is_adjusting_opacity(&self) -> bool { self.is_adjusting_opacity }
@@ -2,5 +2,7 @@ pub mod model;
 pub mod renderer;
 pub mod window;
 pub mod interaction;
+pub mod color_history;
+pub mod snap;
 
 pub use window::GroupWindow;
@@ -1,4 +1,11 @@
+use std::cmp::Ordering;
+use std::collections::HashSet;
 use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime};
+use crate::graphics::layout::{LayoutParams, HEADER_HEIGHT_RATIO};
+use crate::settings::env_path;
+use crate::settings::models::{AppSettings, BackgroundMode, LayoutMode, LockFlags, PersistentIconInfo, RowAlign, SortKey};
+use crate::win32::api::shell;
 
 /// グループウィンドウのデータを管理するよ！
 /// DirectX などの描画詳細には一切依存しないピュアなデータ層。
@@ -7,10 +14,36 @@ pub struct GroupModel {
     pub title: String,
     pub bg_color_hex: String,
     pub opacity: f32, // 0.0 ~ 1.0
+    pub bg_mode: BackgroundMode,
+    pub bg_image: Option<PathBuf>,
     pub icon_size: f32,
+    pub layout_mode: LayoutMode,
+    pub row_alignment: RowAlign, // 埋まりきらない行の寄せ方
+    pub show_labels: bool, // アイコン名を出すか (false ならアイコンだけを詰めて並べるよ)
+    pub border_width: f32, // 枠線の太さ (0.0 なら枠線なし)
+    pub window_opacity: u8, // ウィンドウ全体の不透明度 (描くときではなく, レイヤードウィンドウに設定するよ)
+    pub collapsed: bool, // タイトル行だけに折りたたんでいるか (アイコンは描かないよ)
+    pub font_size: Option<f32>, // このグループだけの文字サイズ (None なら全体の設定)
+    pub font_family: Option<String>, // このグループだけのフォント名 (None なら全体の設定)
     pub icons: Vec<IconState>,
     pub hovered_index: Option<usize>,
     pub executing_index: Option<usize>, // 一瞬だけ光らせるための状態
+    pub launch_flash: f32, // 起動したときに背景を明るくする度合い (0.0 ~ 1.0, だんだん 0 に戻るよ)
+    pub animation_frame: usize, // ホバー中のアニメーションアイコンの現在フレーム
+    pub auto_sort: Option<SortKey>, // 自動整列の基準 (None なら手動で並べ替え)
+    pub lock: LockFlags, // 移動・リサイズ・アイコンの編集のロック
+    pub scroll_offset: f32, // アイコンが収まりきらないときの縦スクロール量 (px, 描画時に丸めるよ)
+    pub hover_boost: f32, // ホバー中に不透明へ近づける度合い (0.0 ~ 1.0, 保存はしないよ)
+    pub icon_drag: Option<(usize, (f32, f32))>, // ドラッグ中のアイコンのインデックスとカーソルの位置
+    pub selected: HashSet<usize>, // 範囲選択で選ばれているアイコンのインデックス
+    pub focused_icon: Option<usize>, // 矢印キーで選んでいるアイコンのインデックス (Enter で開くよ)
+    pub selection_rect: Option<(f32, f32, f32, f32)>, // 範囲選択中の矩形 (left, top, right, bottom)
+    pub toast: Option<String>, // 一時的に重ねて表示する短いメッセージ
+    pub tooltip: Option<(usize, (f32, f32))>, // 名前の吹き出しを出すアイコンと, 出したときのカーソルの位置
+    pub name_draft: Option<String>, // タイトル (かアイコンの名前) を編集中のときの入力途中の文字列
+    pub renaming_icon: Option<usize>, // name_draft がアイコンの名前のときは, そのアイコンのインデックス
+    pub focused: bool, // キーボード操作の対象になっているか (focus_follows_cursor のときだけ)
+    pub drop_target: bool, // ファイルのドラッグがこのグループの上に来ているか
 }
 
 #[derive(Clone)]
@@ -18,6 +51,214 @@ pub struct IconState {
     pub name: String,
     pub path: PathBuf,
     pub exists: bool,
+    pub section: Option<String>, // 所属するセクション名 (None なら見出しなし)
+    pub custom_icon_path: Option<PathBuf>, // 自分で指定したアイコン画像 (読めなければシェルのアイコン)
+    pub custom_name: Option<String>, // 自分で付けた表示名 (None ならファイル名)
+    pub url: Option<String>, // インターネットショートカット (.url) や URL そのものなら, 開く先の URL
+    pub is_network: bool, // UNC パスやネットワークドライブ上のファイルか (取得や存在確認は別スレッドでするよ)
+    pub exists_unknown: bool, // ネットワーク上のファイルで, 別スレッドでの存在確認がまだ終わっていないか
+    pub folder_count: Option<usize>, // フォルダなら直下の項目数 (MAX_FOLDER_COUNT で打ち止め)。フォルダでなければ None
+    pub modified: Option<SystemTime>, // ファイルの更新日時 (変わったらアイコンを取り直すよ)。ネットワーク上や分からなければ None
+    pub folder_counted_at: Option<Instant>, // 最後に項目数を数えた時刻 (まだ数えていなければ None)
+}
+
+/// フォルダの項目数を数える上限だよ。これ以上は「999+」のように出すね。
+pub const MAX_FOLDER_COUNT: usize = 1000;
+
+/// ホバーで項目数を数え直すのは, 前に数えてからこれだけ経ってからにするよ。
+const FOLDER_RECOUNT_INTERVAL: Duration = Duration::from_secs(5);
+
+impl IconState {
+    /// パスからアイコンの状態を作るよ。表示名は拡張子なしのファイル名だよ。
+    pub fn new(path: PathBuf, section: Option<String>) -> Self {
+        let name = path
+            .file_stem()
+            .and_then(|n| n.to_str())
+            .unwrap_or("Unknown")
+            .to_string();
+        let is_network = shell::is_network_path(&path);
+        if is_network {
+            log::info!("Network path detected: {:?}", path);
+        }
+        // ネットワーク上のファイルは, 別スレッドで確かめ終わるまであることにしておくよ
+        let exists = is_network || path.exists();
+        let url = if exists && !is_network { read_url_shortcut(&path) } else { None };
+        let folder_count = if exists && !is_network { count_folder(&path) } else { None };
+        let modified = if exists && !is_network { file_modified(&path) } else { None };
+        let folder_counted_at = (!is_network).then(Instant::now);
+        Self {
+            name,
+            path,
+            exists,
+            section,
+            custom_icon_path: None,
+            custom_name: None,
+            url,
+            is_network,
+            exists_unknown: is_network,
+            folder_count,
+            modified,
+            folder_counted_at,
+        }
+    }
+
+    /// ファイルではない URL そのものからアイコンの状態を作るよ。表示名はホスト名だよ。
+    /// パスの代わりにも URL を入れておくから, 保存しても同じものに戻せるね。
+    pub fn from_url(url: String, section: Option<String>) -> Self {
+        let name = url
+            .split_once("://")
+            .map_or(url.as_str(), |(_, rest)| rest)
+            .split(['/', '?', '#'])
+            .next()
+            .filter(|host| !host.is_empty())
+            .unwrap_or(&url)
+            .to_string();
+        Self {
+            name,
+            path: PathBuf::from(&url),
+            exists: true,
+            section,
+            custom_icon_path: None,
+            custom_name: None,
+            url: Some(url),
+            is_network: false,
+            exists_unknown: false,
+            folder_count: None,
+            modified: None,
+            folder_counted_at: None,
+        }
+    }
+
+    /// ファイルを持たない, URL そのもののアイコンかどうかだよ。
+    pub fn is_web_link(&self) -> bool {
+        self.url.as_deref().is_some_and(|url| self.path.as_os_str() == url)
+    }
+
+    /// 実行するときに開くものだよ。URL があればファイルの代わりにそっちを開くね。
+    pub fn launch_target(&self) -> PathBuf {
+        match &self.url {
+            Some(url) => PathBuf::from(url),
+            None => self.path.clone(),
+        }
+    }
+
+    /// 画面に出す名前だよ。自分で付けた名前があればそっちを使うね。
+    pub fn label(&self) -> &str {
+        self.custom_name.as_deref().unwrap_or(&self.name)
+    }
+
+    /// ファイルの更新日時を調べ直すよ。変わっていたら true だよ (描くときにアイコンが取り直されるね)。
+    /// 描画のたびにファイルを見に行くと重いから, 時々のチェックでだけ調べるんだ。
+    pub fn recheck_modified(&mut self) -> bool {
+        if self.is_web_link() || self.is_network {
+            return false;
+        }
+        let modified = if self.exists { file_modified(&self.path) } else { None };
+        let changed = modified != self.modified;
+        self.modified = modified;
+        changed
+    }
+
+    /// ファイルがあるかどうかを調べ直すよ。表示名やセクションはそのままだよ。
+    /// ネットワーク上のファイルは, 次に描くときに別スレッドで確かめ直すよ。
+    pub fn refresh(&mut self) {
+        if self.is_web_link() {
+            return;
+        }
+        if self.is_network {
+            self.exists_unknown = true;
+            return;
+        }
+        self.exists = self.path.exists();
+        if self.exists {
+            self.url = read_url_shortcut(&self.path).or(self.url.take());
+        }
+        self.recount_folder();
+        self.recheck_modified();
+    }
+
+    /// フォルダの項目数を数え直すよ。変わっていたら true だよ。
+    /// ネットワーク上のフォルダは待たされることがあるから, 数えないよ。
+    pub fn recount_folder(&mut self) -> bool {
+        if self.is_web_link() || self.is_network {
+            return false;
+        }
+        let count = if self.exists { count_folder(&self.path) } else { None };
+        let changed = count != self.folder_count;
+        self.folder_count = count;
+        self.folder_counted_at = Some(Instant::now());
+        changed
+    }
+
+    /// 少し前に数えたばかりなら数え直さない recount_folder だよ。
+    /// カーソルが乗るたびにフォルダを読みに行くと重いから, ホバーではこっちを使ってね。
+    pub fn recount_folder_if_stale(&mut self) -> bool {
+        if self.folder_counted_at.is_some_and(|at| at.elapsed() < FOLDER_RECOUNT_INTERVAL) {
+            return false;
+        }
+        self.recount_folder()
+    }
+
+    /// 手元のファイルがあるかどうかだけを, 軽く調べ直すよ。変わっていたら true だよ。
+    /// ネットワーク上のファイルは待たされることがあるから, ここでは調べないよ。
+    pub fn recheck_exists(&mut self) -> bool {
+        if self.is_web_link() || self.is_network {
+            return false;
+        }
+        let exists = self.path.exists();
+        if exists == self.exists {
+            return false;
+        }
+        self.refresh();
+        true
+    }
+
+    /// 同じファイルかどうかを比べるためのキーだよ。
+    /// 正規化したパスを小文字にするから, 書き方や大文字小文字が違っても同じになるね。
+    /// (ネットワーク上のパスは固まらないように正規化しないよ)
+    pub fn identity(&self) -> String {
+        let path = if self.is_network {
+            self.path.clone()
+        } else {
+            std::fs::canonicalize(&self.path).unwrap_or_else(|_| self.path.clone())
+        };
+        path.to_string_lossy().to_lowercase()
+    }
+
+    /// ホバー中にアニメーションできるかもしれない画像 (GIF / ICO) かどうかだよ。
+    /// 実際に複数フレームあるかは描画側で読み込んだときに判断するんだ。
+    pub fn is_animatable(&self) -> bool {
+        self.path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| e.eq_ignore_ascii_case("gif") || e.eq_ignore_ascii_case("ico"))
+    }
+
+    /// 並べ替えの基準に従って2つのアイコンを比べるよ。
+    pub fn compare(&self, other: &Self, key: SortKey) -> Ordering {
+        let by_name = || self.label().to_lowercase().cmp(&other.label().to_lowercase());
+        match key {
+            SortKey::Name => by_name(),
+            SortKey::NameDesc => by_name().reverse(),
+            SortKey::Extension => {
+                // フォルダは拡張子に関係なく先に並べるよ
+                let is_folder = |i: &Self| i.url.is_none() && i.path.is_dir();
+                let ext = |i: &Self| i.path.extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default();
+                is_folder(other).cmp(&is_folder(self)).then_with(|| ext(self).cmp(&ext(other))).then_with(by_name)
+            }
+        }
+    }
+
+    /// 設定ファイル保存用の形に変換するよ。
+    pub fn to_persistent(&self) -> PersistentIconInfo {
+        PersistentIconInfo {
+            path: self.path.clone(),
+            section: self.section.clone(),
+            custom_icon_path: self.custom_icon_path.clone(),
+            custom_name: self.custom_name.clone(),
+            url: self.url.clone(),
+        }
+    }
 }
 
 impl GroupModel {
@@ -27,18 +268,26 @@ impl GroupModel {
         bg_color_hex: String,
         opacity: f32,
         icon_size: f32,
-        initial_icons: Vec<PathBuf>,
+        initial_icons: Vec<PersistentIconInfo>,
     ) -> Self {
+        // %USERPROFILE% などの環境変数を含むパスは, ここで実際のパスに展開するよ
         let icons = initial_icons
             .into_iter()
-            .map(|path| {
-                let name = path
-                    .file_stem()
-                    .and_then(|n| n.to_str())
-                    .unwrap_or("Unknown")
-                    .to_string();
-                let exists = path.exists();
-                IconState { name, path, exists }
+            .map(|info| {
+                // URL そのもののアイコンは, パスにも URL が入っているよ
+                let icon = match info.url {
+                    Some(url) if info.path.as_os_str() == url.as_str() => IconState::from_url(url, info.section),
+                    url => {
+                        let icon = IconState::new(env_path::expand(&info.path), info.section);
+                        // ファイルが読めないときは, 前に保存した URL を使うよ
+                        IconState { url: icon.url.clone().or(url), ..icon }
+                    }
+                };
+                IconState {
+                    custom_icon_path: info.custom_icon_path.map(|p| env_path::expand(&p)),
+                    custom_name: info.custom_name,
+                    ..icon
+                }
             })
             .collect();
 
@@ -47,10 +296,207 @@ impl GroupModel {
             title,
             bg_color_hex,
             opacity,
+            bg_mode: BackgroundMode::default(),
+            bg_image: None,
             icon_size,
+            layout_mode: LayoutMode::default(),
+            row_alignment: RowAlign::default(),
+            show_labels: true,
+            border_width: 1.5,
+            window_opacity: 255,
+            collapsed: false,
+            font_size: None,
+            font_family: None,
             icons,
             hovered_index: None,
             executing_index: None,
+            launch_flash: 0.0,
+            animation_frame: 0,
+            auto_sort: None,
+            lock: LockFlags::default(),
+            scroll_offset: 0.0,
+            hover_boost: 0.0,
+            icon_drag: None,
+            selected: HashSet::new(),
+            focused_icon: None,
+            selection_rect: None,
+            toast: None,
+            tooltip: None,
+            name_draft: None,
+            renaming_icon: None,
+            focused: false,
+            drop_target: false,
         }
     }
+
+    /// 実際に描画するときの透明度だよ。保存されている opacity から hover_boost の分だけ 1.0 に近づけるね。
+    pub fn render_opacity(&self) -> f32 {
+        self.opacity + (1.0 - self.opacity) * self.hover_boost
+    }
+
+    /// 各アイコンのセクション名をレイアウト計算用に並べて返すよ。
+    pub fn sections(&self) -> Vec<Option<&str>> {
+        self.icons.iter().map(|i| i.section.as_deref()).collect()
+    }
+
+    /// このグループで使う文字サイズだよ (グループごとの指定がなければ全体の設定)。
+    pub fn font_size(&self, app: &AppSettings) -> f32 {
+        self.font_size.unwrap_or(app.font_size)
+    }
+
+    /// このグループで使うフォント名だよ (グループごとの指定がなければ全体の設定)。
+    pub fn font_family<'a>(&'a self, app: &'a AppSettings) -> &'a str {
+        self.font_family.as_deref().unwrap_or(&app.font_family)
+    }
+
+    /// タイトルを編集中かどうかだよ (アイコンの名前の編集中は false だよ)。
+    pub fn is_editing_title(&self) -> bool {
+        self.name_draft.is_some() && self.renaming_icon.is_none()
+    }
+
+    /// レイアウト計算用のパラメータを作るよ。
+    pub fn layout_params(&self, app: &AppSettings) -> LayoutParams {
+        let font_size = self.font_size(app);
+        LayoutParams {
+            icon_size: self.icon_size,
+            font_size,
+            scale_factor: 1.0,
+            // タイトルがあるか編集中なら, 先頭にタイトル行を置くよ
+            title_height: if self.title.is_empty() && !self.is_editing_title() {
+                0.0
+            } else {
+                font_size * HEADER_HEIGHT_RATIO
+            },
+            grid_gap: app.grid_gap,
+            mode: self.layout_mode,
+            label_lines: if self.show_labels { app.max_label_lines } else { 0 },
+            row_align: self.row_alignment,
+        }
+    }
+
+    /// セクションに追加するときの挿入位置を返すよ。
+    /// 同じセクションの最後のアイコンの直後 (なければ末尾) だよ。
+    pub fn insertion_index_for_section(&self, section: Option<&str>) -> usize {
+        self.icons
+            .iter()
+            .rposition(|i| i.section.as_deref() == section)
+            .map(|pos| pos + 1)
+            .unwrap_or(self.icons.len())
+    }
+
+    /// アイコンのセクションを変更するよ。
+    /// 見出しの下にまとまるように, 移動先セクションの末尾へ並べ替えるね。
+    /// 戻り値は移動後のインデックスだよ。
+    pub fn set_icon_section(&mut self, index: usize, section: Option<String>) -> Option<usize> {
+        if index >= self.icons.len() {
+            return None;
+        }
+        let mut icon = self.icons.remove(index);
+        icon.section = section;
+        let new_index = self.insertion_index_for_section(icon.section.as_deref());
+        self.icons.insert(new_index, icon);
+        Some(new_index)
+    }
+
+    /// アイコンを並べ替えるよ。セクションの並び順はそのままで, 各セクションの中だけを整列するね。
+    /// 安定な並べ替えだから, 同じ順位のアイコンは元の順番のままだよ。
+    pub fn sort_icons(&mut self, key: SortKey) {
+        let section_order = self.section_order();
+        let rank = |icon: &IconState| section_order.iter().position(|s| *s == icon.section).unwrap_or(0);
+        self.icons.sort_by(|a, b| rank(a).cmp(&rank(b)).then_with(|| a.compare(b, key)));
+    }
+
+    /// アイコンを整頓するよ。並べ替えとは違って, 順番はできるだけそのままにするね。
+    /// 離ればなれになった同じセクションのアイコンを最初に出てくる位置にまとめて (見出しが重複しないように),
+    /// collapse_sections なら見出しも全部外すよ。何度呼んでも結果は同じで, 変わったら true を返すね。
+    pub fn compact(&mut self, collapse_sections: bool) -> bool {
+        let snapshot = |icons: &[IconState]| -> Vec<(PathBuf, Option<String>)> {
+            icons.iter().map(|i| (i.path.clone(), i.section.clone())).collect()
+        };
+        let before = snapshot(&self.icons);
+
+        if collapse_sections {
+            for icon in &mut self.icons {
+                icon.section = None;
+            }
+        }
+        let section_order = self.section_order();
+        self.icons
+            .sort_by_key(|icon| section_order.iter().position(|s| *s == icon.section).unwrap_or(0));
+
+        snapshot(&self.icons) != before
+    }
+
+    /// グループの中で重複しているアイコン (2つ目以降) のインデックスを返すよ。
+    pub fn duplicate_indices(&self) -> Vec<usize> {
+        let mut seen = HashSet::new();
+        self.icons
+            .iter()
+            .enumerate()
+            .filter(|(_, icon)| !seen.insert(icon.identity()))
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// グループの中で重複しているアイコンを, 最初の1つだけ残して取り除くよ。取り除いた数を返すね。
+    pub fn dedupe(&mut self) -> usize {
+        let duplicates = self.duplicate_indices();
+        for &index in duplicates.iter().rev() {
+            self.icons.remove(index);
+        }
+        duplicates.len()
+    }
+
+    /// セクションが最初に出てくる順に並べて返すよ。
+    fn section_order(&self) -> Vec<Option<String>> {
+        let mut section_order: Vec<Option<String>> = Vec::new();
+        for icon in &self.icons {
+            if !section_order.contains(&icon.section) {
+                section_order.push(icon.section.clone());
+            }
+        }
+        section_order
+    }
+
+    /// 自動整列が有効なら並べ替えるよ。並べ替えたら true だよ。
+    pub fn apply_auto_sort(&mut self) -> bool {
+        match self.auto_sort {
+            Some(key) => {
+                self.sort_icons(key);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// ファイルの更新日時だよ。読めなければ None だよ。
+fn file_modified(path: &std::path::Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// フォルダなら, 直下の項目数を MAX_FOLDER_COUNT まで数えるよ。フォルダでなければ None だよ。
+fn count_folder(path: &std::path::Path) -> Option<usize> {
+    if !path.is_dir() {
+        return None;
+    }
+    shell::count_folder(path, MAX_FOLDER_COUNT).ok()
+}
+
+/// インターネットショートカット (.url / .website) なら, 中の URL= の行を読むよ。
+/// 中身は INI 形式で, [InternetShortcut] セクションに URL が書いてあるんだ。
+fn read_url_shortcut(path: &std::path::Path) -> Option<String> {
+    let ext = path.extension()?.to_str()?;
+    if !ext.eq_ignore_ascii_case("url") && !ext.eq_ignore_ascii_case("website") {
+        return None;
+    }
+    let bytes = std::fs::read(path).ok()?;
+    let text = String::from_utf8_lossy(&bytes);
+    text.lines()
+        .map(str::trim)
+        .find_map(|line| {
+            let (key, value) = line.split_once('=')?;
+            key.trim().eq_ignore_ascii_case("url").then(|| value.trim().to_string())
+        })
+        .filter(|url| !url.is_empty())
 }
@@ -1,13 +1,14 @@
 use std::rc::Rc;
 use windows::Win32::Foundation::HWND;
 use crate::graphics::{Canvas, GraphicsEngine, drawing::resources::DrawingResources};
-use crate::graphics::drawing::painter;
+use crate::graphics::drawing::{diagnostics::{self, FrameStats}, painter};
 use crate::ui::group::model::GroupModel;
 
 /// グループウィンドウの描画を管理するよ！
 pub struct GroupRenderer {
     canvas: Canvas,
     resources: DrawingResources,
+    stats: FrameStats,
 }
 
 impl GroupRenderer {
@@ -23,7 +24,7 @@ impl GroupRenderer {
             engine.wic_factory.clone(),
         );
 
-        let renderer = Self { canvas, resources };
+        let renderer = Self { canvas, resources, stats: FrameStats::default() };
 
         // テキストの描画品質を向上させるよ！
         // 透過ウィンドウでは GRAYSCALE が最も綺麗に馴染むんだ。
@@ -42,24 +43,57 @@ impl GroupRenderer {
         height: f32,
         is_resizing: bool,
     ) -> Result<(), windows::core::Error> {
-        self.canvas.begin_draw();
+        let started_at = self.stats.begin_frame();
+        if !self.canvas.begin_draw() {
+            return Ok(()); // 描画先がないので, このフレームは飛ばすよ
+        }
 
         // painter に描画を依頼するよ。
-        painter::draw_group(
+        let result = painter::draw_group(
             &self.canvas.d2d_context,
             width,
             height,
             model,
             &mut self.resources,
-        )?;
+        )
+        .and_then(|_| {
+            // 診断オーバーレイ (開発者向け, 既定では無効)
+            if crate::settings::manager::get_settings_reader().app.show_diagnostics {
+                diagnostics::draw_overlay(&self.canvas.d2d_context, &self.stats, model.icons.len(), &mut self.resources)?;
+            }
+            Ok(())
+        });
 
         // リサイズ中は VSync を待たずに即座に描画を反映させることで, 
         // マウスカーソルへの追従性を極限まで高めるよ！
+        // 途中で失敗しても BeginDraw したままにならないように, EndDraw は必ず呼ぶね。
         let sync_interval = if is_resizing { 0 } else { 1 };
         self.canvas.end_draw(sync_interval)?;
+        result?;
+        self.stats.end_frame(started_at);
         Ok(())
     }
 
+    /// paths のアイコンを別スレッドで取り始めるよ。届いたら hwnd に WM_ICONS_LOADED が来るね。
+    pub fn load_icons(&mut self, hwnd: HWND, paths: Vec<std::path::PathBuf>) {
+        self.resources.load_file_icons(hwnd, paths);
+    }
+
+    /// 別スレッドから届いたアイコンを取り込むよ。届いたパスと, そのファイルがあったかどうかを返すね。
+    pub fn receive_icons(&mut self) -> Vec<(std::path::PathBuf, bool)> {
+        self.resources.receive_file_icons(&self.canvas.d2d_context)
+    }
+
+    /// キャッシュしているアイコンのビットマップを捨てるよ。アイコンを取り直したいときに呼んでね。
+    pub fn invalidate_icons(&mut self) {
+        self.resources.clear_bitmaps();
+    }
+
+    /// path のアイコンを別スレッドで取り終わっているかどうかだよ (取れなかった場合も含むね)。
+    pub fn has_loaded_icon(&self, path: &std::path::Path) -> bool {
+        self.resources.has_loaded_icon(path)
+    }
+
     /// ウィンドウサイズが変わったときに呼び出してね。
     pub fn resize(&mut self, width: u32, height: u32) -> Result<(), windows::core::Error> {
         self.canvas.resize(width, height)
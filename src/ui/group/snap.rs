@@ -0,0 +1,115 @@
+use windows::Win32::Foundation::RECT;
+use crate::settings::models::DockEdge;
+
+/// ドラッグ中のグループ (rect) を, 近くにある端に吸い付けた左上の位置を返すよ！
+/// 吸い付く先は作業領域 (work_area) の端と, 他のグループ (others) の端だよ。
+/// threshold ピクセル以内にあるものの中で一番近い端に合わせるね。縦と横は別々に決めるよ。
+pub fn snap_position(rect: &RECT, others: &[RECT], work_area: &RECT, threshold: i32) -> (i32, i32) {
+    let width = rect.right - rect.left;
+    let height = rect.bottom - rect.top;
+    if threshold <= 0 {
+        return (rect.left, rect.top);
+    }
+
+    // 他のグループの端は, 横に並んでいる (縦方向に重なっている) ものだけ見るよ
+    let beside = |other: &&RECT| rect.top < other.bottom + threshold && rect.bottom > other.top - threshold;
+    let above_or_below = |other: &&RECT| rect.left < other.right + threshold && rect.right > other.left - threshold;
+
+    let x_targets = [work_area.left, work_area.right - width].into_iter().chain(
+        others.iter().filter(beside).flat_map(|other| {
+            // 隣にくっつけるか, 端をそろえるかだよ
+            [other.right, other.left - width, other.left, other.right - width]
+        }),
+    );
+    let y_targets = [work_area.top, work_area.bottom - height].into_iter().chain(
+        others.iter().filter(above_or_below).flat_map(|other| {
+            [other.bottom, other.top - height, other.top, other.bottom - height]
+        }),
+    );
+
+    (nearest(rect.left, x_targets, threshold), nearest(rect.top, y_targets, threshold))
+}
+
+/// 大きさ (幅, 高さ) が sizes のグループを, 作業領域の edge の端に沿って順に並べたときの左上の位置を返すよ！
+/// 左右の端なら上から下へ, 上下の端なら左から右へ並べて, 入りきらなくなったら内側にもう1列 (1行) 作るね。
+pub fn dock_positions(sizes: &[(i32, i32)], work_area: &RECT, edge: DockEdge) -> Vec<(i32, i32)> {
+    let vertical = matches!(edge, DockEdge::Left | DockEdge::Right);
+    let (start, end) = if vertical { (work_area.top, work_area.bottom) } else { (work_area.left, work_area.right) };
+    let mut along = start; // 並べる向きの, 次のグループを置く位置
+    let mut across = 0; // 端から内側へずらす量 (折り返した列の分)
+    let mut thickness = 0; // 今の列で一番太いグループの太さ
+
+    sizes
+        .iter()
+        .map(|&(width, height)| {
+            let (length, depth) = if vertical { (height, width) } else { (width, height) };
+            // 列の先頭でなければ, はみ出すところで折り返すよ
+            if along + length > end && along > start {
+                across += thickness;
+                along = start;
+                thickness = 0;
+            }
+            let position = along;
+            along += length;
+            thickness = thickness.max(depth);
+            match edge {
+                DockEdge::Left => (work_area.left + across, position),
+                DockEdge::Right => (work_area.right - across - width, position),
+                DockEdge::Top => (position, work_area.top + across),
+                DockEdge::Bottom => (position, work_area.bottom - across - height),
+            }
+        })
+        .collect()
+}
+
+/// targets の中で value に一番近いものを返すよ。threshold より遠ければ value のままだよ。
+fn nearest(value: i32, targets: impl Iterator<Item = i32>, threshold: i32) -> i32 {
+    targets
+        .filter(|target| (target - value).abs() <= threshold)
+        .min_by_key(|target| (target - value).abs())
+        .unwrap_or(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(left: i32, top: i32, width: i32, height: i32) -> RECT {
+        RECT { left, top, right: left + width, bottom: top + height }
+    }
+
+    #[test]
+    fn test_snaps_to_work_area_edges() {
+        let work = rect(0, 0, 1920, 1040);
+        assert_eq!(snap_position(&rect(8, 500, 300, 200), &[], &work, 12), (0, 500));
+        // 右下の隅にも吸い付くよ
+        assert_eq!(snap_position(&rect(1615, 835, 300, 200), &[], &work, 12), (1620, 840));
+        // 遠ければそのままだよ
+        assert_eq!(snap_position(&rect(100, 500, 300, 200), &[], &work, 12), (100, 500));
+        // 0 なら吸い付かないよ
+        assert_eq!(snap_position(&rect(8, 500, 300, 200), &[], &work, 0), (8, 500));
+    }
+
+    #[test]
+    fn test_snaps_beside_other_groups() {
+        let work = rect(0, 0, 1920, 1040);
+        let other = rect(400, 300, 200, 200);
+        // 右隣にくっつけて, 上の端もそろえるよ
+        assert_eq!(snap_position(&rect(610, 306, 300, 200), &[other], &work, 12), (600, 300));
+        // 縦に離れているグループの横の端には吸い付かないよ
+        let far = rect(400, 800, 200, 100);
+        assert_eq!(snap_position(&rect(610, 306, 300, 200), &[far], &work, 12), (610, 306));
+    }
+
+    #[test]
+    fn test_dock_stacks_along_edge_and_wraps() {
+        let work = rect(0, 0, 1920, 1040);
+        let sizes = [(300, 400), (200, 400), (250, 400)];
+        // 左の端: 上から順に積んで, 3つ目は入りきらないから一番太い 300 の分だけ内側の列へ行くよ
+        assert_eq!(dock_positions(&sizes, &work, DockEdge::Left), vec![(0, 0), (0, 400), (300, 0)]);
+        // 右の端は右端にそろえるよ
+        assert_eq!(dock_positions(&sizes, &work, DockEdge::Right), vec![(1620, 0), (1720, 400), (1370, 0)]);
+        // 下の端: 左から順に横へ並べるよ
+        assert_eq!(dock_positions(&sizes, &work, DockEdge::Bottom), vec![(0, 640), (300, 640), (500, 640)]);
+    }
+}
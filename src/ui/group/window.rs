@@ -1,21 +1,49 @@
 use crate::graphics::GraphicsEngine;
+use crate::ui::group::color_history::{ColorHistory, ColorState};
 use crate::ui::group::interaction::{InteractionAction, InteractionHandler};
-use crate::ui::group::model::GroupModel;
+use crate::ui::group::model::{GroupModel, IconState};
 use crate::ui::group::renderer::GroupRenderer;
+use crate::ui::group::snap;
+use crate::ui::keymap::{Keymap, Modifiers};
 use crate::win32::api;
-use crate::settings::{manager};
+use crate::graphics::layout::{self, GroupLayout};
+use crate::settings::{manager, models::{BackgroundMode, ChildSettings, DesktopPinMode, EmptyClickAction, LayoutMode, LockFlags, RowAlign, SortKey, MIN_WINDOW_OPACITY}};
+use std::collections::VecDeque;
 use std::rc::Rc;
 use windows::core::PCWSTR;
 use windows::Win32::Foundation::{HWND, RECT};
 use windows::Win32::System::LibraryLoader::GetModuleHandleW;
 use windows::Win32::UI::WindowsAndMessaging::{
-    GetWindowRect, SetWindowLongPtrW, SetWindowPos, GWLP_USERDATA, HWND_BOTTOM, SWP_NOACTIVATE,
-    SWP_NOMOVE, SWP_NOSIZE, WS_EX_LAYERED, WS_EX_NOACTIVATE, WS_EX_TOOLWINDOW,
+    GetWindowLongPtrW, GetWindowRect, SetForegroundWindow, SetWindowLongPtrW, SetWindowPos, GWLP_USERDATA, GWL_EXSTYLE, HWND_BOTTOM, SWP_NOACTIVATE,
+    SWP_NOMOVE, SWP_NOSIZE, SWP_NOZORDER, SET_WINDOW_POS_FLAGS, WS_EX_LAYERED, WS_EX_NOACTIVATE, WS_EX_TOOLWINDOW,
     WS_POPUP, WS_VISIBLE, WS_EX_ACCEPTFILES, SetTimer, KillTimer,
 };
 
 // タイマー ID の定義
 const IDT_EXECUTE_FLASH: usize = 1;
+const IDT_ANIMATION: usize = 2;
+const ANIMATION_INTERVAL_MS: u32 = 100;
+const IDT_HOVER_FADE: usize = 3;
+const HOVER_FADE_INTERVAL_MS: u32 = 16;
+const HOVER_FADE_STEP: f32 = 0.15;
+const IDT_TOAST: usize = 4;
+const TOAST_DURATION_MS: u32 = 1200;
+const IDT_LOWER: usize = 5;
+const RAISE_DURATION_MS: u32 = 1500;
+const IDT_LAUNCH_FLASH: usize = 6;
+const LAUNCH_FLASH_INTERVAL_MS: u32 = 16;
+const LAUNCH_FLASH_STEP: f32 = 0.08;
+const IDT_LAUNCH_ALL: usize = 7;
+const LAUNCH_ALL_INTERVAL_MS: u32 = 300; // シェルに一度に頼みすぎないように, 1つずつ間をあけるよ
+const IDT_CHECK_EXISTS: usize = 8;
+const CHECK_EXISTS_INTERVAL_MS: u32 = 30_000; // リンク先のファイルが消えたり戻ったりしていないか, 時々調べるよ
+const MAX_FOLDER_ENTRIES: usize = 200; // フォルダの中身を追加するときの上限だよ
+const IDT_TOOLTIP: usize = 9;
+const TOOLTIP_DELAY_MS: u32 = 600; // アイコンの上でカーソルがこれだけ止まっていたら, 名前の吹き出しを出すよ
+
+const VK_BACK: u16 = 0x08;
+const VK_RETURN: u16 = 0x0D;
+const VK_ESCAPE: u16 = 0x1B;
 /// グループウィンドウを統括するコンポーネントだよ！
 #[repr(C)]
 pub struct GroupWindow {
@@ -24,6 +52,21 @@ pub struct GroupWindow {
     pub model: GroupModel,
     pub renderer: GroupRenderer,
     pub interaction: InteractionHandler,
+    /// グループ削除時に取り除いた設定だよ。管理側が「元に戻す」用に引き取るんだ。
+    pub deleted_settings: Option<ChildSettings>,
+    pub deleted_icons: Vec<(usize, IconState)>, // 削除したアイコンと元の位置 (WindowManager が取りに来るよ)
+    pub docked: bool, // auto_dock で並べる対象か (手で動かしたら外れて, Dock Groups で戻るよ)
+    color_history: ColorHistory,
+    is_mouse_inside: bool,
+    is_raised: bool, // 空白クリックやクリック操作で一時的に最前面に出ている間は true
+    keep_on_desktop: bool, // 最背面に固定するか (false なら普通のウィンドウのように重なるよ)
+    topmost: bool, // 常に最前面に出しておくか (このグループだけの設定だよ)
+    /// 大きさのプリセットを切り替え中なら (今のプリセット番号, 切り替える前の大きさ) だよ。
+    size_preset: Option<(usize, (i32, i32))>,
+    /// ドラッグで移動中の, 吸い付ける前の左上の位置だよ (マウスの動きはこちらに足していくね)。
+    drag_position: Option<(i32, i32)>,
+    /// 「全部開く」でこれから開くアイコンのインデックスだよ。
+    launch_queue: VecDeque<usize>,
 }
 
 impl GroupWindow {
@@ -32,18 +75,14 @@ impl GroupWindow {
         engine: Rc<GraphicsEngine>,
         id: String,
         title: String,
-        bg_color_hex: String,
-        opacity: f32,
-        icon_size: f32,
-        width: u32,
-        height: u32,
-        icons: Vec<std::path::PathBuf>,
+        child: &ChildSettings,
     ) -> Result<Box<Self>, windows::core::Error> {
+        let (width, height) = (child.width, child.height);
         let instance = unsafe { GetModuleHandleW(None)? };
 
         let class_name_str = "DesktopGroupingGroupClass";
         let class_name = api::utils::to_wide(class_name_str);
-        let window_name = api::utils::to_wide(&title);
+        let window_name = api::utils::to_wide(&Self::window_title(&id, &title));
         let class_pcwstr = PCWSTR::from_raw(class_name.as_ptr());
         let window_pcwstr = PCWSTR::from_raw(window_name.as_ptr());
 
@@ -75,15 +114,46 @@ impl GroupWindow {
             windows::Win32::UI::WindowsAndMessaging::SetLayeredWindowAttributes(
                 hwnd,
                 windows::Win32::Foundation::COLORREF(0),
-                255,
+                child.window_opacity,
                 windows::Win32::UI::WindowsAndMessaging::LWA_ALPHA,
             )?;
         }
 
-        api::show_window::move_to_bottom(hwnd);
+        // 常に最前面のグループはそのまま一番上に, それ以外は設定に応じてデスクトップの層に置くよ
+        // (keep_on_desktop が false なら, どちらもしないで普通のウィンドウとして置くね)
+        let keep_on_desktop = manager::get_settings_reader().app.keep_on_desktop;
+        if child.topmost {
+            api::show_window::set_topmost(hwnd, true);
+        } else if keep_on_desktop {
+            Self::send_to_desktop_layer(hwnd);
+        }
 
-        let model = GroupModel::new(id, title, bg_color_hex, opacity, icon_size, icons);
-        let renderer = GroupRenderer::new(engine, hwnd, width, height)?;
+        let mut model = GroupModel::new(id, title, child.bg_color.clone(), child.opacity, child.icon_size, child.icons.clone());
+        model.auto_sort = child.auto_sort;
+        model.bg_mode = child.bg_mode;
+        model.layout_mode = child.layout_mode;
+        model.row_alignment = child.row_alignment;
+        model.show_labels = child.show_labels;
+        model.border_width = child.border_width;
+        model.window_opacity = child.window_opacity;
+        model.collapsed = child.collapsed;
+        model.bg_image = child.bg_image.clone();
+        model.font_size = child.font_size;
+        model.font_family = child.font_family.clone();
+        model.lock = child.lock;
+        let color_history = ColorHistory::new(ColorState {
+            bg_color_hex: child.bg_color.clone(),
+            opacity: child.opacity,
+        });
+        let mut renderer = GroupRenderer::new(engine, hwnd, width, height)?;
+        // シェルへの問い合わせで起動が止まらないように, アイコンは別スレッドで取るよ (届くまでは代わりの絵だね)
+        let paths = model
+            .icons
+            .iter()
+            .filter(|icon| icon.exists && !icon.is_network && !icon.is_web_link() && icon.custom_icon_path.is_none())
+            .map(|icon| icon.path.clone())
+            .collect();
+        renderer.load_icons(hwnd, paths);
         let interaction = InteractionHandler::new();
 
         let window = Box::new(Self {
@@ -92,16 +162,50 @@ impl GroupWindow {
             model,
             renderer,
             interaction,
+            deleted_settings: None,
+            deleted_icons: Vec::new(),
+            docked: true,
+            color_history,
+            is_mouse_inside: false,
+            is_raised: false,
+            keep_on_desktop,
+            topmost: child.topmost,
+            size_preset: None,
+            drag_position: None,
+            launch_queue: VecDeque::new(),
         });
 
         unsafe {
             SetWindowLongPtrW(hwnd, GWLP_USERDATA, &*window as *const Self as isize);
+            SetTimer(hwnd, IDT_CHECK_EXISTS, CHECK_EXISTS_INTERVAL_MS, None);
         }
+        // ドラッグ中に上に来たことが分かるように, WM_DROPFILES より先に OLE のドロップ先として受け取るよ
+        api::drop_target::register(hwnd);
 
         Ok(window)
     }
 
+    /// 他のツール (スクリーンリーダーやウィンドウ管理ソフト) から見えるウィンドウタイトルを作るよ。
+    /// グループごとに区別できるように ID を必ず含めるね。
+    fn window_title(id: &str, title: &str) -> String {
+        if title.is_empty() {
+            format!("Desktop Grouping - {}", id)
+        } else {
+            format!("Desktop Grouping - {} ({})", title, id)
+        }
+    }
+
+    /// グループのタイトルを変えて, ウィンドウタイトルにも反映するよ。
+    pub fn set_title(&mut self, title: String) -> Result<(), windows::core::Error> {
+        self.model.title = title;
+        let window_name = api::utils::to_wide(&Self::window_title(&self.model.id, &self.model.title));
+        unsafe {
+            windows::Win32::UI::WindowsAndMessaging::SetWindowTextW(self.hwnd, PCWSTR::from_raw(window_name.as_ptr()))
+        }
+    }
+
     pub fn draw(&mut self) -> Result<(), windows::core::Error> {
+        self.load_network_icons();
         let mut rect = RECT::default();
         unsafe { windows::Win32::UI::WindowsAndMessaging::GetClientRect(self.hwnd, &mut rect)?; }
         let width = (rect.right - rect.left) as f32;
@@ -111,47 +215,309 @@ impl GroupWindow {
         self.renderer.render(&self.model, width, height, is_resizing)
     }
 
+    /// ネットワーク上のファイルの存在確認とアイコン取得を, 別スレッドに頼むよ。
+    /// UI スレッドで触ると応答がないときに固まるから, 結果は WM_ICONS_LOADED で受け取るね。
+    /// (取っている途中のものは頼み直さないから, 描くたびに呼んでも大丈夫だよ)
+    fn load_network_icons(&mut self) {
+        let paths: Vec<_> = self
+            .model
+            .icons
+            .iter()
+            .filter(|icon| icon.is_network && (icon.exists_unknown || !self.renderer.has_loaded_icon(&icon.path)))
+            .map(|icon| icon.path.clone())
+            .collect();
+        if !paths.is_empty() {
+            self.renderer.load_icons(self.hwnd, paths);
+        }
+    }
+
+    /// 別スレッドで取ったアイコンが届いたら, 取り込んで描き直すよ。
+    /// ネットワーク上のファイルは, ここで存在確認の結果も反映するね。
+    pub fn handle_icons_loaded(&mut self) -> Result<(), windows::core::Error> {
+        let received = self.renderer.receive_icons();
+        if received.is_empty() {
+            return Ok(());
+        }
+        for (path, exists) in received {
+            for icon in self.model.icons.iter_mut().filter(|icon| icon.exists_unknown && icon.path == path) {
+                icon.exists = exists;
+                icon.exists_unknown = false;
+            }
+        }
+        self.draw()
+    }
+
     pub fn handle_resize(&mut self, width: u32, height: u32) -> Result<(), windows::core::Error> {
         self.renderer.resize(width, height)
     }
 
-    pub fn handle_lbutton_down(&mut self) {
+    /// ウィンドウが動いたときに, 設定の位置と違っていたら保存するよ。
+    /// ドラッグ以外 (Win + 矢印キーや他のツール) で動かされても位置を失わないようにするためだよ。
+    /// 書き出しは manager::save() がまとめてくれるから, ドラッグ中に何度呼ばれても大丈夫だね。
+    pub fn handle_moved(&mut self) {
+        let mut rect = RECT::default();
+        if unsafe { GetWindowRect(self.hwnd, &mut rect) }.is_err() {
+            return;
+        }
+        let mut settings = manager::get_settings_writer();
+        if let Some(child) = settings.children.get_mut(&self.model.id) && (child.x, child.y) != (rect.left, rect.top) {
+            child.x = rect.left;
+            child.y = rect.top;
+            drop(settings);
+            manager::save();
+        }
+    }
+
+    /// 現在のウィンドウ幅とモデルから, アイコンと見出しのレイアウトを計算するよ。
+    /// スクロールしている分もずらしてあるから, そのままヒット判定に使えるよ。
+    /// 折りたたんでいるときはアイコンを並べないから, どのアイコンにも当たらないね。
+    pub fn current_layout(&self) -> GroupLayout {
+        let mut rect = RECT::default();
+        unsafe { let _ = windows::Win32::UI::WindowsAndMessaging::GetClientRect(self.hwnd, &mut rect); }
+        let width = (rect.right - rect.left) as f32;
+        let height = (rect.bottom - rect.top) as f32;
+
         let settings = manager::get_settings_reader();
-        let font_size = settings.app.font_size;
+        let params = self.model.layout_params(&settings.app);
         drop(settings);
-        self.interaction.handle_lbutton_down(self.hwnd, self.model.icons.len(), self.model.icon_size, font_size);
+
+        let sections = if self.model.collapsed { Vec::new() } else { self.model.sections() };
+        let mut group_layout = layout::calculate_group_layout(width, &sections, &params);
+        group_layout.apply_scroll(self.model.scroll_offset, height);
+        group_layout
+    }
+
+    /// アイコンの一覧を notches 目盛り分 (1目盛りでアイコン1行分) スクロールするよ。
+    /// 中身が全部見えているときや, 端まで来たときは何もしないよ。
+    fn scroll(&mut self, notches: f32) -> Result<(), windows::core::Error> {
+        if self.model.collapsed {
+            return Ok(());
+        }
+        let mut rect = RECT::default();
+        unsafe { windows::Win32::UI::WindowsAndMessaging::GetClientRect(self.hwnd, &mut rect)?; }
+        let (width, height) = ((rect.right - rect.left) as f32, (rect.bottom - rect.top) as f32);
+
+        let params = self.model.layout_params(&manager::get_settings_reader().app);
+        let (_, step) = layout::cell_size(&params);
+        let mut group_layout = layout::calculate_group_layout(width, &self.model.sections(), &params);
+        // 保存されている量は窓を大きくしたときに行き過ぎていることがあるから, 丸めた量から動かすよ
+        let current = group_layout.apply_scroll(self.model.scroll_offset, height);
+        let next = (current + notches * step).clamp(0.0, group_layout.max_scroll(height));
+        if next == current {
+            self.model.scroll_offset = current;
+            return Ok(());
+        }
+
+        self.model.scroll_offset = next;
+        self.model.hovered_index = None;
+        self.draw()
+    }
+
+    pub fn handle_lbutton_down(&mut self) -> Result<(), windows::core::Error> {
+        let layout = self.current_layout();
+        let action = self.interaction.handle_lbutton_down(self.hwnd, &layout);
         unsafe { windows::Win32::UI::Input::KeyboardAndMouse::SetCapture(self.hwnd); }
+        self.raise_on_interact();
+        self.perform_action(action)
+    }
+
+    /// ウィンドウを閉じるよう頼まれたら (Alt + F4 など), 確認してからグループを削除するよ (confirm_window_delete が false なら確認しないね)。
+    /// アプリ自体は終わらないから, トレイから新しいグループを作れるよ。
+    pub fn handle_close(&mut self) -> Result<(), windows::core::Error> {
+        // アイコンの編集がロックされているグループは, 中身ごと消えてしまわないように削除もしないよ
+        if self.icons_locked() {
+            return Ok(());
+        }
+        let confirm = manager::get_settings_reader().app.confirm_window_delete;
+        if !confirm || api::dialog::confirm(&format!("Delete group \"{}\"?\n(Ctrl + Shift + Z on the desktop restores it.)", self.display_name())) {
+            self.perform_action(InteractionAction::DeleteGroup)?;
+        }
+        Ok(())
+    }
+
+    /// confirm_icon_delete が有効なら, アイコン (what) を削除してよいか確認するよ。無効ならいつでも true だよ。
+    fn confirm_icon_delete(&self, what: &str) -> bool {
+        // ダイアログの間も設定を書けるように, 読み取りロックは先に外しておくよ
+        let confirm = manager::get_settings_reader().app.confirm_icon_delete;
+        !confirm || api::dialog::confirm(&format!("Delete {}?\n(Ctrl + Shift + Z on the desktop restores it.)", what))
+    }
+
+    /// メッセージなどに出すグループの名前だよ (タイトルが空なら ID)。
+    pub fn display_name(&self) -> &str {
+        if self.model.title.is_empty() { &self.model.id } else { &self.model.title }
+    }
+
+    /// 最背面に固定しておくべきかどうかだよ。
+    /// 一時的に最前面に出ている間と, keep_on_desktop が false のときと, 常に最前面のときはお休みするんだ。
+    pub fn stays_at_bottom(&self) -> bool {
+        self.keeps_desktop_layer() && !self.is_raised
+    }
+
+    /// 普段はデスクトップの層 (最背面) に置いておくグループかどうかだよ。
+    fn keeps_desktop_layer(&self) -> bool {
+        self.keep_on_desktop && !self.topmost
+    }
+
+    /// 設定に応じてデスクトップの層に固定するか, 単に最背面へ送るよ。
+    fn send_to_desktop_layer(hwnd: HWND) {
+        let pin_mode = manager::get_settings_reader().app.desktop_pin_mode;
+        if pin_mode != DesktopPinMode::Desktop || !api::show_window::pin_to_desktop(hwnd) {
+            api::show_window::move_to_bottom(hwnd);
+        }
+    }
+
+    /// 常に最前面に出しておくかどうかを切り替えて, 設定に保存するよ。
+    /// 戻したときは, 元どおりデスクトップの層 (最背面) へ送り直すね。
+    pub fn set_topmost(&mut self, topmost: bool) -> Result<(), windows::core::Error> {
+        log::info!("Group {} always on top: {}", self.model.id, topmost);
+        self.topmost = topmost;
+        if topmost {
+            api::show_window::unpin_from_desktop(self.hwnd);
+            api::show_window::set_topmost(self.hwnd, true);
+        } else {
+            api::show_window::set_topmost(self.hwnd, false);
+            if self.keep_on_desktop {
+                Self::send_to_desktop_layer(self.hwnd);
+            }
+        }
+
+        let mut settings = manager::get_settings_writer();
+        if let Some(child) = settings.children.get_mut(&self.model.id) {
+            child.topmost = topmost;
+            drop(settings);
+            manager::save();
+        }
+        self.show_toast(if topmost { "Always on top" } else { "Always on top: off" }.to_string())
+    }
+
+    /// タイトル行だけの細い帯に折りたたむか, 折りたたむ前の高さに広げるよ。
+    /// 幅と位置はそのままで, 折りたたんだことと元の高さは設定に保存するね。
+    pub fn toggle_collapse(&mut self) -> Result<(), windows::core::Error> {
+        let mut rect = RECT::default();
+        unsafe { GetWindowRect(self.hwnd, &mut rect)?; }
+        let width = rect.right - rect.left;
+        let current_height = (rect.bottom - rect.top) as u32;
+
+        let collapsed = !self.model.collapsed;
+        let height = if collapsed {
+            // タイトルが無くても, 掴めるように見出し1行分の高さは残すよ
+            let font_size = self.model.font_size(&manager::get_settings_reader().app);
+            (font_size * layout::HEADER_HEIGHT_RATIO + layout::PADDING * 2.0).ceil() as u32
+        } else {
+            let settings = manager::get_settings_reader();
+            settings
+                .children
+                .get(&self.model.id)
+                .and_then(|child| child.expanded_height)
+                .unwrap_or(ChildSettings::default().height)
+        };
+        log::info!("Group {} collapsed: {} (height {})", self.model.id, collapsed, height);
+
+        self.model.collapsed = collapsed;
+        self.model.hovered_index = None;
+        self.model.selected.clear();
+        self.model.focused_icon = None;
+        unsafe {
+            SetWindowPos(self.hwnd, HWND_BOTTOM, 0, 0, width, height as i32, SWP_NOMOVE | self.position_flags())?;
+        }
+
+        let mut settings = manager::get_settings_writer();
+        if let Some(child) = settings.children.get_mut(&self.model.id) {
+            child.collapsed = collapsed;
+            child.expanded_height = if collapsed { Some(current_height) } else { None };
+            child.height = height;
+            drop(settings);
+            manager::save();
+        }
+        self.show_toast(if collapsed { "Collapsed" } else { "Expanded" }.to_string())
+    }
+
+    /// 位置や大きさを変えるときの SetWindowPos のフラグだよ。
+    /// 最背面に固定しないときは, Z 順 (重なり順) には触らないようにするね。
+    pub fn position_flags(&self) -> SET_WINDOW_POS_FLAGS {
+        if self.keeps_desktop_layer() {
+            SWP_NOACTIVATE
+        } else {
+            SWP_NOACTIVATE | SWP_NOZORDER
+        }
     }
 
     pub fn handle_lbutton_dblclk(&mut self) -> Result<(), windows::core::Error> {
-        let settings = manager::get_settings_reader();
-        let font_size = settings.app.font_size;
-        drop(settings);
-        let action = self.interaction.handle_lbutton_dblclk(self.hwnd, self.model.icons.len(), self.model.icon_size, font_size);
+        let layout = self.current_layout();
+        let action = self.interaction.handle_lbutton_dblclk(self.hwnd, &layout);
+        self.raise_on_interact();
         self.perform_action(action)
     }
 
     pub fn handle_rbutton_down(&mut self) -> Result<(), windows::core::Error> {
-        let settings = manager::get_settings_reader();
-        let font_size = settings.app.font_size;
-        drop(settings);
-        let action = self.interaction.handle_rbutton_down(self.hwnd, self.model.icons.len(), self.model.icon_size, font_size);
+        let layout = self.current_layout();
+        let action = self.interaction.handle_rbutton_down(self.hwnd, &layout);
         self.perform_action(action)
     }
 
     pub fn handle_rbutton_up(&mut self) -> Result<(), windows::core::Error> {
-        let settings = manager::get_settings_reader();
-        let font_size = settings.app.font_size;
-        drop(settings);
-        let action = self.interaction.handle_rbutton_up(self.hwnd, self.model.icons.len(), self.model.icon_size, font_size);
+        let layout = self.current_layout();
+        let action = self.interaction.handle_rbutton_up(self.hwnd, &layout);
         self.perform_action(action)
     }
 
     pub fn handle_mouse_move(&mut self) -> Result<(), windows::core::Error> {
-        let settings = manager::get_settings_reader();
-        let font_size = settings.app.font_size;
-        drop(settings);
-        let action = self.interaction.handle_mouse_move(self.hwnd, self.model.icons.len(), self.model.icon_size, font_size);
+        // ウィンドウから出たときに WM_MOUSELEAVE が届くようにするよ
+        unsafe {
+            let mut tme = windows::Win32::UI::Input::KeyboardAndMouse::TRACKMOUSEEVENT {
+                cbSize: std::mem::size_of::<windows::Win32::UI::Input::KeyboardAndMouse::TRACKMOUSEEVENT>() as u32,
+                dwFlags: windows::Win32::UI::Input::KeyboardAndMouse::TME_LEAVE,
+                hwndTrack: self.hwnd,
+                dwHoverTime: 0,
+            };
+            windows::Win32::UI::Input::KeyboardAndMouse::TrackMouseEvent(&mut tme).ok();
+        }
+
+        if !self.is_mouse_inside {
+            self.is_mouse_inside = true;
+            self.start_hover_fade();
+        }
+
+        let layout = self.current_layout();
+        let action = self.interaction.handle_mouse_move(self.hwnd, &layout);
+        self.perform_action(action)?;
+        self.restart_tooltip()
+    }
+
+    /// カーソルが動いたら吹き出しを消して, アイコンの上ならもう一度待ち始めるよ。
+    /// (描き直しなどでカーソルが動かずに WM_MOUSEMOVE が来たときは, そのままにするね)
+    fn restart_tooltip(&mut self) -> Result<(), windows::core::Error> {
+        let cursor = InteractionHandler::cursor_in_window(self.hwnd);
+        if let Some((_, pos)) = self.model.tooltip {
+            if cursor == Some(pos) {
+                return Ok(());
+            }
+            self.model.tooltip = None;
+            self.draw()?;
+        }
+        let busy = self.interaction.is_dragging() || self.interaction.is_resizing() || self.model.icon_drag.is_some();
+        unsafe {
+            if self.model.hovered_index.is_some() && !busy {
+                SetTimer(self.hwnd, IDT_TOOLTIP, TOOLTIP_DELAY_MS, None);
+            } else {
+                KillTimer(self.hwnd, IDT_TOOLTIP).ok();
+            }
+        }
+        Ok(())
+    }
+
+    pub fn handle_mouse_leave(&mut self) -> Result<(), windows::core::Error> {
+        self.is_mouse_inside = false;
+        // カーソルが離れたらキーボード操作の対象でもなくなるから, 矢印キーのフォーカスも外すよ
+        if !self.model.focused {
+            self.model.focused_icon = None;
+        }
+        unsafe { KillTimer(self.hwnd, IDT_TOOLTIP).ok(); }
+        self.model.tooltip = None;
+        // カーソルが離れたら元の層に戻すよ
+        self.lower();
+        self.start_hover_fade();
+        let action = self.interaction.handle_mouse_leave();
         self.perform_action(action)
     }
 
@@ -161,29 +527,342 @@ impl GroupWindow {
     }
 
     pub fn handle_keydown(&mut self, virtual_key: u16) -> Result<(), windows::core::Error> {
+        // 名前の入力中は Ctrl + V でクリップボードの文字を入れるよ (色の貼り付けはお休み)
+        if let Some(draft) = self.model.name_draft.as_mut() {
+            if matches!(self.interaction.handle_keydown(virtual_key), InteractionAction::PasteColor)
+                && let Some(text) = api::utils::get_clipboard_text()
+            {
+                draft.push_str(text.lines().next().unwrap_or_default());
+                self.draw()?;
+            }
+            return Ok(());
+        }
         let action = self.interaction.handle_keydown(virtual_key);
         self.perform_action(action)
     }
 
+    /// 名前の入力中の文字入力だよ。Enter で確定, Esc で取り消しだよ。
+    pub fn handle_char(&mut self, code: u16) -> Result<(), windows::core::Error> {
+        let Some(draft) = self.model.name_draft.as_mut() else {
+            return Ok(());
+        };
+        match code {
+            VK_RETURN => return self.end_rename(true),
+            VK_ESCAPE => return self.end_rename(false),
+            VK_BACK => {
+                draft.pop();
+            }
+            _ => {
+                if let Some(c) = char::from_u32(code as u32).filter(|c| !c.is_control()) {
+                    draft.push(c);
+                }
+            }
+        }
+        self.draw()
+    }
+
+    /// 他のウィンドウにフォーカスが移ったら, 入力中の名前で確定するよ。
+    pub fn handle_deactivate(&mut self) -> Result<(), windows::core::Error> {
+        if self.is_renaming() {
+            return self.end_rename(true);
+        }
+        Ok(())
+    }
+
+    /// ドラッグでグループを動かしている途中か, 大きさを変えている途中かどうかだよ。
+    pub fn is_moving_or_resizing(&self) -> bool {
+        self.interaction.is_dragging() || self.interaction.is_resizing()
+    }
+
+    /// タイトルかアイコンの名前を編集中かどうかだよ。編集中はメッセージループのショートカットをお休みするんだ。
+    pub fn is_renaming(&self) -> bool {
+        self.model.name_draft.is_some()
+    }
+
+    /// タイトルの編集を始めるよ。
+    pub fn begin_rename(&mut self) -> Result<(), windows::core::Error> {
+        let title = self.model.title.clone();
+        self.begin_editing(title, None)
+    }
+
+    /// index のアイコンの表示名の編集を始めるよ。今出ている名前から書き換えられるね。
+    pub fn begin_icon_rename(&mut self, index: usize) -> Result<(), windows::core::Error> {
+        let Some(label) = self.model.icons.get(index).map(|icon| icon.label().to_string()) else {
+            return Ok(());
+        };
+        self.begin_editing(label, Some(index))
+    }
+
+    /// 名前の入力を始めるよ。
+    /// 文字を受け取れるように, 編集中だけ WS_EX_NOACTIVATE を外して前面に出すね。
+    fn begin_editing(&mut self, text: String, icon: Option<usize>) -> Result<(), windows::core::Error> {
+        if self.is_renaming() {
+            return Ok(());
+        }
+        self.model.name_draft = Some(text);
+        self.model.renaming_icon = icon;
+        unsafe {
+            let ex_style = GetWindowLongPtrW(self.hwnd, GWL_EXSTYLE);
+            SetWindowLongPtrW(self.hwnd, GWL_EXSTYLE, ex_style & !(WS_EX_NOACTIVATE.0 as isize));
+            if !SetForegroundWindow(self.hwnd).as_bool() {
+                log::warn!("Could not bring group {} to the foreground for renaming.", self.model.id);
+            }
+        }
+        self.draw()
+    }
+
+    /// 名前の編集を終えるよ。commit が true なら保存するね。
+    fn end_rename(&mut self, commit: bool) -> Result<(), windows::core::Error> {
+        let Some(draft) = self.model.name_draft.take() else {
+            return Ok(());
+        };
+        unsafe {
+            let ex_style = GetWindowLongPtrW(self.hwnd, GWL_EXSTYLE);
+            SetWindowLongPtrW(self.hwnd, GWL_EXSTYLE, ex_style | WS_EX_NOACTIVATE.0 as isize);
+        }
+
+        if let Some(index) = self.model.renaming_icon.take() {
+            if commit {
+                return self.rename_icon(index, &draft);
+            }
+            return self.draw();
+        }
+
+        let title = draft.trim().to_string();
+        if commit && title != self.model.title {
+            log::info!("Renaming group {} to {:?}", self.model.id, title);
+            self.set_title(title.clone())?;
+            let mut settings = manager::get_settings_writer();
+            if let Some(child) = settings.children.get_mut(&self.model.id) {
+                child.title = title;
+                drop(settings);
+                manager::save();
+            }
+        }
+        self.draw()
+    }
+
     /// タイマーが発火したときの処理だよ。
     pub fn handle_timer(&mut self, timer_id: usize) -> Result<(), windows::core::Error> {
         if timer_id == IDT_EXECUTE_FLASH {
             self.model.executing_index = None;
             unsafe { KillTimer(self.hwnd, IDT_EXECUTE_FLASH).ok(); }
             self.draw()?;
+        } else if timer_id == IDT_ANIMATION {
+            self.model.animation_frame = self.model.animation_frame.wrapping_add(1);
+            self.draw()?;
+        } else if timer_id == IDT_HOVER_FADE {
+            // 目標 (中にいれば 1.0, 外なら 0.0) に少しずつ近づけるよ
+            let target = if self.is_mouse_inside { 1.0 } else { 0.0 };
+            let boost = self.model.hover_boost;
+            self.model.hover_boost = if boost < target {
+                (boost + HOVER_FADE_STEP).min(target)
+            } else {
+                (boost - HOVER_FADE_STEP).max(target)
+            };
+            if self.model.hover_boost == target {
+                unsafe { KillTimer(self.hwnd, IDT_HOVER_FADE).ok(); }
+            }
+            self.draw()?;
+        } else if timer_id == IDT_TOAST {
+            self.model.toast = None;
+            unsafe { KillTimer(self.hwnd, IDT_TOAST).ok(); }
+            self.draw()?;
+        } else if timer_id == IDT_LOWER {
+            self.lower();
+        } else if timer_id == IDT_CHECK_EXISTS {
+            // 1つでも変わっていたら描き直すよ (全部調べたいから any は使わないね)
+            // フォルダの項目数のバッジと, アイコンを取り直すための更新日時も, ついでに調べ直すよ
+            let changed = self
+                .model
+                .icons
+                .iter_mut()
+                .fold(false, |changed, icon| icon.recheck_exists() | icon.recheck_modified() | icon.recount_folder_if_stale() | changed);
+            if changed {
+                self.draw()?;
+            }
+        } else if timer_id == IDT_TOOLTIP {
+            // 名前が省略されていなければ, 描くときに吹き出しは出さないよ
+            unsafe { KillTimer(self.hwnd, IDT_TOOLTIP).ok(); }
+            if let (Some(index), Some(pos)) = (self.model.hovered_index, InteractionHandler::cursor_in_window(self.hwnd)) {
+                self.model.tooltip = Some((index, pos));
+                self.draw()?;
+            }
+        } else if timer_id == IDT_LAUNCH_ALL {
+            self.launch_next()?;
+        } else if timer_id == IDT_LAUNCH_FLASH {
+            self.model.launch_flash = (self.model.launch_flash - LAUNCH_FLASH_STEP).max(0.0);
+            if self.model.launch_flash == 0.0 {
+                unsafe { KillTimer(self.hwnd, IDT_LAUNCH_FLASH).ok(); }
+            }
+            self.draw()?;
         }
         Ok(())
     }
 
+    /// 起動したときの手応え (launch_feedback) を返すよ。
+    /// Flash なら背景を明るくしてタイマーで戻し, Sound なら既定のサウンドを鳴らすね。
+    fn launch_feedback(&mut self) {
+        let feedback = manager::get_settings_reader().app.launch_feedback;
+        if feedback.flash() {
+            self.model.launch_flash = 1.0;
+            unsafe { SetTimer(self.hwnd, IDT_LAUNCH_FLASH, LAUNCH_FLASH_INTERVAL_MS, None); }
+        }
+        if feedback.sound() {
+            api::dialog::play_default_sound();
+        }
+    }
+
+    /// 見つからないアイコンについて, 新しい場所を選ぶか確認するよ。選んだらそこへつなぎ直すね。
+    fn offer_relink(&mut self, index: usize) -> Result<(), windows::core::Error> {
+        let Some(icon) = self.model.icons.get(index) else {
+            return Ok(());
+        };
+        let old_path = icon.path.clone();
+        let message = format!("{} was not found.\n\n{}\n\nDo you want to pick its new location?", icon.label(), old_path.display());
+        if !api::dialog::confirm(&message) {
+            return Ok(());
+        }
+        match api::dialog::pick_file("Select the new location", Some(&old_path)) {
+            Some(path) => self.relink_icon(index, path),
+            None => Ok(()),
+        }
+    }
+
+    /// アイコンのリンク先を path に付け替えて保存するよ。
+    /// 表示名・セクション・アイコン画像は, 自分で付けたものをそのまま引き継ぐね。
+    pub fn relink_icon(&mut self, index: usize, path: std::path::PathBuf) -> Result<(), windows::core::Error> {
+        if index >= self.model.icons.len() {
+            return Ok(());
+        }
+        let old = &self.model.icons[index];
+        log::info!("Relinking {:?} to {:?}", old.path, path);
+        let mut icon = IconState::new(path, old.section.clone());
+        icon.custom_name = old.custom_name.clone();
+        icon.custom_icon_path = old.custom_icon_path.clone();
+        self.model.icons[index] = icon;
+        if self.model.apply_auto_sort() {
+            self.model.hovered_index = None;
+        }
+        self.save_icons();
+        self.draw()
+    }
+
+    /// グループのアイコンを全部, 並んでいる順に開くよ。
+    /// 1つずつ間をあけて開いて, そのたびにアイコンを光らせるね。開いている途中なら何もしないよ。
+    fn execute_all(&mut self) -> Result<(), windows::core::Error> {
+        if self.model.icons.is_empty() || !self.launch_queue.is_empty() {
+            return Ok(());
+        }
+        self.launch_queue = (0..self.model.icons.len()).collect();
+        self.launch_feedback();
+        self.launch_next()?;
+        if !self.launch_queue.is_empty() {
+            unsafe { SetTimer(self.hwnd, IDT_LAUNCH_ALL, LAUNCH_ALL_INTERVAL_MS, None); }
+        }
+        Ok(())
+    }
+
+    /// launch_queue の次のアイコンを開くよ。開けなかったものはログに残して, 次に進むね。
+    fn launch_next(&mut self) -> Result<(), windows::core::Error> {
+        let Some(index) = self.launch_queue.pop_front() else {
+            unsafe { KillTimer(self.hwnd, IDT_LAUNCH_ALL).ok(); }
+            return Ok(());
+        };
+        if self.launch_queue.is_empty() {
+            unsafe { KillTimer(self.hwnd, IDT_LAUNCH_ALL).ok(); }
+        }
+        // 途中でアイコンが消されていたら飛ばすよ
+        let Some(path) = self.model.icons.get(index).map(|i| i.launch_target()) else {
+            return Ok(());
+        };
+        self.model.executing_index = Some(index);
+        self.draw()?;
+        unsafe { SetTimer(self.hwnd, IDT_EXECUTE_FLASH, 150, None); }
+
+        log::info!("Executing: {:?}", path);
+        if let Err(e) = api::shell::execute_path(&path) {
+            log::error!("Failed to execute {:?}: {}", path, e);
+        }
+        Ok(())
+    }
+
+    /// raise_on_interact が有効なら, グループを少しの間だけ最前面に出すよ。
+    /// 時間が経つか, カーソルが離れたら元の層に戻るね。
+    fn raise_on_interact(&mut self) {
+        if !manager::get_settings_reader().app.raise_on_interact {
+            return;
+        }
+        self.raise();
+        unsafe { SetTimer(self.hwnd, IDT_LOWER, RAISE_DURATION_MS, None); }
+    }
+
+    /// グループを一時的に最前面に出すよ (最背面への固定はその間お休みだよ)。
+    fn raise(&mut self) {
+        if !self.is_raised {
+            self.is_raised = true;
+            api::show_window::move_to_top(self.hwnd);
+        }
+    }
+
+    /// 一時的に最前面に出していたグループを, 元の層に戻すよ。
+    fn lower(&mut self) {
+        unsafe { KillTimer(self.hwnd, IDT_LOWER).ok(); }
+        if self.is_raised {
+            self.is_raised = false;
+            if self.keeps_desktop_layer() {
+                api::show_window::move_to_bottom(self.hwnd);
+            }
+        }
+    }
+
+    /// キーボード操作の対象になったかどうかを切り替えて, 枠の強調を描き直すよ。
+    /// キーボード操作の対象でなくなったら, 矢印キーで選んでいたアイコンも忘れるよ。
+    pub fn set_focused(&mut self, focused: bool) {
+        self.model.focused = focused;
+        if !focused {
+            self.model.focused_icon = None;
+        }
+        if let Err(e) = self.draw() {
+            log::error!("Draw error: {}", e);
+        }
+    }
+
+    /// 短いメッセージをグループの上に少しの間だけ表示するよ。
+    fn show_toast(&mut self, text: String) -> Result<(), windows::core::Error> {
+        self.model.toast = Some(text);
+        unsafe { SetTimer(self.hwnd, IDT_TOAST, TOAST_DURATION_MS, None); }
+        self.draw()
+    }
+
+    /// ホバー時に不透明にする設定が有効なら, フェードのタイマーを動かすよ。
+    fn start_hover_fade(&mut self) {
+        if !manager::get_settings_reader().app.opaque_on_hover {
+            self.model.hover_boost = 0.0;
+            return;
+        }
+        unsafe { SetTimer(self.hwnd, IDT_HOVER_FADE, HOVER_FADE_INTERVAL_MS, None); }
+    }
+
     pub fn perform_action(&mut self, action: InteractionAction) -> Result<(), windows::core::Error> {
+        if action.edits_icons() && self.icons_locked() {
+            return Ok(());
+        }
         match action {
+            InteractionAction::Move { .. } if self.model.lock.r#move => {}
+            InteractionAction::Resize { .. } if self.model.lock.resize => {}
             InteractionAction::Move { dx, dy } => {
                 let mut rect = RECT::default();
                 unsafe {
                     GetWindowRect(self.hwnd, &mut rect)?;
-                    let new_x = rect.left + dx;
-                    let new_y = rect.top + dy;
-                    SetWindowPos(self.hwnd, HWND_BOTTOM, new_x, new_y, 0, 0, SWP_NOSIZE | SWP_NOACTIVATE)?;
+                    // 吸い付いた位置ではなく, マウスどおりに動かした位置を元に次の位置を決めるよ
+                    let (free_x, free_y) = self.drag_position.unwrap_or((rect.left, rect.top));
+                    let free = (free_x + dx, free_y + dy);
+                    self.drag_position = Some(free);
+                    let (new_x, new_y) = self.snap_position(&rect, free);
+                    SetWindowPos(self.hwnd, HWND_BOTTOM, new_x, new_y, 0, 0, SWP_NOSIZE | self.position_flags())?;
+                    // 手で置いた場所を優先するから, 並べ直しの対象から外すよ
+                    self.docked = false;
 
                     let mut settings = manager::get_settings_writer();
                     if let Some(child) = settings.children.get_mut(&self.model.id) {
@@ -193,16 +872,30 @@ impl GroupWindow {
                     }
                 }
             }
-            InteractionAction::Resize { dw, dh } => {
+            InteractionAction::Resize { dx, dy, edges } => {
+                const MIN_SIZE: i32 = 50;
                 let mut rect = RECT::default();
                 unsafe {
                     GetWindowRect(self.hwnd, &mut rect)?;
-                    let new_width = ((rect.right - rect.left) + dw).max(50);
-                    let new_height = ((rect.bottom - rect.top) + dh).max(50);
-                    SetWindowPos(self.hwnd, HWND_BOTTOM, 0, 0, new_width, new_height, SWP_NOMOVE | SWP_NOACTIVATE)?;
+                    // 左 (上) の辺をつかんでいるときは, 反対側の辺を動かさないように位置もずらすよ
+                    let (left, right) = match edges.0 {
+                        -1 => ((rect.left + dx).min(rect.right - MIN_SIZE), rect.right),
+                        1 => (rect.left, (rect.right + dx).max(rect.left + MIN_SIZE)),
+                        _ => (rect.left, rect.right),
+                    };
+                    let (top, bottom) = match edges.1 {
+                        -1 => ((rect.top + dy).min(rect.bottom - MIN_SIZE), rect.bottom),
+                        1 => (rect.top, (rect.bottom + dy).max(rect.top + MIN_SIZE)),
+                        _ => (rect.top, rect.bottom),
+                    };
+                    let (new_width, new_height) = (right - left, bottom - top);
+                    // 手で大きさを変えたら, それが新しい「元の大きさ」になるよ
+                    self.size_preset = None;
+                    SetWindowPos(self.hwnd, HWND_BOTTOM, left, top, new_width, new_height, self.position_flags())?;
 
                     let mut settings = manager::get_settings_writer();
                     if let Some(child) = settings.children.get_mut(&self.model.id) {
+                        child.x = left; child.y = top;
                         child.width = new_width as u32; child.height = new_height as u32;
                         drop(settings);
                         manager::save();
@@ -212,6 +905,7 @@ impl GroupWindow {
             }
             InteractionAction::ChangeOpacity { delta } | InteractionAction::ChangeOpacityContinuous { delta } => {
                 self.model.opacity = (self.model.opacity + delta).clamp(0.1, 1.0);
+                self.color_history.record_coalesced(self.color_state());
                 let mut settings = manager::get_settings_writer();
                 if let Some(child) = settings.children.get_mut(&self.model.id) {
                     child.opacity = self.model.opacity;
@@ -225,13 +919,146 @@ impl GroupWindow {
                     let text = text_raw.trim().to_lowercase();
                     
                     // 1. アイコンサイズ指定の解析 (例: size:64)
-                    if text.starts_with("size:") {
-                        if let Ok(size) = text["size:".len()..].parse::<f32>() {
+                    if let Some(value) = text.strip_prefix("size:") {
+                        // 空なら設定ファイルの icon_size に戻すよ
+                        let size = match value.trim() {
+                            "" => Some(manager::get_settings_reader().app.icon_size),
+                            value => value.parse::<f32>().ok(),
+                        };
+                        if let Some(size) = size {
                             return self.perform_action(InteractionAction::ChangeIconSize { size });
                         }
                     }
 
-                    // 2. 背景色指定の解析 (#RRGGBB, #random)
+                    // 1.5. このグループだけの文字サイズ (例: fontsize:14, 空なら全体の設定に戻す)
+                    if let Some(value) = text.strip_prefix("fontsize:") {
+                        let value = value.trim();
+                        if value.is_empty() {
+                            return self.set_font_size(None);
+                        }
+                        match value.parse::<f32>() {
+                            Ok(size) => return self.set_font_size(Some(size)),
+                            Err(_) => {
+                                log::warn!("Invalid font size: {}", value);
+                                return Ok(());
+                            }
+                        }
+                    }
+
+                    // 1.6. このグループだけのフォント (例: font:Yu Gothic UI, 空なら全体の設定に戻す)
+                    // フォント名は大文字小文字をそのまま残したいから, 小文字にする前の text_raw から取るよ
+                    if text.starts_with("font:") {
+                        let value = text_raw.trim()["font:".len()..].trim();
+                        return self.set_font_family((!value.is_empty()).then(|| value.to_string()));
+                    }
+
+                    // 1.7. 並べ方の切り替え (例: layout:list, 空ならグリッドに戻す)
+                    if let Some(value) = text.strip_prefix("layout:") {
+                        let mode = match value.trim() {
+                            "" | "grid" => LayoutMode::Grid,
+                            "list" => LayoutMode::List,
+                            other => {
+                                log::warn!("Unknown layout mode: {}", other);
+                                return Ok(());
+                            }
+                        };
+                        return self.set_layout_mode(mode);
+                    }
+
+                    // 1.9. 埋まりきらない行の寄せ方 (例: align:center, 空か align:left で左詰め)
+                    if let Some(value) = text.strip_prefix("align:") {
+                        let align = match value.trim() {
+                            "" | "left" => RowAlign::Left,
+                            "center" | "centre" => RowAlign::Center,
+                            other => {
+                                log::warn!("Unknown row alignment: {}", other);
+                                return Ok(());
+                            }
+                        };
+                        return self.set_row_alignment(align);
+                    }
+
+                    // 1.95. アイコン名の表示 (例: labels:off でアイコンだけ, 空か labels:on で戻す)
+                    if let Some(value) = text.strip_prefix("labels:") {
+                        let show = match value.trim() {
+                            "" | "on" => true,
+                            "off" => false,
+                            other => {
+                                log::warn!("Unknown labels option: {}", other);
+                                return Ok(());
+                            }
+                        };
+                        return self.set_show_labels(show);
+                    }
+
+                    // 2. 自動整列の指定 (例: autosort:name, autosort:ext, 空なら解除)
+                    if let Some(value) = text.strip_prefix("autosort:") {
+                        let key = SortKey::parse(value);
+                        if key.is_none() && !value.trim().is_empty() {
+                            log::warn!("Unknown sort key: {}", value);
+                            return Ok(());
+                        }
+                        return self.set_auto_sort(key);
+                    }
+
+                    // 2.3. 一度だけの並べ替え (例: sort:name, sort:name_desc, sort:ext)
+                    if let Some(value) = text.strip_prefix("sort:") {
+                        match SortKey::parse(value) {
+                            Some(key) => return self.sort_once(key),
+                            None => {
+                                log::warn!("Unknown sort key: {}", value);
+                                return Ok(());
+                            }
+                        }
+                    }
+
+                    // 2.5. 整頓 (例: tidy:, 見出しも外すなら tidy:flat)
+                    if let Some(value) = text.strip_prefix("tidy:") {
+                        return self.compact(value.trim() == "flat");
+                    }
+
+                    // 2.7. ロックの切り替え (例: lock:move, lock:resize, lock:icons, lock:all, 空なら全部解除)
+                    if let Some(value) = text.strip_prefix("lock:") {
+                        let mut lock = self.model.lock;
+                        match value.trim() {
+                            "move" => lock.r#move = !lock.r#move,
+                            "resize" => lock.resize = !lock.resize,
+                            "icons" => lock.edit_icons = !lock.edit_icons,
+                            "all" => lock = LockFlags::all(),
+                            "" => lock = LockFlags::default(),
+                            other => {
+                                log::warn!("Unknown lock target: {}", other);
+                                return Ok(());
+                            }
+                        }
+                        return self.set_lock(lock);
+                    }
+
+                    // 3. 背景画像の指定 (例: bgimage:C:\wall.png, 空なら解除)
+                    if text.starts_with("bgimage:") {
+                        let path = text_raw.trim()["bgimage:".len()..].trim().trim_matches('"');
+                        let image = if path.is_empty() { None } else { Some(std::path::PathBuf::from(path)) };
+                        return self.set_background_image(image);
+                    }
+
+                    // 4. アイコン画像の指定 (例: icon:C:\icons\app.png, 空なら解除)
+                    if text.starts_with("icon:") {
+                        let path = text_raw.trim()["icon:".len()..].trim().trim_matches('"');
+                        let image = if path.is_empty() { None } else { Some(std::path::PathBuf::from(path)) };
+                        if let Some(index) = self.model.hovered_index {
+                            return self.set_custom_icon(index, image);
+                        }
+                        return Ok(());
+                    }
+
+                    // 5. セクション指定の解析 (例: section:Editors, 空なら解除)
+                    if text.starts_with("section:") {
+                        let name = text_raw.trim()["section:".len()..].trim();
+                        let section = if name.is_empty() { None } else { Some(name.to_string()) };
+                        return self.perform_action(InteractionAction::SetSection { section });
+                    }
+
+                    // 6. 背景色指定の解析 (#RRGGBB, #random)
                     let mut hex = text_raw.trim().to_string();
                     if hex.to_lowercase() == "#random" {
                         use rand::Rng;
@@ -239,17 +1066,38 @@ impl GroupWindow {
                         hex = format!("#{:02X}{:02X}{:02X}", rng.r#gen::<u8>(), rng.r#gen::<u8>(), rng.r#gen::<u8>());
                     }
                     if (hex.len() == 7 || hex.len() == 9) && hex.starts_with('#') {
-                        self.model.bg_color_hex = hex.clone();
-                        let mut settings = manager::get_settings_writer();
-                        if let Some(child) = settings.children.get_mut(&self.model.id) {
-                            child.bg_color = hex;
-                            drop(settings);
-                            manager::save();
-                        }
-                        self.draw()?;
+                        self.set_background_color(hex)?;
                     }
                 }
             }
+            InteractionAction::PickColor => {
+                self.pick_background_color()?;
+            }
+            InteractionAction::ChangeBorderWidth { delta } => {
+                self.model.border_width = (self.model.border_width + delta).clamp(0.0, 8.0);
+                let mut settings = manager::get_settings_writer();
+                if let Some(child) = settings.children.get_mut(&self.model.id) {
+                    child.border_width = self.model.border_width;
+                    drop(settings);
+                    manager::save();
+                }
+                self.draw()?;
+            }
+            InteractionAction::ChangeWindowOpacity { delta } => {
+                // 背景の opacity はそのままで, その上からウィンドウ全体を薄くするよ
+                let opacity = (self.model.window_opacity as i16 + delta).clamp(MIN_WINDOW_OPACITY as i16, 255) as u8;
+                if opacity == self.model.window_opacity {
+                    return Ok(());
+                }
+                self.model.window_opacity = opacity;
+                api::show_window::set_window_opacity(self.hwnd, opacity);
+                let mut settings = manager::get_settings_writer();
+                if let Some(child) = settings.children.get_mut(&self.model.id) {
+                    child.window_opacity = opacity;
+                    drop(settings);
+                    manager::save();
+                }
+            }
             InteractionAction::ChangeIconSize { size } => {
                 self.model.icon_size = size.clamp(16.0, 256.0);
                 let mut settings = manager::get_settings_writer();
@@ -260,13 +1108,34 @@ impl GroupWindow {
                 }
                 self.draw()?;
             }
+            InteractionAction::SetSection { section } => {
+                // ホバー中のアイコンを対象にするよ
+                if let Some(index) = self.model.hovered_index && let Some(new_index) = self.model.set_icon_section(index, section) {
+                    self.model.hovered_index = Some(new_index);
+                    if self.model.apply_auto_sort() {
+                        self.model.hovered_index = None;
+                    }
+                    self.save_icons();
+                    self.draw()?;
+                }
+            }
             InteractionAction::ExecuteIcon { index } => {
-                // 先にパスだけを取得して, self への借用を終わらせるよ
-                let maybe_path = self.model.icons.get(index).map(|i| i.path.clone());
+                // リンク先が見つからないなら, 開く代わりに新しい場所を選んでもらうよ
+                let missing = self.model.icons.get_mut(index).is_some_and(|icon| {
+                    icon.refresh();
+                    !icon.exists && !icon.is_web_link()
+                });
+                if missing {
+                    return self.offer_relink(index);
+                }
+
+                // 先に開くもの (パスか URL) だけを取得して, self への借用を終わらせるよ
+                let maybe_path = self.model.icons.get(index).map(|i| i.launch_target());
                 
                 if let Some(path) = maybe_path {
                     // ここからは &mut self を自由に使えるよ
                     self.model.executing_index = Some(index);
+                    self.launch_feedback();
                     self.draw()?;
                     
                     unsafe { SetTimer(self.hwnd, IDT_EXECUTE_FLASH, 150, None); }
@@ -276,39 +1145,72 @@ impl GroupWindow {
                 }
             }
             InteractionAction::OpenLocation { index } => {
-                let icon_path = self.model.icons.get(index).map(|i| i.path.clone());
+                // URL そのもののアイコンには開く場所がないよ
+                let icon_path = self.model.icons.get(index).filter(|i| !i.is_web_link()).map(|i| i.path.clone());
                 if let Some(path) = icon_path {
                     log::info!("Opening location: {:?}", path);
                     api::shell::open_file_location(&path)?;
                 }
             }
             InteractionAction::ReorderIcon { from, to } => {
+                // 自動整列中は手で並べ替えても次の追加で戻ってしまうから, 受け付けないよ
+                if self.model.auto_sort.is_some() {
+                    log::warn!("Group {} is auto-sorted. Manual reordering is disabled.", self.model.id);
+                    return Ok(());
+                }
                 if from < self.model.icons.len() && to < self.model.icons.len() {
-                    self.model.icons.swap(from, to);
+                    // 入れ替えではなく, 抜き出して to の位置に差し込むよ (間のアイコンは1つずつずれるね)
+                    let icon = self.model.icons.remove(from);
+                    self.model.icons.insert(to, icon);
+                    self.model.selected.clear();
+                    self.model.focused_icon = None;
+                    self.model.hovered_index = Some(to);
                     let mut settings = manager::get_settings_writer();
                     if let Some(child) = settings.children.get_mut(&self.model.id) {
-                        child.icons.swap(from, to);
+                        if from < child.icons.len() && to < child.icons.len() {
+                            let info = child.icons.remove(from);
+                            child.icons.insert(to, info);
+                        }
                         drop(settings);
                         manager::save();
                     }
                     self.draw()?;
                 }
             }
+            InteractionAction::DragIcon { index, target, pos } => {
+                self.model.icon_drag = Some((index, pos));
+                self.model.hovered_index = target;
+                self.draw()?;
+            }
             InteractionAction::DeleteIcon { index } => {
-                if index < self.model.icons.len() {
-                    self.model.icons.remove(index);
+                let label = self.model.icons.get(index).map(|icon| format!("\"{}\"", icon.label()));
+                if label.is_some_and(|label| self.confirm_icon_delete(&label)) {
+                    let icon = self.model.icons.remove(index);
+                    self.deleted_icons = vec![(index, icon)];
+                    self.model.selected.clear();
                     let mut settings = manager::get_settings_writer();
                     if let Some(child) = settings.children.get_mut(&self.model.id) {
                         child.icons.remove(index);
                         drop(settings);
                         manager::save();
                     }
+                    self.auto_fit();
+                    self.draw()?;
+                }
+            }
+            InteractionAction::DuplicateIcon { index } => {
+                if let Some(icon) = self.model.icons.get(index).cloned() {
+                    log::info!("Duplicating icon: {:?}", icon.path);
+                    self.model.icons.insert(index + 1, icon);
+                    self.model.hovered_index = None;
+                    self.model.apply_auto_sort();
+                    self.save_icons();
                     self.draw()?;
                 }
             }
             InteractionAction::DeleteGroup => {
                 let mut settings = manager::get_settings_writer();
-                settings.children.remove(&self.model.id);
+                self.deleted_settings = settings.children.remove(&self.model.id);
                 drop(settings);
                 manager::save();
                 unsafe {
@@ -323,29 +1225,826 @@ impl GroupWindow {
             }
             InteractionAction::HoverChanged { index } => {
                 self.model.hovered_index = index;
+                // フォルダの中身が変わっているかもしれないから, 乗ったアイコンの項目数は数え直しておくよ
+                // (少し前に数えたばかりなら, そのままだよ)
+                if let Some(icon) = index.and_then(|i| self.model.icons.get_mut(i)) {
+                    icon.recount_folder_if_stale();
+                }
+
+                // アニメーションはホバー中のアイコンだけ. 対象が変わったら最初のフレームから再生するよ
+                self.model.animation_frame = 0;
+                let animate = index
+                    .and_then(|i| self.model.icons.get(i))
+                    .is_some_and(|icon| icon.exists && icon.is_animatable());
+                unsafe {
+                    if animate {
+                        SetTimer(self.hwnd, IDT_ANIMATION, ANIMATION_INTERVAL_MS, None);
+                    } else {
+                        KillTimer(self.hwnd, IDT_ANIMATION).ok();
+                    }
+                }
+                self.draw()?;
+            }
+            InteractionAction::StepColorHistory { forward } => {
+                let state = if forward { self.color_history.forward() } else { self.color_history.back() };
+                if let Some(state) = state {
+                    // 履歴からの復元は新しい履歴として積まないよ
+                    self.model.bg_color_hex = state.bg_color_hex.clone();
+                    self.model.opacity = state.opacity;
+                    let mut settings = manager::get_settings_writer();
+                    if let Some(child) = settings.children.get_mut(&self.model.id) {
+                        child.bg_color = state.bg_color_hex;
+                        child.opacity = state.opacity;
+                        drop(settings);
+                        manager::save();
+                    }
+                    self.draw()?;
+                }
+            }
+            InteractionAction::EmptyClick => {
+                let empty_click_action = manager::get_settings_reader().app.empty_click_action;
+                if empty_click_action == EmptyClickAction::Raise {
+                    // こちらはカーソルが離れるまで出しておくよ
+                    unsafe { KillTimer(self.hwnd, IDT_LOWER).ok(); }
+                    self.raise();
+                }
+            }
+            InteractionAction::UpdateSelection { rect } => {
+                let (left, top, right, bottom) = rect;
+                let layout = self.current_layout();
+                self.model.selected = layout
+                    .items
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, item)| {
+                        let r = &item.hit_rect;
+                        r.left < right && r.right > left && r.top < bottom && r.bottom > top
+                    })
+                    .map(|(i, _)| i)
+                    .collect();
+                self.model.selection_rect = Some(rect);
                 self.draw()?;
             }
+            InteractionAction::FitToContent => {
+                self.fit_to_content()?;
+                self.draw()?;
+            }
+            InteractionAction::DeleteSelected => {
+                let count = self.model.selected.len();
+                if count > 0 && self.confirm_icon_delete(&format!("{} icon(s)", count)) {
+                    let selected = std::mem::take(&mut self.model.selected);
+                    let mut kept = Vec::with_capacity(self.model.icons.len());
+                    let mut removed = Vec::new();
+                    for (index, icon) in std::mem::take(&mut self.model.icons).into_iter().enumerate() {
+                        if selected.contains(&index) {
+                            removed.push((index, icon));
+                        } else {
+                            kept.push(icon);
+                        }
+                    }
+                    self.model.icons = kept;
+                    self.deleted_icons = removed;
+                    self.model.hovered_index = None;
+                    self.save_icons();
+                    self.draw()?;
+                }
+            }
+            InteractionAction::CopyIconPath { index } => {
+                // URL のアイコンなら URL をコピーするよ
+                if let Some(text) = self.model.icons.get(index).map(|i| i.launch_target().to_string_lossy().into_owned()) {
+                    if api::utils::set_clipboard_text(&text) {
+                        log::info!("Copied to clipboard: {}", text);
+                        self.show_toast("Path copied".to_string())?;
+                    } else {
+                        log::warn!("Failed to copy {} to the clipboard.", text);
+                    }
+                }
+            }
+            InteractionAction::ExecuteSelected => {
+                let paths: Vec<std::path::PathBuf> = self
+                    .model
+                    .icons
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| self.model.selected.contains(i))
+                    .map(|(_, icon)| icon.launch_target())
+                    .collect();
+                if !paths.is_empty() {
+                    self.launch_feedback();
+                    self.draw()?;
+                }
+                // 1つ開けなくても, 残りは開くよ
+                for path in paths {
+                    log::info!("Executing: {:?}", path);
+                    if let Err(e) = api::shell::execute_path(&path) {
+                        log::error!("Failed to execute {:?}: {}", path, e);
+                    }
+                }
+            }
+            InteractionAction::ExecuteAll => {
+                self.execute_all()?;
+            }
+            InteractionAction::BeginRename => {
+                self.begin_rename()?;
+            }
+            InteractionAction::ToggleTopmost => {
+                self.set_topmost(!self.topmost)?;
+            }
+            InteractionAction::ToggleCollapse => {
+                self.toggle_collapse()?;
+            }
+            InteractionAction::ToggleLock => {
+                let lock = if self.model.lock.is_empty() { LockFlags::all() } else { LockFlags::default() };
+                self.set_lock(lock)?;
+            }
+            InteractionAction::MoveIconFocus { dx, dy } => {
+                self.move_icon_focus(dx, dy)?;
+            }
+            InteractionAction::BeginIconRename { index } => {
+                self.begin_icon_rename(index)?;
+            }
+            InteractionAction::CycleBackgroundMode => {
+                let mode = self.model.bg_mode.next(self.model.bg_image.is_some());
+                self.set_background_mode(mode);
+                self.show_toast(format!("Background: {}", mode.label()))?;
+            }
+            InteractionAction::Scroll { notches } => {
+                self.scroll(notches)?;
+            }
+            InteractionAction::CycleSizePreset => {
+                self.cycle_size_preset()?;
+            }
             InteractionAction::None => {}
         }
         Ok(())
     }
 
-    pub fn handle_drop_files(&mut self, paths: Vec<std::path::PathBuf>) -> Result<(), windows::core::Error> {
+    /// このグループだけの文字サイズを変えて保存するよ (None なら全体の設定に戻すね)。
+    pub fn set_font_size(&mut self, size: Option<f32>) -> Result<(), windows::core::Error> {
+        self.model.font_size = size.map(|size| size.clamp(8.0, 72.0));
+        let mut settings = manager::get_settings_writer();
+        if let Some(child) = settings.children.get_mut(&self.model.id) {
+            child.font_size = self.model.font_size;
+            drop(settings);
+            manager::save();
+        }
+        // 文字の高さが変わるとレイアウトも変わるから, 必要なら大きさも合わせ直すよ
+        self.auto_fit();
+        let label = match self.model.font_size {
+            Some(size) => format!("Font size: {}", size),
+            None => "Font size: default".to_string(),
+        };
+        self.show_toast(label)
+    }
+
+    /// このグループだけのフォントを変えて保存するよ (None なら全体の設定に戻すね)。
+    /// インストールされているフォントの名前で指定してね。無い名前なら DirectWrite が似たフォントで代わりに描くよ。
+    pub fn set_font_family(&mut self, family: Option<String>) -> Result<(), windows::core::Error> {
+        self.model.font_family = family;
+        let mut settings = manager::get_settings_writer();
+        if let Some(child) = settings.children.get_mut(&self.model.id) {
+            child.font_family = self.model.font_family.clone();
+            drop(settings);
+            manager::save();
+        }
+        let label = match &self.model.font_family {
+            Some(family) => format!("Font: {}", family),
+            None => "Font: default".to_string(),
+        };
+        self.show_toast(label)
+    }
+
+    /// 背景色を変えて保存するよ。色の履歴にも残すね。
+    fn set_background_color(&mut self, hex: String) -> Result<(), windows::core::Error> {
+        self.model.bg_color_hex = hex.clone();
+        self.color_history.record(self.color_state());
+        let mut settings = manager::get_settings_writer();
+        if let Some(child) = settings.children.get_mut(&self.model.id) {
+            child.bg_color = hex;
+            drop(settings);
+            manager::save();
+        }
+        self.draw()
+    }
+
+    /// 色の選択ダイアログで背景色を選ばせるよ！ 透明度 (#RRGGBBAA の AA) は今のままだよ。
+    fn pick_background_color(&mut self) -> Result<(), windows::core::Error> {
+        let current = self.model.bg_color_hex.trim_start_matches('#');
+        let channel = |i: usize| current.get(i..i + 2).and_then(|s| u8::from_str_radix(s, 16).ok()).unwrap_or(0);
+        let alpha = current.get(6..8).map(str::to_string);
+        let initial = (channel(0), channel(2), channel(4));
+
+        let Some((r, g, b)) = api::dialog::choose_color(self.hwnd, initial) else {
+            return Ok(());
+        };
+        let hex = format!("#{:02X}{:02X}{:02X}{}", r, g, b, alpha.unwrap_or_default());
+        log::info!("Picked background color {} for group {}", hex, self.model.id);
+        self.set_background_color(hex)
+    }
+
+    /// アイコンの並べ方 (グリッド / リスト) を変えて保存するよ。
+    fn set_layout_mode(&mut self, mode: LayoutMode) -> Result<(), windows::core::Error> {
+        self.model.layout_mode = mode;
+        let mut settings = manager::get_settings_writer();
+        if let Some(child) = settings.children.get_mut(&self.model.id) {
+            child.layout_mode = mode;
+            drop(settings);
+            manager::save();
+        }
+        // 行の高さが変わるから, 必要なら大きさも合わせ直すよ
+        self.auto_fit();
+        let label = match mode {
+            LayoutMode::Grid => "Layout: grid",
+            LayoutMode::List => "Layout: list",
+        };
+        self.show_toast(label.to_string())
+    }
+
+    /// 埋まりきらない行の寄せ方を変えて保存するよ。
+    fn set_row_alignment(&mut self, align: RowAlign) -> Result<(), windows::core::Error> {
+        self.model.row_alignment = align;
+        let mut settings = manager::get_settings_writer();
+        if let Some(child) = settings.children.get_mut(&self.model.id) {
+            child.row_alignment = align;
+            drop(settings);
+            manager::save();
+        }
+        let label = match align {
+            RowAlign::Left => "Align: left",
+            RowAlign::Center => "Align: center",
+        };
+        self.show_toast(label.to_string())
+    }
+
+    /// アイコン名を出すかどうかを変えて保存するよ。
+    fn set_show_labels(&mut self, show: bool) -> Result<(), windows::core::Error> {
+        self.model.show_labels = show;
+        let mut settings = manager::get_settings_writer();
+        if let Some(child) = settings.children.get_mut(&self.model.id) {
+            child.show_labels = show;
+            drop(settings);
+            manager::save();
+        }
+        // セルの大きさが変わるから, 必要なら大きさも合わせ直すよ
+        self.auto_fit();
+        self.show_toast(if show { "Labels: on" } else { "Labels: off" }.to_string())
+    }
+
+    /// 背景の塗り方を変えて保存するよ。
+    fn set_background_mode(&mut self, mode: BackgroundMode) {
+        self.model.bg_mode = mode;
+        let mut settings = manager::get_settings_writer();
+        if let Some(child) = settings.children.get_mut(&self.model.id) {
+            child.bg_mode = mode;
+            drop(settings);
+            manager::save();
+        }
+    }
+
+    /// 背景画像を設定 (None なら解除) して保存するよ。
+    /// 設定したときはすぐ見えるように画像モードに, 解除したときは画像モードなら単色に戻すね。
+    fn set_background_image(&mut self, image: Option<std::path::PathBuf>) -> Result<(), windows::core::Error> {
+        let mode = match (&image, self.model.bg_mode) {
+            (Some(_), _) => BackgroundMode::Image,
+            (None, BackgroundMode::Image) => BackgroundMode::Solid,
+            (None, mode) => mode,
+        };
+        self.model.bg_image = image.clone();
+        {
+            let mut settings = manager::get_settings_writer();
+            if let Some(child) = settings.children.get_mut(&self.model.id) {
+                child.bg_image = image;
+            }
+        }
+        self.set_background_mode(mode);
+        self.draw()
+    }
+
+    /// ファイルがドロップされたときの処理だよ。
+    /// 見出しの上ならそのセクション, アイコンの上ならそのアイコンのセクションに追加するよ。
+    /// 空白部分へのドロップは最後のセクションに追加するね。
+    /// ファイルのドラッグが上に来たら枠を光らせて, 離れたり落とされたりしたら戻すよ。
+    pub fn handle_drag_hover(&mut self, hovering: bool) -> Result<(), windows::core::Error> {
+        // ロック中は受け取らないから, 光らせないでおくね
+        let hovering = hovering && !self.model.lock.edit_icons;
+        if self.model.drop_target == hovering {
+            return Ok(());
+        }
+        self.model.drop_target = hovering;
+        self.draw()
+    }
+
+    pub fn handle_drop_files(&mut self, paths: Vec<std::path::PathBuf>, drop_point: Option<(f32, f32)>) -> Result<(), windows::core::Error> {
+        if self.icons_locked() {
+            return Ok(());
+        }
+        let section = self.section_at(drop_point);
+
+        // 修飾キーを押しながらフォルダを落としたら, フォルダの代わりに中身を1つずつ追加するよ
+        let keymap = Keymap::current();
+        let paths = if Modifiers::current().holds(keymap.expand_folder_drop) {
+            self.expand_folders(paths)?
+        } else {
+            paths
+        };
+
+        for path in paths {
+            let index = self.model.insertion_index_for_section(section.as_deref());
+            self.model.icons.insert(index, IconState::new(path, section.clone()));
+        }
+
+        // 自動整列が有効なら, 追加したものも含めて並べ直すよ
+        self.model.apply_auto_sort();
+
+        // 追加が終わってから1回だけ保存と再描画をするよ
+        self.save_icons();
+        self.draw()
+    }
+
+    /// フォルダをその直下のファイルとフォルダに置き換えるよ (フォルダ以外はそのまま)。
+    /// グループがあふれないように, 1つのフォルダから追加するのは MAX_FOLDER_ENTRIES 個までだよ。
+    fn expand_folders(&mut self, paths: Vec<std::path::PathBuf>) -> Result<Vec<std::path::PathBuf>, windows::core::Error> {
+        let mut expanded = Vec::new();
         for path in paths {
-            let name = path.file_stem().and_then(|n| n.to_str()).unwrap_or("Unknown").to_string();
-            self.model.icons.push(crate::ui::group::model::IconState { name, path: path.clone(), exists: true });
+            if !path.is_dir() {
+                expanded.push(path);
+                continue;
+            }
+            match api::shell::list_folder(&path, MAX_FOLDER_ENTRIES) {
+                Ok((entries, truncated)) => {
+                    if truncated {
+                        log::warn!("Folder {:?} has more than {} entries. Only the first ones were added.", path, MAX_FOLDER_ENTRIES);
+                        self.show_toast(format!("Added the first {} items only", MAX_FOLDER_ENTRIES))?;
+                    }
+                    expanded.extend(entries);
+                }
+                Err(e) => {
+                    // 読めないフォルダは, いつもどおりフォルダ1つとして追加するよ
+                    log::warn!("Failed to read folder {:?}: {}", path, e);
+                    expanded.push(path);
+                }
+            }
+        }
+        Ok(expanded)
+    }
+
+    /// ドロップ位置から追加先のセクションを決めるよ。
+    fn section_at(&self, point: Option<(f32, f32)>) -> Option<String> {
+        let last_section = || self.model.icons.last().and_then(|i| i.section.clone());
+        let Some((x, y)) = point else {
+            return last_section();
+        };
+
+        let layout = self.current_layout();
+        if let Some(header) = layout.hit_header(x, y) {
+            return Some(header.name.clone());
+        }
+        if let Some(index) = layout.hit_item(x, y) {
+            return self.model.icons[index].section.clone();
+        }
+        last_section()
+    }
+
+    /// アイコンを取り直して再描画するよ。アイコンパックの変更などを反映したいとき用だね。
+    /// 並び順や表示名, 位置には触らないよ。
+    pub fn refresh_icons(&mut self) -> Result<(), windows::core::Error> {
+        for icon in &mut self.model.icons {
+            icon.refresh();
+        }
+        self.renderer.invalidate_icons();
+        self.draw()
+    }
+
+    /// ロックを変えて保存するよ。いまロックされているものをトーストで教えるね。
+    fn set_lock(&mut self, lock: LockFlags) -> Result<(), windows::core::Error> {
+        self.model.lock = lock;
+        {
             let mut settings = manager::get_settings_writer();
             if let Some(child) = settings.children.get_mut(&self.model.id) {
-                child.icons.push(crate::settings::models::PersistentIconInfo { path: path.clone() });
+                child.lock = lock;
                 drop(settings);
                 manager::save();
             }
         }
+        log::info!("Lock of group {}: {:?}", self.model.id, lock);
+
+        let locked: Vec<&str> = [(lock.r#move, "move"), (lock.resize, "resize"), (lock.edit_icons, "icons")]
+            .into_iter()
+            .filter_map(|(on, name)| on.then_some(name))
+            .collect();
+        let text = if locked.is_empty() { "Unlocked".to_string() } else { format!("Locked: {}", locked.join(", ")) };
+        self.show_toast(text)
+    }
+
+    /// アイコンの編集がロックされているかどうかだよ。ロック中ならトーストで知らせるね。
+    pub fn icons_locked(&mut self) -> bool {
+        if !self.model.lock.edit_icons {
+            return false;
+        }
+        log::info!("Icons of group {} are locked.", self.model.id);
+        if let Err(e) = self.show_toast("Icons are locked".to_string()) {
+            log::error!("Draw error: {}", e);
+        }
+        true
+    }
+
+    /// アイコンの隙間やバラバラになったセクションを詰めて, きれいに並べ直すよ (順番はそのまま)。
+    /// collapse_sections なら見出しも外すね。変わったときだけ保存するよ。
+    pub fn compact(&mut self, collapse_sections: bool) -> Result<(), windows::core::Error> {
+        if self.icons_locked() {
+            return Ok(());
+        }
+        if self.model.compact(collapse_sections) {
+            log::info!("Compacted group {}", self.model.id);
+            self.model.hovered_index = None;
+            self.save_icons();
+        }
+        self.show_toast("Tidied".to_string())
+    }
+
+    /// グループの中で重複しているアイコンを取り除いて保存するよ。取り除いた数を返すね。
+    pub fn dedupe_icons(&mut self) -> Result<usize, windows::core::Error> {
+        if self.icons_locked() {
+            return Ok(0);
+        }
+        let removed = self.model.dedupe();
+        if removed > 0 {
+            log::info!("Removed {} duplicate icon(s) from group {}", removed, self.model.id);
+            self.model.hovered_index = None;
+            self.save_icons();
+            self.show_toast(format!("Removed {} duplicate(s)", removed))?;
+        }
+        Ok(removed)
+    }
+
+    /// アイコンに自分で用意した画像を設定 (None なら解除) して保存するよ。
+    pub fn set_custom_icon(&mut self, index: usize, image: Option<std::path::PathBuf>) -> Result<(), windows::core::Error> {
+        let Some(icon) = self.model.icons.get_mut(index) else {
+            return Ok(());
+        };
+        log::info!("Custom icon for {:?}: {:?}", icon.path, image);
+        icon.custom_icon_path = image.clone();
+        let mut settings = manager::get_settings_writer();
+        if let Some(info) = settings.children.get_mut(&self.model.id).and_then(|child| child.icons.get_mut(index)) {
+            info.custom_icon_path = image;
+            drop(settings);
+            manager::save();
+        }
+        self.draw()
+    }
+
+    /// index のアイコンに表示名を付けるよ。空かファイル名と同じなら, 付けた名前を外してファイル名に戻すね。
+    pub fn rename_icon(&mut self, index: usize, name: &str) -> Result<(), windows::core::Error> {
+        let Some(icon) = self.model.icons.get_mut(index) else {
+            return Ok(());
+        };
+        let name = name.trim();
+        let custom_name = (!name.is_empty() && name != icon.name).then(|| name.to_string());
+        if custom_name == icon.custom_name {
+            return self.draw();
+        }
+        log::info!("Icon label for {:?}: {:?}", icon.path, custom_name);
+        icon.custom_name = custom_name.clone();
+        let mut settings = manager::get_settings_writer();
+        if let Some(info) = settings.children.get_mut(&self.model.id).and_then(|child| child.icons.get_mut(index)) {
+            info.custom_name = custom_name;
+            drop(settings);
+            manager::save();
+        }
+        self.draw()
+    }
+
+    /// 選択中のアイコンのパスを, 並び順で返すよ。
+    pub fn selected_paths(&self) -> Vec<std::path::PathBuf> {
+        self.model
+            .icons
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| self.model.selected.contains(i))
+            .map(|(_, icon)| icon.path.clone())
+            .collect()
+    }
+
+    /// 指定したパスのアイコンを取り出して (グループからは削除して) 返すよ。
+    pub fn remove_icons_by_paths(&mut self, paths: &[std::path::PathBuf]) -> Vec<IconState> {
+        let (removed, kept): (Vec<IconState>, Vec<IconState>) = std::mem::take(&mut self.model.icons)
+            .into_iter()
+            .partition(|icon| paths.contains(&icon.path));
+        self.model.icons = kept;
+        self.model.hovered_index = None;
+        self.save_icons();
+        if let Err(e) = self.draw() {
+            log::error!("Draw error: {}", e);
+        }
+        removed
+    }
+
+    /// 他のグループから移ってきたアイコンを追加するよ。セクションは最後のセクションに合わせるね。
+    pub fn add_icons(&mut self, icons: Vec<IconState>) -> Result<(), windows::core::Error> {
+        let section = self.model.icons.last().and_then(|i| i.section.clone());
+        for icon in icons {
+            let index = self.model.insertion_index_for_section(section.as_deref());
+            self.model.icons.insert(index, IconState { section: section.clone(), ..icon });
+        }
+        self.model.apply_auto_sort();
+        self.save_icons();
+        self.draw()
+    }
+
+    fn color_state(&self) -> ColorState {
+        ColorState {
+            bg_color_hex: self.model.bg_color_hex.clone(),
+            opacity: self.model.opacity,
+        }
+    }
+
+    /// 自動整列の基準を変えて保存するよ。設定したときはその場で並べ替えるね。
+    fn set_auto_sort(&mut self, key: Option<SortKey>) -> Result<(), windows::core::Error> {
+        if self.icons_locked() {
+            return Ok(());
+        }
+        self.model.auto_sort = key;
+        self.model.hovered_index = None;
+        self.model.apply_auto_sort();
+
+        {
+            let mut settings = manager::get_settings_writer();
+            if let Some(child) = settings.children.get_mut(&self.model.id) {
+                child.auto_sort = key;
+            }
+        }
+        self.save_icons();
+        self.draw()
+    }
+
+    /// 削除したアイコンを元の位置に戻して保存するよ。icons は元の位置の小さい順に並んでいてね。
+    /// 間のアイコンが減っていたら, 入れられる一番近い位置に入れるよ。
+    pub fn restore_icons(&mut self, icons: Vec<(usize, IconState)>) -> Result<(), windows::core::Error> {
+        log::info!("Restoring {} icon(s) to group {}.", icons.len(), self.model.id);
+        for (index, icon) in icons {
+            let index = index.min(self.model.icons.len());
+            self.model.icons.insert(index, icon);
+        }
+        self.model.hovered_index = None;
+        self.model.apply_auto_sort();
+        self.save_icons();
+        self.draw()
+    }
+
+    /// 今のアイコンを一度だけ並べ替えて保存するよ。自動整列とは違って, あとから追加したものはそのままだよ。
+    fn sort_once(&mut self, key: SortKey) -> Result<(), windows::core::Error> {
+        if self.icons_locked() {
+            return Ok(());
+        }
+        self.model.hovered_index = None;
+        self.model.sort_icons(key);
+        self.save_icons();
+        self.show_toast("Sorted".to_string())
+    }
+
+    /// モデルのアイコン一覧で設定ファイル側のアイコン一覧を置き換えて保存するよ。
+    /// 並べ替えやセクション変更のように順序が変わる操作のあとで使ってね。
+    /// インデックスがずれるから, 範囲選択と矢印キーのフォーカスは解除するね。
+    fn save_icons(&mut self) {
+        self.model.selected.clear();
+        self.model.focused_icon = None;
+        let mut settings = manager::get_settings_writer();
+        let use_env_paths = settings.app.use_env_paths;
+        if let Some(child) = settings.children.get_mut(&self.model.id) {
+            child.icons = self
+                .model
+                .icons
+                .iter()
+                .map(|icon| {
+                    let info = icon.to_persistent();
+                    if use_env_paths { info.with_env_paths() } else { info }
+                })
+                .collect();
+            drop(settings);
+            manager::save();
+        }
+        self.auto_fit();
+    }
+
+    /// グループの大きさを size_presets の次のものに切り替えるよ。
+    /// 最後のプリセットの次は, 切り替えを始める前の大きさに戻すね。
+    fn cycle_size_preset(&mut self) -> Result<(), windows::core::Error> {
+        let presets = manager::get_settings_reader().app.size_presets.clone();
+        if presets.is_empty() {
+            log::info!("No size presets configured.");
+            return Ok(());
+        }
+
+        let mut rect = RECT::default();
+        unsafe { GetWindowRect(self.hwnd, &mut rect)?; }
+        let current = (rect.right - rect.left, rect.bottom - rect.top);
+
+        let (next, original) = match self.size_preset {
+            Some((index, original)) => (index + 1, original),
+            None => (0, current),
+        };
+        let (width, height, label) = match presets.get(next) {
+            Some(&(width, height)) => {
+                self.size_preset = Some((next, original));
+                (width as i32, height as i32, format!("Size: {} x {}", width, height))
+            }
+            None => {
+                self.size_preset = None;
+                (original.0, original.1, "Size: original".to_string())
+            }
+        };
+
+        self.set_window_size(width, height)?;
+        self.show_toast(label)
+    }
+
+    /// グループの大きさを変えて保存するよ。小さすぎず, 画面 (作業領域) からはみ出さないようにするね。
+    fn set_window_size(&mut self, width: i32, height: i32) -> Result<(), windows::core::Error> {
+        const MIN_SIZE: i32 = 50;
+        if self.model.lock.resize {
+            log::info!("Size of group {} is locked.", self.model.id);
+            return Ok(());
+        }
+        // 折りたたみ中に大きさを変えると, 広げたときの高さと食い違うからね
+        if self.model.collapsed {
+            log::info!("Group {} is collapsed. Skipping resize.", self.model.id);
+            return Ok(());
+        }
+
+        let mut rect = RECT::default();
+        unsafe { GetWindowRect(self.hwnd, &mut rect)?; }
+        let work = api::show_window::work_area_at(rect.left, rect.top);
+
+        let new_width = width.clamp(MIN_SIZE, (work.right - rect.left).max(MIN_SIZE));
+        let new_height = height.clamp(MIN_SIZE, (work.bottom - rect.top).max(MIN_SIZE));
+
+        unsafe {
+            SetWindowPos(self.hwnd, HWND_BOTTOM, 0, 0, new_width, new_height, SWP_NOMOVE | self.position_flags())?;
+        }
+
+        let mut settings = manager::get_settings_writer();
+        if let Some(child) = settings.children.get_mut(&self.model.id) {
+            child.width = new_width as u32;
+            child.height = new_height as u32;
+            drop(settings);
+            manager::save();
+        }
+        Ok(())
+    }
+
+    /// auto_fit が有効なら, アイコンの増減に合わせて大きさを調整するよ。
+    fn auto_fit(&mut self) {
+        // 読み取りロックを持ったまま大きさを変えないように, 先に取り出しておくよ
+        let auto_fit = manager::get_settings_reader().app.auto_fit;
+        if auto_fit && let Err(e) = self.fit_to_content() {
+            log::error!("Fit to content error: {}", e);
+        }
+    }
+
+    /// グループの大きさをアイコンがちょうど収まるように変えて保存するよ！
+    /// 今の列数はできるだけ保って, 画面 (作業領域) からはみ出さないようにするね。
+    /// リスト表示は名前が途中で切れないように, 今の幅のままで高さだけ合わせるよ。
+    pub fn fit_to_content(&mut self) -> Result<(), windows::core::Error> {
+        let params = self.model.layout_params(&manager::get_settings_reader().app);
+        let (cell_width, _) = layout::cell_size(&params);
+        let current = self.current_layout();
+        let columns = current.columns.min(self.model.icons.len()).max(1);
+
+        let width = match params.mode {
+            LayoutMode::Grid => layout::PADDING * 2.0 + columns as f32 * cell_width - params.grid_gap,
+            LayoutMode::List => {
+                let mut rect = RECT::default();
+                unsafe { windows::Win32::UI::WindowsAndMessaging::GetClientRect(self.hwnd, &mut rect)?; }
+                (rect.right - rect.left) as f32
+            }
+        };
+        let fitted = layout::calculate_group_layout(width, &self.model.sections(), &params);
+        let height = fitted.content_height();
+
+        self.set_window_size(width.ceil() as i32, height.ceil() as i32)
+    }
+
+    /// 矢印キーでアイコンのフォーカスを動かすよ。
+    /// まだどこにもフォーカスが無ければ, カーソルの下のアイコン (無ければ先頭) から始めるね。
+    /// 見えないところに動いたら, 見える位置までスクロールするよ。
+    fn move_icon_focus(&mut self, dx: i32, dy: i32) -> Result<(), windows::core::Error> {
+        if self.model.icons.is_empty() || self.model.collapsed {
+            return Ok(());
+        }
+        let mut rect = RECT::default();
+        unsafe { windows::Win32::UI::WindowsAndMessaging::GetClientRect(self.hwnd, &mut rect)?; }
+        let (width, height) = ((rect.right - rect.left) as f32, (rect.bottom - rect.top) as f32);
+
+        // スクロールする前の位置で考えるよ
+        let params = self.model.layout_params(&manager::get_settings_reader().app);
+        let group_layout = layout::calculate_group_layout(width, &self.model.sections(), &params);
+        let next = match self.model.focused_icon.filter(|&index| index < self.model.icons.len()) {
+            Some(current) => group_layout.neighbor(current, dx, dy).unwrap_or(current),
+            None => self.model.hovered_index.unwrap_or(0),
+        };
+        self.model.focused_icon = Some(next);
+
+        if let Some(item) = group_layout.items.get(next) {
+            let max = group_layout.max_scroll(height);
+            let mut offset = self.model.scroll_offset.clamp(0.0, max);
+            let top = group_layout.content_top();
+            if item.hit_rect.top - offset < top {
+                offset = item.hit_rect.top - top;
+            } else if item.hit_rect.bottom - offset > height - layout::PADDING {
+                offset = item.hit_rect.bottom - height + layout::PADDING;
+            }
+            self.model.scroll_offset = offset.clamp(0.0, max);
+        }
+        self.draw()
+    }
+
+    /// 左上を position に動かしたときに, 画面の端や他のグループの端へ吸い付けた位置を返すよ。
+    /// snap_threshold が 0 なら吸い付けないよ。
+    fn snap_position(&self, rect: &RECT, position: (i32, i32)) -> (i32, i32) {
+        let settings = manager::get_settings_reader();
+        let threshold = settings.app.snap_threshold;
+        if threshold <= 0 {
+            return position;
+        }
+        let others: Vec<RECT> = settings
+            .children
+            .iter()
+            .filter(|(id, _)| **id != self.model.id)
+            .map(|(_, child)| RECT {
+                left: child.x,
+                top: child.y,
+                right: child.x + child.width as i32,
+                bottom: child.y + child.height as i32,
+            })
+            .collect();
+        drop(settings);
+
+        let moved = RECT {
+            left: position.0,
+            top: position.1,
+            right: position.0 + (rect.right - rect.left),
+            bottom: position.1 + (rect.bottom - rect.top),
+        };
+        let work_area = api::show_window::work_area_at(
+            (moved.left + moved.right) / 2,
+            (moved.top + moved.bottom) / 2,
+        );
+        snap::snap_position(&moved, &others, &work_area, threshold)
+    }
+
+    /// snap_size_to_grid が有効なら, 大きさをアイコンのセルがちょうど収まる大きさに丸めるよ。
+    fn snap_size_to_grid(&mut self) -> Result<(), windows::core::Error> {
+        let params = {
+            let settings = manager::get_settings_reader();
+            if !settings.app.snap_size_to_grid {
+                return Ok(());
+            }
+            self.model.layout_params(&settings.app)
+        };
+        let mut rect = RECT::default();
+        unsafe { GetWindowRect(self.hwnd, &mut rect)?; }
+        let (width, height) = layout::snap_size((rect.right - rect.left) as f32, (rect.bottom - rect.top) as f32, &params);
+        let (width, height) = (width.round() as i32, height.round() as i32);
+        if (width, height) == (rect.right - rect.left, rect.bottom - rect.top) {
+            return Ok(());
+        }
+        self.set_window_size(width, height)?;
         self.draw()
     }
 
     pub fn handle_lbutton_up(&mut self) {
+        // アイコンをドラッグしていたら, 離した場所のアイコンの位置へ移すよ (アイコンの上でなければ取り消し)
+        let icon_drop = self.interaction.take_icon_drop();
+        if self.model.icon_drag.take().is_some() {
+            if let Some((from, to)) = icon_drop
+                && let Err(e) = self.perform_action(InteractionAction::ReorderIcon { from, to })
+            {
+                log::error!("Failed to reorder icons: {}", e);
+            }
+            if let Err(e) = self.draw() {
+                log::error!("Draw error: {}", e);
+            }
+        }
+
+        // 空白部分をクリックしただけなら選択を解除, 範囲選択ならその結果を確定するよ
+        let clicked_empty = self.interaction.is_pending_selection();
+        let finished_selection = self.model.selection_rect.take().is_some();
+        if clicked_empty {
+            self.model.selected.clear();
+        }
+        if (clicked_empty || finished_selection) && let Err(e) = self.draw() {
+            log::error!("Draw error: {}", e);
+        }
+
+        self.drag_position = None;
+
+        // リサイズし終わったら, 設定によってはセルの大きさに合わせて丸めるよ
+        let finished_resize = self.interaction.is_resizing();
         self.interaction.handle_lbutton_up();
+        if finished_resize && let Err(e) = self.snap_size_to_grid() {
+            log::error!("Failed to snap group size: {}", e);
+        }
+        self.color_history.seal();
         unsafe { windows::Win32::UI::Input::KeyboardAndMouse::ReleaseCapture().ok(); }
     }
 }
@@ -37,7 +37,9 @@ impl HelpRenderer {
         bg_color_hex: &str,
         opacity: f32,
     ) -> Result<(), windows::core::Error> {
-        self.canvas.begin_draw();
+        if !self.canvas.begin_draw() {
+            return Ok(()); // 描画先がないので, このフレームは飛ばすよ
+        }
 
         let context = &self.canvas.d2d_context;
         let bg_rect = D2D_RECT_F { left: 0.0, top: 0.0, right: width, bottom: height };
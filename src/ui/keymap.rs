@@ -0,0 +1,290 @@
+use std::sync::{Arc, LazyLock, RwLock};
+use windows::Win32::UI::Input::KeyboardAndMouse::{GetKeyState, VK_CONTROL, VK_MENU, VK_SHIFT};
+use crate::settings::models::Keybindings;
+
+/// キーボードだけで起こせる操作だよ。どのキーで起こすかは Keymap が決めるんだ。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyAction {
+    Paste,
+    UndoColor,
+    RedoColor,
+    CycleBackground,
+    CycleSizePreset,
+    DuplicateIcon,
+    Rename,
+    Cut,
+    DeleteIcon,
+    OpenSelected,
+    OpenAll,
+    Solo,
+    RestoreGroup,
+    ToggleTopmost,
+    ToggleCollapse,
+    ToggleLock,
+    FocusLeft,
+    FocusRight,
+    FocusUp,
+    FocusDown,
+}
+
+/// 押されている修飾キーの組み合わせだよ。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Modifiers {
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+}
+
+impl Modifiers {
+    /// 今押されている修飾キーを調べるよ。
+    pub fn current() -> Self {
+        let is_down = |vk: i32| unsafe { (GetKeyState(vk) as u16 & 0x8000) != 0 };
+        Self {
+            ctrl: is_down(VK_CONTROL.0 as i32),
+            shift: is_down(VK_SHIFT.0 as i32),
+            alt: is_down(VK_MENU.0 as i32),
+        }
+    }
+
+    /// other の修飾キーが全部押されているか (他のキーが一緒に押されていても良い) だよ。
+    /// マウス操作の修飾キーは, 今までどおり優先順に「押されているか」だけで判定するからね。
+    pub fn holds(&self, other: Modifiers) -> bool {
+        (!other.ctrl || self.ctrl) && (!other.shift || self.shift) && (!other.alt || self.alt)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Modifiers::default()
+    }
+}
+
+/// "ctrl+shift+s" のようなキーの組み合わせだよ。key が None なら修飾キーだけだよ。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyCombo {
+    pub modifiers: Modifiers,
+    pub key: Option<i32>, // 仮想キーコード
+}
+
+impl KeyCombo {
+    /// 文字列から読み取るよ。大文字小文字と空白は気にしないよ。読めなければ None だよ。
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut modifiers = Modifiers::default();
+        let mut key = None;
+        for part in text.split('+').map(|p| p.trim().to_lowercase()) {
+            match part.as_str() {
+                "ctrl" | "control" => modifiers.ctrl = true,
+                "shift" => modifiers.shift = true,
+                "alt" => modifiers.alt = true,
+                name => {
+                    // キーは1つだけだよ
+                    if key.is_some() {
+                        return None;
+                    }
+                    key = Some(virtual_key(name)?);
+                }
+            }
+        }
+        if key.is_none() && modifiers.is_empty() {
+            return None;
+        }
+        Some(Self { modifiers, key })
+    }
+}
+
+/// キーの名前を仮想キーコードにするよ。
+fn virtual_key(name: &str) -> Option<i32> {
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) && c.is_ascii_alphanumeric() {
+        return Some(c.to_ascii_uppercase() as i32);
+    }
+    if let Some(n) = name.strip_prefix('f').and_then(|n| n.parse::<i32>().ok()) {
+        return (1..=12).contains(&n).then_some(0x70 + n - 1);
+    }
+    match name {
+        "delete" | "del" => Some(0x2E),
+        "enter" | "return" => Some(0x0D),
+        "space" => Some(0x20),
+        "insert" | "ins" => Some(0x2D),
+        "home" => Some(0x24),
+        "end" => Some(0x23),
+        "backspace" => Some(0x08),
+        "tab" => Some(0x09),
+        "left" => Some(0x25),
+        "up" => Some(0x26),
+        "right" => Some(0x27),
+        "down" => Some(0x28),
+        _ => None,
+    }
+}
+
+/// 今使っている対応表だよ。入力のたびに読み取り直すと, 読めない割り当ての警告が何度も出てしまうから覚えておくんだ。
+static CURRENT: LazyLock<RwLock<Arc<Keymap>>> = LazyLock::new(|| RwLock::new(Arc::new(Keymap::from_settings())));
+
+/// 設定の keybindings を読み取った, 操作とキーの対応表だよ！
+pub struct Keymap {
+    pub move_group: Modifiers,
+    pub resize_group: Modifiers,
+    pub adjust_opacity: Modifiers,
+    pub wheel_opacity: Modifiers,
+    pub wheel_border_width: Modifiers,
+    pub wheel_window_opacity: Modifiers,
+    pub delete_click: Modifiers,
+    pub rename_icon_click: Modifiers,
+    pub copy_path_click: Modifiers,
+    pub expand_folder_drop: Modifiers,
+    pub toggle_all_groups: KeyCombo, // システム全体のホットキーとして登録するよ
+    keys: Vec<(KeyAction, KeyCombo)>,
+}
+
+impl Keymap {
+    /// 今の対応表だよ。設定を読み直したら reload() で作り直してね。
+    pub fn current() -> Arc<Self> {
+        CURRENT.read().expect("Failed to acquire read lock on keymap").clone()
+    }
+
+    /// 今の設定から対応表を作り直して, これからはそれを使うよ。
+    pub fn reload() -> Arc<Self> {
+        let keymap = Arc::new(Self::from_settings());
+        *CURRENT.write().expect("Failed to acquire write lock on keymap") = keymap.clone();
+        keymap
+    }
+
+    fn from_settings() -> Self {
+        Self::new(&crate::settings::manager::get_settings_reader().app.keybindings)
+    }
+
+    /// 読めない割り当ては, ログに残して既定の割り当てを使うよ。
+    pub fn new(bindings: &Keybindings) -> Self {
+        let defaults = Keybindings::default();
+        let combo = |name: &str, value: &str, default: &str, needs_key: bool| {
+            match KeyCombo::parse(value).filter(|c| c.key.is_some() == needs_key) {
+                Some(combo) => combo,
+                None => {
+                    log::warn!("Invalid keybinding {} = {:?}. Using {:?}.", name, value, default);
+                    KeyCombo::parse(default).expect("default keybinding")
+                }
+            }
+        };
+        let modifiers = |name: &str, value: &str, default: &str| combo(name, value, default, false).modifiers;
+        let key = |action: KeyAction, name: &str, value: &str, default: &str| (action, combo(name, value, default, true));
+
+        // 移動とリサイズが同じ修飾キーだと, ドラッグはいつも移動になってしまうから既定に戻すよ
+        let (mut move_group, mut resize_group) = (
+            modifiers("move_group", &bindings.move_group, &defaults.move_group),
+            modifiers("resize_group", &bindings.resize_group, &defaults.resize_group),
+        );
+        if move_group == resize_group {
+            log::warn!(
+                "move_group and resize_group are both {:?}. Using {:?} and {:?}.",
+                bindings.move_group, defaults.move_group, defaults.resize_group
+            );
+            move_group = modifiers("move_group", &defaults.move_group, &defaults.move_group);
+            resize_group = modifiers("resize_group", &defaults.resize_group, &defaults.resize_group);
+        }
+
+        Self {
+            move_group,
+            resize_group,
+            adjust_opacity: modifiers("adjust_opacity", &bindings.adjust_opacity, &defaults.adjust_opacity),
+            wheel_opacity: modifiers("wheel_opacity", &bindings.wheel_opacity, &defaults.wheel_opacity),
+            wheel_border_width: modifiers("wheel_border_width", &bindings.wheel_border_width, &defaults.wheel_border_width),
+            wheel_window_opacity: modifiers("wheel_window_opacity", &bindings.wheel_window_opacity, &defaults.wheel_window_opacity),
+            delete_click: modifiers("delete_click", &bindings.delete_click, &defaults.delete_click),
+            rename_icon_click: modifiers("rename_icon_click", &bindings.rename_icon_click, &defaults.rename_icon_click),
+            copy_path_click: modifiers("copy_path_click", &bindings.copy_path_click, &defaults.copy_path_click),
+            expand_folder_drop: modifiers("expand_folder_drop", &bindings.expand_folder_drop, &defaults.expand_folder_drop),
+            toggle_all_groups: combo("toggle_all_groups", &bindings.toggle_all_groups, &defaults.toggle_all_groups, true),
+            keys: vec![
+                key(KeyAction::Paste, "paste", &bindings.paste, &defaults.paste),
+                key(KeyAction::UndoColor, "undo_color", &bindings.undo_color, &defaults.undo_color),
+                key(KeyAction::RedoColor, "redo_color", &bindings.redo_color, &defaults.redo_color),
+                key(KeyAction::CycleBackground, "cycle_background", &bindings.cycle_background, &defaults.cycle_background),
+                key(KeyAction::CycleSizePreset, "cycle_size_preset", &bindings.cycle_size_preset, &defaults.cycle_size_preset),
+                key(KeyAction::DuplicateIcon, "duplicate_icon", &bindings.duplicate_icon, &defaults.duplicate_icon),
+                key(KeyAction::Rename, "rename", &bindings.rename, &defaults.rename),
+                key(KeyAction::Cut, "cut", &bindings.cut, &defaults.cut),
+                key(KeyAction::DeleteIcon, "delete_icon", &bindings.delete_icon, &defaults.delete_icon),
+                key(KeyAction::OpenSelected, "open_selected", &bindings.open_selected, &defaults.open_selected),
+                key(KeyAction::OpenAll, "open_all", &bindings.open_all, &defaults.open_all),
+                key(KeyAction::Solo, "solo", &bindings.solo, &defaults.solo),
+                key(KeyAction::RestoreGroup, "restore_group", &bindings.restore_group, &defaults.restore_group),
+                key(KeyAction::ToggleTopmost, "toggle_topmost", &bindings.toggle_topmost, &defaults.toggle_topmost),
+                key(KeyAction::ToggleCollapse, "toggle_collapse", &bindings.toggle_collapse, &defaults.toggle_collapse),
+                key(KeyAction::ToggleLock, "toggle_lock", &bindings.toggle_lock, &defaults.toggle_lock),
+                key(KeyAction::FocusLeft, "focus_left", &bindings.focus_left, &defaults.focus_left),
+                key(KeyAction::FocusRight, "focus_right", &bindings.focus_right, &defaults.focus_right),
+                key(KeyAction::FocusUp, "focus_up", &bindings.focus_up, &defaults.focus_up),
+                key(KeyAction::FocusDown, "focus_down", &bindings.focus_down, &defaults.focus_down),
+            ],
+        }
+    }
+
+    /// 割り当てに使われているキー (仮想キーコード) の一覧だよ。押された瞬間を拾うのに使ってね。
+    pub fn keys(&self) -> Vec<i32> {
+        let mut keys: Vec<i32> = self.keys.iter().filter_map(|(_, combo)| combo.key).collect();
+        keys.sort_unstable();
+        keys.dedup();
+        keys
+    }
+
+    /// 今の修飾キーで key が押されたときに起こす操作だよ (修飾キーはぴったり同じものだけ)。
+    pub fn action_for(&self, modifiers: Modifiers, key: i32) -> Option<KeyAction> {
+        self.keys
+            .iter()
+            .find(|(_, combo)| combo.key == Some(key) && combo.modifiers == modifiers)
+            .map(|&(action, _)| action)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_key_combo() {
+        let combo = KeyCombo::parse("Ctrl + Shift + S").expect("parse");
+        assert_eq!(combo.modifiers, Modifiers { ctrl: true, shift: true, alt: false });
+        assert_eq!(combo.key, Some('S' as i32));
+
+        assert_eq!(KeyCombo::parse("f2").and_then(|c| c.key), Some(0x71));
+        assert_eq!(KeyCombo::parse("Down").and_then(|c| c.key), Some(0x28));
+        assert_eq!(KeyCombo::parse("alt").map(|c| (c.modifiers.alt, c.key)), Some((true, None)));
+        assert!(KeyCombo::parse("ctrl+a+b").is_none());
+        assert!(KeyCombo::parse("hyper+q").is_none());
+        assert!(KeyCombo::parse("").is_none());
+    }
+
+    #[test]
+    fn test_default_keymap_matches_builtin_shortcuts() {
+        let keymap = Keymap::new(&Keybindings::default());
+        let ctrl = Modifiers { ctrl: true, ..Default::default() };
+        let ctrl_shift = Modifiers { ctrl: true, shift: true, alt: false };
+
+        assert_eq!(keymap.action_for(ctrl, 'V' as i32), Some(KeyAction::Paste));
+        assert_eq!(keymap.action_for(ctrl, 'S' as i32), Some(KeyAction::CycleSizePreset));
+        assert_eq!(keymap.action_for(ctrl_shift, 'S' as i32), Some(KeyAction::Solo));
+        assert_eq!(keymap.action_for(Modifiers::default(), 0x2E), Some(KeyAction::DeleteIcon));
+        assert_eq!(keymap.action_for(Modifiers::default(), 'V' as i32), None);
+        assert_eq!(keymap.move_group, ctrl);
+    }
+
+    #[test]
+    fn test_invalid_binding_falls_back_to_default() {
+        let bindings = Keybindings { paste: "ctrl".to_string(), move_group: "ctrl+m".to_string(), ..Default::default() };
+        let keymap = Keymap::new(&bindings);
+        let ctrl = Modifiers { ctrl: true, ..Default::default() };
+        assert_eq!(keymap.action_for(ctrl, 'V' as i32), Some(KeyAction::Paste));
+        assert_eq!(keymap.move_group, ctrl);
+    }
+
+    #[test]
+    fn test_move_and_resize_need_different_modifiers() {
+        let alt = Modifiers { alt: true, ..Default::default() };
+        let keymap = Keymap::new(&Keybindings { move_group: "alt".to_string(), resize_group: "ctrl".to_string(), ..Default::default() });
+        assert_eq!(keymap.move_group, alt);
+
+        // 同じにされたら, 両方とも既定 (ctrl / shift) に戻るよ
+        let keymap = Keymap::new(&Keybindings { move_group: "alt".to_string(), resize_group: "Alt".to_string(), ..Default::default() });
+        assert_eq!(keymap.move_group, Modifiers { ctrl: true, ..Default::default() });
+        assert_eq!(keymap.resize_group, Modifiers { shift: true, ..Default::default() });
+    }
+}
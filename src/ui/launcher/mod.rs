@@ -0,0 +1,4 @@
+pub mod window;
+pub mod renderer;
+
+pub use window::LauncherWindow;
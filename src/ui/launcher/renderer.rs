@@ -0,0 +1,86 @@
+use std::rc::Rc;
+use windows::Win32::Foundation::HWND;
+use windows::Win32::Graphics::Direct2D::Common::D2D_RECT_F;
+use crate::graphics::{Canvas, GraphicsEngine, DrawingResources, layout, drawing::{background, label, painter}};
+use crate::ui::group::model::GroupModel;
+
+/// 画面下部の検索バーの高さだよ。
+pub const SEARCH_BAR_HEIGHT: f32 = 36.0;
+
+/// ランチャー (トレイから開くアイコン一覧) の描画を担当するよ！
+pub struct LauncherRenderer {
+    canvas: Canvas,
+    resources: DrawingResources,
+}
+
+impl LauncherRenderer {
+    pub fn new(
+        engine: Rc<GraphicsEngine>,
+        hwnd: HWND,
+        width: u32,
+        height: u32,
+    ) -> Result<Self, windows::core::Error> {
+        let canvas = Canvas::new(engine.clone(), hwnd, width, height)?;
+        let resources = DrawingResources::new(
+            engine.dwrite_factory.clone(),
+            engine.wic_factory.clone(),
+        );
+
+        let renderer = Self { canvas, resources };
+
+        unsafe {
+            renderer.canvas.d2d_context.SetTextAntialiasMode(windows::Win32::Graphics::Direct2D::D2D1_TEXT_ANTIALIAS_MODE_GRAYSCALE);
+        }
+
+        Ok(renderer)
+    }
+
+    /// アイコングリッドと検索バーを描画するよ。
+    /// グリッド部分はグループと同じ painter を使い回すんだ。
+    pub fn render(
+        &mut self,
+        model: &GroupModel,
+        query: &str,
+        width: f32,
+        height: f32,
+    ) -> Result<(), windows::core::Error> {
+        if !self.canvas.begin_draw() {
+            return Ok(()); // 描画先がないので, このフレームは飛ばすよ
+        }
+
+        let context = &self.canvas.d2d_context;
+        painter::draw_group(context, width, height, model, &mut self.resources)?;
+
+        // 検索バー
+        let bg_brush = self.resources.get_brush(context, &model.bg_color_hex)?;
+        let bg_color = unsafe { bg_brush.GetColor() };
+        let is_dark = layout::is_dark_color(bg_color.r, bg_color.g, bg_color.b);
+        let text_color_hex = if is_dark { "#FFFFFFFF" } else { "#000000FF" };
+        let bar_color_hex = if is_dark { "#FFFFFF22" } else { "#00000011" };
+
+        let bar_rect = D2D_RECT_F {
+            left: layout::PADDING,
+            top: height - SEARCH_BAR_HEIGHT,
+            right: width - layout::PADDING,
+            bottom: height - layout::PADDING,
+        };
+        let bar_brush = self.resources.get_brush(context, bar_color_hex)?;
+        let text_brush = self.resources.get_brush(context, text_color_hex)?;
+        background::draw_rounded_rect(context, &bar_rect, &bar_brush, None, 0.0, 6.0);
+
+        let settings = crate::settings::manager::get_settings_reader();
+        let format = self.resources.get_header_text_format(&settings.app.font_family, settings.app.font_size, settings.app.label_font_weight)?;
+        drop(settings);
+
+        let text = if query.is_empty() {
+            "Type to search...".to_string()
+        } else {
+            format!("{}_", query)
+        };
+        let text_rect = D2D_RECT_F { left: bar_rect.left + 8.0, right: bar_rect.right - 8.0, ..bar_rect };
+        label::draw_text(context, &text, &text_rect, &text_brush, &format);
+
+        self.canvas.end_draw(1)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,225 @@
+use std::rc::Rc;
+use windows::core::PCWSTR;
+use windows::Win32::Foundation::{HWND, RECT};
+use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+use windows::Win32::UI::WindowsAndMessaging::{
+    DestroyWindow, GetClientRect, PostMessageW, SetForegroundWindow, SetWindowLongPtrW,
+    GWLP_USERDATA, WS_EX_LAYERED, WS_EX_NOREDIRECTIONBITMAP, WS_EX_TOOLWINDOW, WS_EX_TOPMOST, WS_POPUP,
+    WS_VISIBLE,
+};
+use crate::graphics::{layout::{self, GroupLayout}, GraphicsEngine};
+use crate::settings::manager;
+use crate::ui::group::model::{GroupModel, IconState};
+use crate::ui::launcher::renderer::{LauncherRenderer, SEARCH_BAR_HEIGHT};
+use crate::win32::api;
+
+const LAUNCHER_WIDTH: i32 = 420;
+const LAUNCHER_MIN_HEIGHT: i32 = 120;
+const LAUNCHER_MAX_HEIGHT: i32 = 520;
+const LAUNCHER_ICON_SIZE: f32 = 32.0;
+
+const VK_BACK: u16 = 0x08;
+const VK_RETURN: u16 = 0x0D;
+const VK_ESCAPE: u16 = 0x1B;
+
+/// トレイの左クリックで開く, 全グループのアイコンを並べた小さなランチャーだよ！
+/// グループと違って前面に出てフォーカスも受け取るから, 文字を打って絞り込めるんだ。
+#[repr(C)]
+pub struct LauncherWindow {
+    pub window_type: crate::ui::WindowType,
+    pub hwnd: HWND,
+    pub renderer: LauncherRenderer,
+    pub model: GroupModel,      // 絞り込み後に表示しているアイコン
+    entries: Vec<IconState>,    // 全グループから集めたアイコン
+    query: String,
+    closing: bool,
+}
+
+impl LauncherWindow {
+    /// トレイアイコンの位置 (anchor) の近くにランチャーを開くよ。
+    pub fn create(
+        engine: Rc<GraphicsEngine>,
+        entries: Vec<IconState>,
+        anchor: (i32, i32),
+    ) -> Result<Box<Self>, windows::core::Error> {
+        let instance = unsafe { GetModuleHandleW(None)? };
+        let class_name = api::utils::to_wide("DesktopGroupingGroupClass");
+        let window_name = api::utils::to_wide("Desktop Grouping Launcher");
+        let class_pcwstr = PCWSTR::from_raw(class_name.as_ptr());
+        let window_pcwstr = PCWSTR::from_raw(window_name.as_ptr());
+
+        let mut model = GroupModel::new(
+            "LAUNCHER".to_string(),
+            String::new(), // タイトル行は出さないよ
+            "#202020".to_string(),
+            0.92,
+            LAUNCHER_ICON_SIZE,
+            Vec::new(),
+        );
+        model.icons = entries.clone();
+
+        // 中身に合わせて高さを決めて, 作業領域からはみ出さないように置くよ
+        let width = LAUNCHER_WIDTH;
+        let height = Self::fit_height(&model, width as f32);
+        let work = api::show_window::work_area_at(anchor.0, anchor.1);
+        let x = (anchor.0 - width / 2).clamp(work.left, (work.right - width).max(work.left));
+        let y = (anchor.1 - height).clamp(work.top, (work.bottom - height).max(work.top));
+
+        let options = api::create_window::WindowOptions {
+            x, y, width, height,
+            ex_style: Some(
+                WS_EX_LAYERED | WS_EX_TOOLWINDOW | WS_EX_TOPMOST | WS_EX_NOREDIRECTIONBITMAP
+            ),
+            style: Some(WS_POPUP | WS_VISIBLE),
+        };
+
+        let hwnd = api::create_window::create_window(
+            instance.into(),
+            class_pcwstr,
+            window_pcwstr,
+            options,
+        )?;
+
+        unsafe {
+            windows::Win32::UI::WindowsAndMessaging::SetLayeredWindowAttributes(
+                hwnd,
+                windows::Win32::Foundation::COLORREF(0),
+                255,
+                windows::Win32::UI::WindowsAndMessaging::LWA_ALPHA,
+            )?;
+        }
+
+        let renderer = LauncherRenderer::new(engine, hwnd, width as u32, height as u32)?;
+
+        let window = Box::new(Self {
+            window_type: crate::ui::WindowType::Launcher,
+            hwnd,
+            renderer,
+            model,
+            entries,
+            query: String::new(),
+            closing: false,
+        });
+
+        unsafe {
+            SetWindowLongPtrW(hwnd, GWLP_USERDATA, &*window as *const Self as isize);
+            // 文字入力を受け取るために前面に出すよ
+            let _ = SetForegroundWindow(hwnd);
+        }
+
+        Ok(window)
+    }
+
+    /// アイコンを全部並べたときに必要な高さを計算するよ。
+    fn fit_height(model: &GroupModel, width: f32) -> i32 {
+        let params = model.layout_params(&manager::get_settings_reader().app);
+        let group_layout = layout::calculate_group_layout(width, &model.sections(), &params);
+        let content_bottom = group_layout
+            .items
+            .iter()
+            .map(|item| item.hit_rect.bottom)
+            .fold(0.0f32, f32::max);
+        let height = content_bottom + layout::PADDING + SEARCH_BAR_HEIGHT;
+        (height.ceil() as i32).clamp(LAUNCHER_MIN_HEIGHT, LAUNCHER_MAX_HEIGHT)
+    }
+
+    fn client_size(&self) -> (f32, f32) {
+        let mut rect = RECT::default();
+        unsafe { let _ = GetClientRect(self.hwnd, &mut rect); }
+        ((rect.right - rect.left) as f32, (rect.bottom - rect.top) as f32)
+    }
+
+    fn current_layout(&self) -> GroupLayout {
+        let (width, _) = self.client_size();
+        let params = self.model.layout_params(&manager::get_settings_reader().app);
+        layout::calculate_group_layout(width, &self.model.sections(), &params)
+    }
+
+    pub fn draw(&mut self) -> Result<(), windows::core::Error> {
+        let (width, height) = self.client_size();
+        self.renderer.render(&self.model, &self.query, width, height)
+    }
+
+    pub fn handle_mouse_move(&mut self, x: i32, y: i32) -> Result<(), windows::core::Error> {
+        let hovered = self.current_layout().hit_item(x as f32, y as f32);
+        if hovered != self.model.hovered_index {
+            self.model.hovered_index = hovered;
+            self.draw()?;
+        }
+        Ok(())
+    }
+
+    pub fn handle_lbutton_up(&mut self, x: i32, y: i32) {
+        if let Some(index) = self.current_layout().hit_item(x as f32, y as f32) {
+            self.launch(index);
+        }
+    }
+
+    /// 文字入力で絞り込み, Enter で起動, Esc で閉じるよ。
+    pub fn handle_char(&mut self, code: u16) -> Result<(), windows::core::Error> {
+        match code {
+            VK_ESCAPE => self.close(),
+            VK_RETURN => {
+                if let Some(index) = self.model.hovered_index.or(if self.model.icons.is_empty() { None } else { Some(0) }) {
+                    self.launch(index);
+                }
+            }
+            VK_BACK => {
+                self.query.pop();
+                self.apply_filter();
+                self.draw()?;
+            }
+            _ => {
+                if let Some(c) = char::from_u32(code as u32).filter(|c| !c.is_control()) {
+                    self.query.push(c);
+                    self.apply_filter();
+                    self.draw()?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 他のウィンドウにフォーカスが移ったら閉じるよ。
+    pub fn handle_deactivate(&mut self) {
+        self.close();
+    }
+
+    fn apply_filter(&mut self) {
+        let query = self.query.to_lowercase();
+        self.model.icons = self
+            .entries
+            .iter()
+            .filter(|icon| query.is_empty() || icon.label().to_lowercase().contains(&query))
+            .cloned()
+            .collect();
+        self.model.hovered_index = None;
+    }
+
+    fn launch(&mut self, index: usize) {
+        if let Some(icon) = self.model.icons.get(index) {
+            log::info!("Launching from launcher: {:?}", icon.path);
+            if let Err(e) = api::shell::execute_path(&icon.launch_target()) {
+                log::error!("Failed to launch {:?}: {}", icon.path, e);
+            }
+        }
+        self.close();
+    }
+
+    /// ランチャーを閉じて, メッセージループの管理リストからも外してもらうよ。
+    pub fn close(&mut self) {
+        if self.closing {
+            return;
+        }
+        self.closing = true;
+        unsafe {
+            PostMessageW(
+                HWND(0), // スレッドメッセージとして送信
+                api::WM_REMOVE_WINDOW,
+                windows::Win32::Foundation::WPARAM(self.hwnd.0 as usize),
+                windows::Win32::Foundation::LPARAM(0),
+            ).ok();
+            DestroyWindow(self.hwnd).ok();
+        }
+    }
+}
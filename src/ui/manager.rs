@@ -0,0 +1,522 @@
+use std::path::PathBuf;
+use std::rc::Rc;
+use windows::Win32::Foundation::{HWND, POINT, RECT};
+use windows::Win32::UI::WindowsAndMessaging::{
+    DestroyWindow, GetTopWindow, GetWindow, GetWindowRect, SetWindowPos, GW_HWNDNEXT, HWND_BOTTOM, SWP_NOACTIVATE,
+    SWP_NOSIZE, SWP_NOZORDER,
+};
+use crate::graphics::GraphicsEngine;
+use crate::settings::models::{ChildSettings, DockEdge};
+use crate::ui::group::{snap, GroupWindow};
+use crate::ui::group::model::IconState;
+use crate::win32::api;
+
+/// auto_dock で並べたグループの ID と大きさ (幅, 高さ) だよ。
+type DockedGroups = Vec<(String, (i32, i32))>;
+
+/// 直前に削除したもの (元に戻すための控え) だよ。
+enum Deleted {
+    /// 削除したグループの ID と設定
+    Group { id: String, settings: ChildSettings },
+    /// アイコンを削除したグループの ID と, 削除したアイコン (元の位置と一緒に)
+    Icons { group_id: String, icons: Vec<(usize, IconState)> },
+}
+
+/// グループウィンドウをまとめて管理するよ！
+/// 設定ファイルのキー (グループ ID) ごとにウィンドウは必ず 1 つだけ, を守る係なんだ。
+#[derive(Default)]
+pub struct WindowManager {
+    // ウィンドウプロシージャが GWLP_USERDATA のポインタで GroupWindow を参照するから,
+    // Vec が伸びても動かないように Box に入れたままにしておくよ
+    #[allow(clippy::vec_box)]
+    windows: Vec<Box<GroupWindow>>,
+    /// 直前に削除したグループかアイコン。Ctrl + Shift + Z で1回だけ元に戻せるよ。
+    last_deleted: Option<Deleted>,
+    /// Ctrl + X で切り取ったアイコン (元のグループ ID とパス)。別のグループで Ctrl + V すると移動するよ。
+    pending_move: Option<(String, Vec<std::path::PathBuf>)>,
+    /// focus_follows_cursor が有効なときに, 最後にカーソルが乗ったグループの ID だよ。
+    focused_id: Option<String>,
+    /// ソロ表示中のグループの ID と, そのために隠したグループの ID だよ。もう一度切り替えると元に戻すね。
+    solo: Option<(String, Vec<String>)>,
+    /// ホットキーで全グループを隠している間は true だよ。
+    all_hidden: bool,
+    /// 最後にカーソルが乗ったグループの ID だよ。トレイの Export Group はこのグループを書き出すね。
+    last_hovered_id: Option<String>,
+    /// 最後に auto_dock で並べたときの端と, 並べたグループの ID と大きさだよ。変わったら並べ直すね。
+    dock_state: Option<(DockEdge, DockedGroups)>,
+}
+
+impl WindowManager {
+    /// グループ ID から, 既に開いているウィンドウを探すよ。
+    pub fn window_for(&self, id: &str) -> Option<&GroupWindow> {
+        self.windows.iter().find(|w| w.model.id == id).map(|w| &**w)
+    }
+
+    /// 同じ ID のウィンドウが既にあるかどうかだよ。
+    pub fn contains(&self, id: &str) -> bool {
+        self.window_for(id).is_some()
+    }
+
+    /// ウィンドウを管理下に加えるよ。
+    /// 同じ ID のウィンドウが既にあったら, 新しい方は閉じて既存のものを使い続けるね。
+    /// (2 つのウィンドウが同じ設定エントリを奪い合わないようにするためだよ)
+    pub fn insert(&mut self, window: Box<GroupWindow>) -> bool {
+        if self.contains(&window.model.id) {
+            log::warn!("Group {} is already open. Keeping the existing window.", window.model.id);
+            unsafe { DestroyWindow(window.hwnd).ok(); }
+            return false;
+        }
+        self.windows.push(window);
+        true
+    }
+
+    /// 設定からグループウィンドウを作って, 管理下に加えるよ。
+    pub fn open_group(&mut self, engine: &Rc<GraphicsEngine>, id: &str, child: &ChildSettings) -> bool {
+        if self.contains(id) {
+            log::warn!("Group {} is already open. Skipping restore.", id);
+            return false;
+        }
+        log::info!("Restoring group: {}", id);
+        match GroupWindow::create(
+            engine.clone(),
+            id.to_string(),
+            child.title.clone(),
+            child,
+        ) {
+            Ok(mut window) => {
+                unsafe {
+                    SetWindowPos(window.hwnd, HWND_BOTTOM, child.x, child.y, 0, 0, SWP_NOSIZE | window.position_flags()).ok();
+                }
+                let _ = window.draw();
+                self.insert(window)
+            }
+            Err(e) => {
+                log::error!("Failed to restore group {}: {}", id, e);
+                false
+            }
+        }
+    }
+
+    /// 破棄されたウィンドウを管理リストから外すよ。外したら true だよ。
+    /// 削除されたグループだったら, 元に戻せるように設定を取っておくね。
+    pub fn remove_by_hwnd(&mut self, hwnd: HWND) -> bool {
+        let Some(pos) = self.windows.iter().position(|w| w.hwnd == hwnd) else {
+            return false;
+        };
+        let mut window = self.windows.remove(pos);
+        if let Some(settings) = window.deleted_settings.take() {
+            self.last_deleted = Some(Deleted::Group { id: window.model.id.clone(), settings });
+        }
+        true
+    }
+
+    /// 各グループで削除されたアイコンを, 元に戻せるように預かるよ。
+    /// 後から削除したものだけを覚えておくから, メッセージを処理するたびに呼んでね。
+    pub fn collect_deleted_icons(&mut self) {
+        for window in &mut self.windows {
+            if !window.deleted_icons.is_empty() {
+                let icons = std::mem::take(&mut window.deleted_icons);
+                self.last_deleted = Some(Deleted::Icons { group_id: window.model.id.clone(), icons });
+            }
+        }
+    }
+
+    /// auto_dock が有効なら, グループが増えたり減ったり大きさが変わったりしたときに並べ直すよ。
+    /// 変わったかどうかを比べるだけなら軽いから, メッセージを処理するたびに呼んでね。
+    pub fn update_dock(&mut self) {
+        let Some(edge) = crate::settings::manager::get_settings_reader().app.auto_dock else {
+            self.dock_state = None;
+            return;
+        };
+        // ドラッグ中に周りが動くと落ち着かないから, 終わるまで待つよ
+        if self.windows.iter().any(|w| w.is_moving_or_resizing()) {
+            return;
+        }
+        let docked: DockedGroups = self
+            .windows
+            .iter()
+            .filter(|w| w.docked)
+            .filter_map(|w| {
+                let mut rect = RECT::default();
+                unsafe { GetWindowRect(w.hwnd, &mut rect) }.ok()?;
+                Some((w.model.id.clone(), (rect.right - rect.left, rect.bottom - rect.top)))
+            })
+            .collect();
+        if self.dock_state.as_ref().is_some_and(|(last_edge, last)| *last_edge == edge && *last == docked) {
+            return;
+        }
+        self.relayout_dock(edge, docked);
+    }
+
+    /// 並べる対象のグループを, メイン画面の作業領域の端に沿って順に置き直すよ。
+    /// 動かした位置は WM_MOVE で各グループが保存してくれるね。
+    fn relayout_dock(&mut self, edge: DockEdge, docked: DockedGroups) {
+        // (0, 0) は必ずメイン画面の左上だから, そこに一番近いモニターがメイン画面だよ
+        let work_area = api::show_window::work_area_at(0, 0);
+        let sizes: Vec<(i32, i32)> = docked.iter().map(|(_, size)| *size).collect();
+        let positions = snap::dock_positions(&sizes, &work_area, edge);
+        for ((id, _), (x, y)) in docked.iter().zip(positions) {
+            if let Some(window) = self.windows.iter().find(|w| w.model.id == *id) {
+                unsafe {
+                    let _ = SetWindowPos(window.hwnd, HWND(0), x, y, 0, 0, SWP_NOSIZE | SWP_NOZORDER | SWP_NOACTIVATE);
+                }
+            }
+        }
+        log::info!("Docked {} group(s) to the {:?} edge", docked.len(), edge);
+        self.dock_state = Some((edge, docked));
+    }
+
+    /// 手で動かして並びから外れたグループも含めて, 全部並べ直すよ。
+    pub fn redock_all(&mut self) {
+        if crate::settings::manager::get_settings_reader().app.auto_dock.is_none() {
+            log::info!("auto_dock is off. Nothing to dock.");
+            return;
+        }
+        for window in &mut self.windows {
+            window.docked = true;
+        }
+        // 次の update_dock で必ず並べ直すように, 前の状態は忘れるよ
+        self.dock_state = None;
+        self.update_dock();
+    }
+
+    /// 開いているグループが1つもないかどうかだよ。
+    pub fn is_empty(&self) -> bool {
+        self.windows.is_empty()
+    }
+
+    /// 直前に削除したグループかアイコンを元に戻すよ。戻せるのは1回だけだよ。
+    pub fn undo_last_delete(&mut self, engine: &Rc<GraphicsEngine>) {
+        match self.last_deleted.take() {
+            Some(Deleted::Group { id, settings }) => self.restore_group(engine, id, settings),
+            Some(Deleted::Icons { group_id, icons }) => {
+                let Some(window) = self.windows.iter_mut().find(|w| w.model.id == group_id) else {
+                    log::info!("Group {} is no longer open. Cannot restore its icons.", group_id);
+                    return;
+                };
+                if let Err(e) = window.restore_icons(icons) {
+                    log::error!("Failed to restore icons: {}", e);
+                }
+            }
+            None => log::info!("Nothing to restore."),
+        }
+    }
+
+    /// 削除したグループを設定に戻して, 開き直すよ。
+    fn restore_group(&mut self, engine: &Rc<GraphicsEngine>, id: String, child: ChildSettings) {
+        {
+            let mut settings = crate::settings::manager::get_settings_writer();
+            settings.children.insert(id.clone(), child.clone());
+            drop(settings);
+            crate::settings::manager::save();
+        }
+
+        self.open_group(engine, &id, &child);
+    }
+
+    /// 設定を読み直したあとに, 開いているグループを設定に合わせるよ。
+    /// 設定から消えたグループは閉じて, 新しく増えたものは開くね。
+    /// 残っているものも, どの設定が変わったか分からないから作り直すよ。
+    pub fn reload_groups(&mut self, engine: &Rc<GraphicsEngine>) {
+        let ordered = crate::settings::manager::get_settings_reader().children_front_to_back();
+        for window in self.windows.drain(..) {
+            if !ordered.iter().any(|(id, _)| *id == window.model.id) {
+                log::info!("Group {} is no longer in the config. Closing it.", window.model.id);
+            }
+            unsafe { DestroyWindow(window.hwnd).ok(); }
+        }
+        // 隠していたグループも作り直すと表示されるから, ソロ表示などは解除するよ
+        self.solo = None;
+        self.all_hidden = false;
+        self.focused_id = None;
+        for (id, child) in &ordered {
+            self.open_group(engine, id, child);
+        }
+        log::info!("Reloaded {} group(s) from the config.", self.windows.len());
+    }
+
+    /// 今の画面での重なり順を, 各グループの z_order として設定に書き込むよ (手前ほど大きいね)。
+    /// 変わったものがあるときだけ保存するよ。
+    pub fn record_z_order(&self) {
+        // 一番手前のウィンドウから順にたどって, 管理しているグループだけを拾うよ
+        let mut front_to_back: Vec<&str> = Vec::new();
+        let mut hwnd = unsafe { GetTopWindow(HWND(0)) };
+        while hwnd.0 != 0 {
+            if let Some(window) = self.windows.iter().find(|w| w.hwnd == hwnd) {
+                front_to_back.push(&window.model.id);
+            }
+            hwnd = unsafe { GetWindow(hwnd, GW_HWNDNEXT) };
+        }
+
+        let mut settings = crate::settings::manager::get_settings_writer();
+        let count = front_to_back.len() as u32;
+        let mut changed = false;
+        for (position, id) in front_to_back.into_iter().enumerate() {
+            if let Some(child) = settings.children.get_mut(id) {
+                let z_order = count - position as u32;
+                changed |= child.z_order != z_order;
+                child.z_order = z_order;
+            }
+        }
+        drop(settings);
+        if changed {
+            log::info!("Recorded the stacking order of {} group(s)", count);
+            crate::settings::manager::save();
+        }
+    }
+
+    /// スクリーン座標 pt の下にあるグループを返すよ。
+    pub fn window_at_point(&mut self, pt: POINT) -> Option<&mut GroupWindow> {
+        let index = self.index_at_point(pt)?;
+        Some(&mut *self.windows[index])
+    }
+
+    /// キーボード操作の対象にするグループを返すよ。
+    /// カーソルの下のグループを優先して, なければフォーカス中のグループだよ。
+    pub fn keyboard_target(&mut self, pt: POINT) -> Option<&mut GroupWindow> {
+        let index = self.keyboard_target_index(pt)?;
+        Some(&mut *self.windows[index])
+    }
+
+    fn keyboard_target_index(&self, pt: POINT) -> Option<usize> {
+        self.index_at_point(pt).or_else(|| {
+            let id = self.focused_id.as_ref()?;
+            self.windows.iter().position(|w| &w.model.id == id)
+        })
+    }
+
+    /// pt の下のグループだけを表示する「ソロ表示」を切り替えるよ。
+    /// ソロ表示中にもう一度呼ぶと, そのときに隠したグループだけを表示し直すね (元から隠れていたものはそのまま)。
+    /// 隠すのは画面からだけで, 設定ファイルのグループはそのまま残るよ。
+    pub fn toggle_solo(&mut self, pt: POINT) {
+        if let Some((solo_id, hidden)) = self.solo.take() {
+            log::info!("Leaving solo mode of group {} ({} group(s) shown again)", solo_id, hidden.len());
+            for window in self.windows.iter().filter(|w| hidden.contains(&w.model.id)) {
+                api::show_window::set_visible(window.hwnd, true);
+            }
+            return;
+        }
+
+        let Some(index) = self.keyboard_target_index(pt) else {
+            return;
+        };
+        let solo_id = self.windows[index].model.id.clone();
+        let hidden: Vec<String> = self
+            .windows
+            .iter()
+            .filter(|w| w.model.id != solo_id && api::show_window::is_visible(w.hwnd))
+            .map(|w| {
+                api::show_window::set_visible(w.hwnd, false);
+                w.model.id.clone()
+            })
+            .collect();
+        log::info!("Solo mode on group {} ({} group(s) hidden)", solo_id, hidden.len());
+        self.solo = Some((solo_id, hidden));
+    }
+
+    /// 全グループをまとめて隠したり, 表示し直したりするよ (スクリーンショットを撮るときなどに)。
+    /// 表示し直すときはソロ表示で隠したものも含めて全部出すから, ソロ表示も終わりだよ。
+    pub fn toggle_all_hidden(&mut self) {
+        self.all_hidden = !self.all_hidden;
+        log::info!("{} all {} group(s)", if self.all_hidden { "Hiding" } else { "Showing" }, self.windows.len());
+        if !self.all_hidden {
+            self.solo = None;
+        }
+        for window in &self.windows {
+            api::show_window::set_visible(window.hwnd, !self.all_hidden);
+        }
+    }
+
+    /// カーソルの下のグループにフォーカスを移すよ (enabled が false ならフォーカスを外すね)。
+    /// カーソルがグループから離れても, 最後に乗ったグループがフォーカスを持ち続けるよ。
+    pub fn update_focus(&mut self, pt: POINT, enabled: bool) {
+        let focused_id = if enabled {
+            self.index_at_point(pt)
+                .map(|index| self.windows[index].model.id.clone())
+                .or_else(|| self.focused_id.clone())
+        } else {
+            None
+        };
+        if focused_id == self.focused_id {
+            return;
+        }
+
+        for window in &mut self.windows {
+            let focused = focused_id.as_deref() == Some(window.model.id.as_str());
+            if window.model.focused != focused {
+                window.set_focused(focused);
+            }
+        }
+        self.focused_id = focused_id;
+    }
+
+    /// pt の下にグループがあれば, 最後にカーソルが乗ったグループとして覚えておくよ。
+    pub fn track_hover(&mut self, pt: POINT) {
+        if let Some(index) = self.index_at_point(pt) {
+            self.last_hovered_id = Some(self.windows[index].model.id.clone());
+        }
+    }
+
+    /// 最後にカーソルが乗ったグループだよ (もう閉じていたら None だよ)。
+    pub fn last_hovered(&self) -> Option<&GroupWindow> {
+        self.window_for(self.last_hovered_id.as_deref()?)
+    }
+
+    /// pt の下にある (表示中の) グループのインデックスだよ。
+    fn index_at_point(&self, pt: POINT) -> Option<usize> {
+        self.windows.iter().position(|w| {
+            let mut rect = RECT::default();
+            api::show_window::is_visible(w.hwnd)
+                && unsafe { GetWindowRect(w.hwnd, &mut rect).is_ok() }
+                && pt.x >= rect.left && pt.x <= rect.right && pt.y >= rect.top && pt.y <= rect.bottom
+        })
+    }
+
+    /// pt の下のグループで選択中のアイコンを「切り取り」状態にするよ。
+    /// 実際に動かすのは, 別のグループで move_pending_icons_to を呼んだときだよ。
+    pub fn cut_selection_at(&mut self, pt: POINT) {
+        let Some(index) = self.index_at_point(pt) else {
+            return;
+        };
+        let window = &self.windows[index];
+        let paths = window.selected_paths();
+        if !paths.is_empty() {
+            log::info!("Cut {} icon(s) from group {}", paths.len(), window.model.id);
+            self.pending_move = Some((window.model.id.clone(), paths));
+        }
+    }
+
+    /// 切り取ったアイコンを pt の下のグループへ移動するよ。
+    /// 移動するものがあって処理したときは true を返すよ (false なら通常の貼り付けに任せてね)。
+    pub fn move_pending_icons_to(&mut self, pt: POINT) -> bool {
+        if self.pending_move.is_none() {
+            return false;
+        }
+        let Some(target) = self.index_at_point(pt) else {
+            return false;
+        };
+        let Some((source_id, paths)) = self.pending_move.take() else {
+            return false;
+        };
+        let Some(source) = self.windows.iter().position(|w| w.model.id == source_id) else {
+            log::warn!("Source group {} is gone. Nothing to move.", source_id);
+            return false;
+        };
+        if source == target {
+            return true; // 同じグループに貼り付けたときは何もしないよ
+        }
+        // どちらかのアイコンがロックされていたら動かさないよ (切り取りは取り消しだね)
+        if self.windows[source].icons_locked() || self.windows[target].icons_locked() {
+            return true;
+        }
+
+        let icons = self.windows[source].remove_icons_by_paths(&paths);
+        log::info!("Moving {} icon(s) to group {}", icons.len(), self.windows[target].model.id);
+        if let Err(e) = self.windows[target].add_icons(icons) {
+            log::error!("Failed to add moved icons: {}", e);
+        }
+        true
+    }
+
+    /// どのモニターにも映っていないグループを, メイン画面の真ん中に集めるよ。集めた数を返すね。
+    /// 重ならないように少しずつずらして置くよ。動かした位置は WM_MOVE で各グループが保存してくれるね。
+    pub fn gather_offscreen(&mut self) -> usize {
+        // (0, 0) は必ずメイン画面の左上だから, そこに一番近いモニターがメイン画面だよ
+        let work = api::show_window::work_area_at(0, 0);
+        let center = ((work.left + work.right) / 2, (work.top + work.bottom) / 2);
+        let mut gathered = 0;
+        for window in &self.windows {
+            let mut rect = RECT::default();
+            if unsafe { GetWindowRect(window.hwnd, &mut rect) }.is_err() || api::show_window::is_on_screen(&rect) {
+                continue;
+            }
+            let (width, height) = (rect.right - rect.left, rect.bottom - rect.top);
+            let offset = gathered as i32 * 24;
+            let (x, y) = api::show_window::fit_in_work_area(
+                center.0 - width / 2 + offset,
+                center.1 - height / 2 + offset,
+                width,
+                height,
+            );
+            log::info!("Gathering off-screen group {} to ({}, {})", window.model.id, x, y);
+            unsafe {
+                let _ = SetWindowPos(window.hwnd, HWND(0), x, y, 0, 0, SWP_NOSIZE | SWP_NOZORDER | SWP_NOACTIVATE);
+            }
+            gathered += 1;
+        }
+        gathered
+    }
+
+    /// ID のグループのアイコンを, 順番はそのままで詰めて並べ直すよ。
+    pub fn compact_group(&mut self, id: &str, collapse_sections: bool) {
+        let Some(window) = self.windows.iter_mut().find(|w| w.model.id == id) else {
+            log::warn!("Group {} is not open. Nothing to compact.", id);
+            return;
+        };
+        if let Err(e) = window.compact(collapse_sections) {
+            log::error!("Failed to compact group {}: {}", id, e);
+        }
+    }
+
+    /// ID のグループの index 番目のアイコンに表示名を付けるよ (空ならファイル名に戻すね)。
+    pub fn rename_icon(&mut self, id: &str, index: usize, name: &str) {
+        let Some(window) = self.windows.iter_mut().find(|w| w.model.id == id) else {
+            log::warn!("Group {} is not open. Nothing to rename.", id);
+            return;
+        };
+        if let Err(e) = window.rename_icon(index, name) {
+            log::error!("Failed to rename icon {} of group {}: {}", index, id, e);
+        }
+    }
+
+    /// 2つ以上のグループに入っているアイコンを探すよ。
+    /// パスは正規化して比べて, 見つかったパスと入っているグループの名前の一覧を返すね。
+    pub fn find_duplicate_icons(&self) -> Vec<(PathBuf, Vec<String>)> {
+        let mut found: Vec<(String, PathBuf, Vec<String>)> = Vec::new();
+        for window in &self.windows {
+            for icon in &window.model.icons {
+                let key = icon.identity();
+                let name = window.display_name().to_string();
+                match found.iter_mut().find(|(k, _, _)| *k == key) {
+                    Some((_, _, groups)) => {
+                        if !groups.contains(&name) {
+                            groups.push(name);
+                        }
+                    }
+                    None => found.push((key, icon.path.clone(), vec![name])),
+                }
+            }
+        }
+        found
+            .into_iter()
+            .filter(|(_, _, groups)| groups.len() > 1)
+            .map(|(_, path, groups)| (path, groups))
+            .collect()
+    }
+
+    /// ID のグループの中で重複しているアイコンを取り除くよ。取り除いた数を返すね。
+    pub fn dedupe_group(&mut self, id: &str) -> usize {
+        let Some(window) = self.windows.iter_mut().find(|w| w.model.id == id) else {
+            log::warn!("Group {} is not open. Nothing to dedupe.", id);
+            return 0;
+        };
+        window.dedupe_icons().unwrap_or_else(|e| {
+            log::error!("Failed to dedupe group {}: {}", id, e);
+            0
+        })
+    }
+
+    /// すべてのグループのアイコンを取り直すよ。
+    pub fn refresh_all_icons(&mut self) {
+        log::info!("Refreshing icons of {} group(s)", self.windows.len());
+        for window in &mut self.windows {
+            if let Err(e) = window.refresh_icons() {
+                log::error!("Failed to refresh icons of group {}: {}", window.model.id, e);
+            }
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &GroupWindow> {
+        self.windows.iter().map(|w| &**w)
+    }
+}
@@ -1,9 +1,13 @@
 pub mod group;
 pub mod help;
+pub mod keymap;
+pub mod launcher;
+pub mod manager;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(u32)]
 pub enum WindowType {
     Group,
     Help,
+    Launcher,
 }
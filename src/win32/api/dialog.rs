@@ -0,0 +1,127 @@
+use std::cell::Cell;
+use std::path::{Path, PathBuf};
+use windows::core::PCWSTR;
+use windows::Win32::Foundation::{COLORREF, HWND};
+use windows::Win32::System::Com::{CoCreateInstance, CoTaskMemFree, CLSCTX_INPROC_SERVER};
+use windows::Win32::UI::Shell::{FileOpenDialog, FileSaveDialog, IFileOpenDialog, IFileSaveDialog, IShellItem, SIGDN_FILESYSPATH};
+use windows::Win32::System::Diagnostics::Debug::MessageBeep;
+use windows::Win32::UI::Controls::Dialogs::{ChooseColorW, CC_FULLOPEN, CC_RGBINIT, CHOOSECOLORW};
+use windows::Win32::UI::WindowsAndMessaging::{
+    MessageBoxW, IDYES, MB_ICONERROR, MB_ICONINFORMATION, MB_ICONQUESTION, MB_OK, MB_SETFOREGROUND, MB_YESNO,
+    MESSAGEBOX_RESULT, MESSAGEBOX_STYLE,
+};
+use crate::win32::api::utils::to_wide;
+
+/// ダイアログのタイトルに使うアプリ名だよ。
+const DIALOG_TITLE: &str = "Desktop Grouping";
+
+/// 情報メッセージを表示するよ！
+pub fn show_info(text: &str) {
+    show_message_box(text, MB_OK | MB_ICONINFORMATION);
+}
+
+/// エラーメッセージを表示するよ！
+pub fn show_error(text: &str) {
+    show_message_box(text, MB_OK | MB_ICONERROR);
+}
+
+/// 「はい / いいえ」で確認するよ。「はい」なら true だよ。
+pub fn confirm(text: &str) -> bool {
+    show_message_box(text, MB_YESNO | MB_ICONQUESTION) == IDYES
+}
+
+thread_local! {
+    /// 色の選択ダイアログの「作成した色」だよ。アプリを閉じるまでは覚えておくね。
+    static CUSTOM_COLORS: Cell<[COLORREF; 16]> = const { Cell::new([COLORREF(0x00FF_FFFF); 16]) };
+}
+
+/// Windows の色の選択ダイアログを開くよ！ initial (R, G, B) を選んだ状態で始まるね。
+/// 選んだ色を返すよ。キャンセルされたら None だよ。
+pub fn choose_color(owner: HWND, initial: (u8, u8, u8)) -> Option<(u8, u8, u8)> {
+    let (r, g, b) = initial;
+    let mut custom_colors = CUSTOM_COLORS.get();
+    let mut choose = CHOOSECOLORW {
+        lStructSize: std::mem::size_of::<CHOOSECOLORW>() as u32,
+        hwndOwner: owner,
+        // COLORREF は 0x00BBGGRR の並びだよ
+        rgbResult: COLORREF(r as u32 | (g as u32) << 8 | (b as u32) << 16),
+        lpCustColors: custom_colors.as_mut_ptr(),
+        Flags: CC_RGBINIT | CC_FULLOPEN,
+        ..Default::default()
+    };
+    let chosen = unsafe { ChooseColorW(&mut choose) }.as_bool();
+    CUSTOM_COLORS.set(custom_colors);
+    if !chosen {
+        return None;
+    }
+    let rgb = choose.rgbResult.0;
+    Some((rgb as u8, (rgb >> 8) as u8, (rgb >> 16) as u8))
+}
+
+/// ファイルを1つ選んでもらうよ。キャンセルされたり開けなかったりしたら None だよ。
+/// current があれば, そのファイル名を最初から入れておくね。
+pub fn pick_file(title: &str, current: Option<&Path>) -> Option<PathBuf> {
+    unsafe {
+        let dialog: IFileOpenDialog = CoCreateInstance(&FileOpenDialog, None, CLSCTX_INPROC_SERVER)
+            .map_err(|e| log::error!("Failed to create the file dialog: {}", e))
+            .ok()?;
+        let wide_title = to_wide(title);
+        dialog.SetTitle(PCWSTR::from_raw(wide_title.as_ptr())).ok();
+        if let Some(name) = current.and_then(|path| path.file_name()) {
+            let wide_name = to_wide(&name.to_string_lossy());
+            dialog.SetFileName(PCWSTR::from_raw(wide_name.as_ptr())).ok();
+        }
+        // キャンセルもエラーとして返ってくるから, ここは静かに諦めるよ
+        dialog.Show(None).ok()?;
+        item_path(&dialog.GetResult().ok()?)
+    }
+}
+
+/// 保存先のファイルを選んでもらうよ。キャンセルされたり開けなかったりしたら None だよ。
+/// file_name を最初から入れておいて, 拡張子が無ければ extension を付けるね。
+pub fn pick_save_file(title: &str, file_name: &str, extension: &str) -> Option<PathBuf> {
+    unsafe {
+        let dialog: IFileSaveDialog = CoCreateInstance(&FileSaveDialog, None, CLSCTX_INPROC_SERVER)
+            .map_err(|e| log::error!("Failed to create the save dialog: {}", e))
+            .ok()?;
+        let wide_title = to_wide(title);
+        dialog.SetTitle(PCWSTR::from_raw(wide_title.as_ptr())).ok();
+        let wide_name = to_wide(file_name);
+        dialog.SetFileName(PCWSTR::from_raw(wide_name.as_ptr())).ok();
+        let wide_extension = to_wide(extension);
+        dialog.SetDefaultExtension(PCWSTR::from_raw(wide_extension.as_ptr())).ok();
+        dialog.Show(None).ok()?;
+        item_path(&dialog.GetResult().ok()?)
+    }
+}
+
+/// ダイアログで選ばれたもののファイルパスだよ。
+fn item_path(item: &IShellItem) -> Option<PathBuf> {
+    unsafe {
+        let name = item.GetDisplayName(SIGDN_FILESYSPATH).ok()?;
+        let path = name.to_string().ok().map(PathBuf::from);
+        CoTaskMemFree(Some(name.0 as *const _));
+        path
+    }
+}
+
+/// システムの既定のサウンドを鳴らすよ (サウンドの設定で無音にされていたら鳴らないね)。
+pub fn play_default_sound() {
+    if let Err(e) = unsafe { MessageBeep(MB_OK) } {
+        log::warn!("Failed to play the default sound: {}", e);
+    }
+}
+
+/// MessageBoxW の薄いラッパーだよ。オーナーウィンドウは持たないよ。
+fn show_message_box(text: &str, style: MESSAGEBOX_STYLE) -> MESSAGEBOX_RESULT {
+    let wide_text = to_wide(text);
+    let wide_title = to_wide(DIALOG_TITLE);
+    unsafe {
+        MessageBoxW(
+            None,
+            PCWSTR::from_raw(wide_text.as_ptr()),
+            PCWSTR::from_raw(wide_title.as_ptr()),
+            style | MB_SETFOREGROUND,
+        )
+    }
+}
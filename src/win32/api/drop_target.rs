@@ -0,0 +1,150 @@
+use std::cell::Cell;
+use std::path::PathBuf;
+use windows::core::implement;
+use windows::Win32::Foundation::{HWND, LPARAM, POINT, POINTL, WPARAM};
+use windows::Win32::Graphics::Gdi::ScreenToClient;
+use windows::Win32::System::Com::{IDataObject, DVASPECT_CONTENT, FORMATETC, TYMED_HGLOBAL};
+use windows::Win32::System::Ole::{
+    IDropTarget, IDropTarget_Impl, RegisterDragDrop, ReleaseStgMedium, RevokeDragDrop,
+    CF_HDROP, DROPEFFECT, DROPEFFECT_COPY, DROPEFFECT_LINK, DROPEFFECT_NONE,
+};
+use windows::Win32::System::SystemServices::MODIFIERKEYS_FLAGS;
+use windows::Win32::UI::Shell::HDROP;
+use windows::Win32::UI::WindowsAndMessaging::SendMessageW;
+use super::{WM_DRAG_HOVER, WM_DROP_FILES};
+
+/// ドロップされたファイルと, 落とされた位置 (クライアント座標) だよ。
+/// WM_DROP_FILES の lparam にこれへのポインタを入れて渡すね。
+pub struct DroppedFiles {
+    pub paths: Vec<PathBuf>,
+    pub point: Option<(f32, f32)>,
+}
+
+/// ファイルのドラッグを受け取るターゲットだよ！
+/// WM_DROPFILES と違ってドラッグ中の出入りも分かるから, 上に来たことをウィンドウに知らせるんだ。
+#[implement(IDropTarget)]
+struct DropTarget {
+    hwnd: HWND,
+    effect: Cell<DROPEFFECT>, // ファイルのドラッグでなければ DROPEFFECT_NONE のままだよ
+}
+
+impl DropTarget {
+    fn set_hover(&self, hovering: bool) {
+        unsafe {
+            SendMessageW(self.hwnd, WM_DRAG_HOVER, WPARAM(hovering as usize), LPARAM(0));
+        }
+    }
+}
+
+/// ファイル一覧 (HDROP) を取り出すときの形式だよ。
+fn hdrop_format() -> FORMATETC {
+    FORMATETC {
+        cfFormat: CF_HDROP.0,
+        ptd: std::ptr::null_mut(),
+        dwAspect: DVASPECT_CONTENT.0,
+        lindex: -1,
+        tymed: TYMED_HGLOBAL.0 as u32,
+    }
+}
+
+/// 元のファイルはそのままで場所を覚えるだけだから, できればリンクとして受け取るよ。
+fn choose_effect(allowed: DROPEFFECT) -> DROPEFFECT {
+    if allowed.0 & DROPEFFECT_LINK.0 != 0 {
+        DROPEFFECT_LINK
+    } else if allowed.0 & DROPEFFECT_COPY.0 != 0 {
+        DROPEFFECT_COPY
+    } else {
+        DROPEFFECT_NONE
+    }
+}
+
+impl IDropTarget_Impl for DropTarget {
+    fn DragEnter(
+        &self,
+        pdataobj: Option<&IDataObject>,
+        _grfkeystate: MODIFIERKEYS_FLAGS,
+        _pt: &POINTL,
+        pdweffect: *mut DROPEFFECT,
+    ) -> windows::core::Result<()> {
+        let has_files = pdataobj.is_some_and(|data| unsafe { data.QueryGetData(&hdrop_format()) }.is_ok());
+        let effect = if has_files { unsafe { choose_effect(*pdweffect) } } else { DROPEFFECT_NONE };
+        self.effect.set(effect);
+        unsafe { *pdweffect = effect; }
+        if effect != DROPEFFECT_NONE {
+            self.set_hover(true);
+        }
+        Ok(())
+    }
+
+    fn DragOver(
+        &self,
+        _grfkeystate: MODIFIERKEYS_FLAGS,
+        _pt: &POINTL,
+        pdweffect: *mut DROPEFFECT,
+    ) -> windows::core::Result<()> {
+        unsafe { *pdweffect = self.effect.get(); }
+        Ok(())
+    }
+
+    fn DragLeave(&self) -> windows::core::Result<()> {
+        if self.effect.replace(DROPEFFECT_NONE) != DROPEFFECT_NONE {
+            self.set_hover(false);
+        }
+        Ok(())
+    }
+
+    fn Drop(
+        &self,
+        pdataobj: Option<&IDataObject>,
+        _grfkeystate: MODIFIERKEYS_FLAGS,
+        pt: &POINTL,
+        pdweffect: *mut DROPEFFECT,
+    ) -> windows::core::Result<()> {
+        let effect = self.effect.get();
+        self.DragLeave()?;
+        unsafe { *pdweffect = DROPEFFECT_NONE; }
+        let Some(data) = pdataobj.filter(|_| effect != DROPEFFECT_NONE) else {
+            return Ok(());
+        };
+
+        let paths = unsafe {
+            let mut medium = data.GetData(&hdrop_format())?;
+            let paths = super::utils::get_dropped_files(HDROP(medium.u.hGlobal.0 as isize));
+            ReleaseStgMedium(&mut medium);
+            paths
+        };
+
+        // 落とされた位置はスクリーン座標で届くから, ウィンドウの中の座標に直すよ
+        let mut point = POINT { x: pt.x, y: pt.y };
+        let point = unsafe { ScreenToClient(self.hwnd, &mut point) }
+            .as_bool()
+            .then_some((point.x as f32, point.y as f32));
+
+        let dropped = DroppedFiles { paths, point };
+        unsafe {
+            SendMessageW(self.hwnd, WM_DROP_FILES, WPARAM(0), LPARAM(&dropped as *const DroppedFiles as isize));
+            *pdweffect = effect;
+        }
+        Ok(())
+    }
+}
+
+/// ウィンドウをドラッグ&ドロップの受け取り先として登録するよ！
+/// 登録できなかったときは, WS_EX_ACCEPTFILES の WM_DROPFILES だけで受け取ることになるね。
+pub fn register(hwnd: HWND) {
+    let target: IDropTarget = DropTarget {
+        hwnd,
+        effect: Cell::new(DROPEFFECT_NONE),
+    }
+    .into();
+    if let Err(e) = unsafe { RegisterDragDrop(hwnd, &target) } {
+        log::warn!("Failed to register drop target: {}", e);
+    }
+}
+
+/// 登録を外すよ。ウィンドウが壊れる前に呼んでね。
+pub fn revoke(hwnd: HWND) {
+    unsafe {
+        let _ = RevokeDragDrop(hwnd);
+    }
+}
@@ -0,0 +1,41 @@
+use windows::Win32::Foundation::HWND;
+use windows::Win32::UI::Input::KeyboardAndMouse::{
+    RegisterHotKey, UnregisterHotKey, MOD_ALT, MOD_CONTROL, MOD_NOREPEAT, MOD_SHIFT,
+};
+use crate::ui::keymap::KeyCombo;
+
+/// 全グループの表示 / 非表示を切り替えるホットキーの ID だよ。
+pub const HOTKEY_TOGGLE_VISIBILITY: i32 = 1;
+
+/// どのアプリを使っていても反応するホットキーを登録するよ！
+/// ウィンドウではなくスレッドに登録するから, WM_HOTKEY はメッセージループに直接届くんだ。
+/// 他のアプリが同じキーを使っていると登録できなくて, そのときは false を返すよ。
+pub fn register(id: i32, combo: KeyCombo) -> bool {
+    let Some(key) = combo.key else {
+        return false;
+    };
+    let mut modifiers = MOD_NOREPEAT;
+    if combo.modifiers.ctrl {
+        modifiers |= MOD_CONTROL;
+    }
+    if combo.modifiers.shift {
+        modifiers |= MOD_SHIFT;
+    }
+    if combo.modifiers.alt {
+        modifiers |= MOD_ALT;
+    }
+    match unsafe { RegisterHotKey(HWND(0), id, modifiers, key as u32) } {
+        Ok(()) => true,
+        Err(e) => {
+            log::warn!("Failed to register hotkey {}: {}", id, e);
+            false
+        }
+    }
+}
+
+/// 登録したホットキーを外すよ。
+pub fn unregister(id: i32) {
+    unsafe {
+        let _ = UnregisterHotKey(HWND(0), id);
+    }
+}
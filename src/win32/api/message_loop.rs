@@ -3,21 +3,25 @@ use windows::core::PCWSTR;
 use windows::Win32::System::LibraryLoader::GetModuleHandleW;
 use windows::Win32::UI::WindowsAndMessaging::{
     DispatchMessageW, MsgWaitForMultipleObjectsEx, PeekMessageW, TranslateMessage, MSG, MWMO_INPUTAVAILABLE, PM_REMOVE, QS_ALLINPUT,
-    GetCursorPos, GetWindowRect,
+    GetCursorPos,
 };
-use windows::Win32::UI::Input::KeyboardAndMouse::{GetKeyState, GetAsyncKeyState, VK_CONTROL};
-use windows::Win32::Foundation::{POINT, RECT};
-use tray_icon::{TrayIconEvent, menu::MenuEvent};
+use windows::Win32::UI::Input::KeyboardAndMouse::GetAsyncKeyState;
+use windows::Win32::Foundation::POINT;
+use tray_icon::{MouseButton, MouseButtonState, TrayIcon, TrayIconEvent, menu::MenuEvent};
 use crate::ui::group::GroupWindow;
 use crate::ui::help::HelpWindow;
+use crate::ui::keymap::{KeyAction, Keymap, Modifiers};
+use crate::ui::launcher::LauncherWindow;
+use crate::ui::manager::WindowManager;
 use crate::graphics::GraphicsEngine;
-use crate::settings::{manager, models::ChildSettings};
+use crate::settings::{manager, models::ChildSettings, storage};
 use crate::ui::group::interaction::InteractionAction;
+use crate::ui::group::model::IconState;
 use crate::win32::vproc::window_proc;
 use crate::win32::api;
 
 /// ウィンドウメッセージとトレイイベントを処理し続けるループだよ！
-pub fn run_message_loop(engine: Rc<GraphicsEngine>) -> Result<(), windows::core::Error> {
+pub fn run_message_loop(engine: Rc<GraphicsEngine>, tray: &TrayIcon) -> Result<(), windows::core::Error> {
     unsafe {
         let mut msg = MSG::default();
         let tray_channel = TrayIconEvent::receiver();
@@ -36,110 +40,332 @@ pub fn run_message_loop(engine: Rc<GraphicsEngine>) -> Result<(), windows::core:
         )?;
 
         // 複数のグループウィンドウを管理する
-        let mut windows: Vec<Box<GroupWindow>> = Vec::new();
+        let mut windows = WindowManager::default();
         let mut help_window: Option<Box<HelpWindow>> = None;
+        let mut launcher: Option<Box<LauncherWindow>> = None;
+        // ランチャーが閉じた時刻 (トレイクリックで閉じた直後に開き直さないため)
+        let mut launcher_closed_at: Option<std::time::Instant> = None;
 
-        // キーの状態管理
-        let mut v_was_down = false;
+        // キーの状態管理 (割り当ては設定の keybindings から読むよ)
+        let mut keymap = Keymap::current();
+        let mut key_edges: Vec<KeyEdge> = keymap.keys().into_iter().map(KeyEdge::new).collect();
+        // 全グループの表示切り替えは, 他のアプリを使っているときも効くようにホットキーで受け取るよ
+        // (他のアプリが同じキーを使っていたら登録できないけど, 他の操作はそのまま使えるね)
+        api::hotkey::register(api::hotkey::HOTKEY_TOGGLE_VISIBILITY, keymap.toggle_all_groups);
+        // トレイのツールチップに「未保存」の印を出しているか
+        let mut shows_unsaved = false;
 
         // 起動時に設定から既存のグループを復元するよ
+        // 手前のものから順に一番奥へ置いていくから, 前回の重なり順のままになるね
         {
             let settings = manager::get_settings_reader();
-            for (id, child) in &settings.children {
-                log::info!("Restoring group: {}", id);
-                let icons = child.icons.iter().map(|i| i.path.clone()).collect();
-                match GroupWindow::create(
-                    engine.clone(),
-                    id.clone(),
-                    "Restored Group".to_string(),
-                    child.bg_color.clone(),
-                    child.opacity,
-                    child.icon_size,
-                    child.width,
-                    child.height,
-                    icons,
-                ) {
-                    Ok(mut window) => {
-                        windows::Win32::UI::WindowsAndMessaging::SetWindowPos(
-                            window.hwnd,
-                            windows::Win32::UI::WindowsAndMessaging::HWND_BOTTOM,
-                            child.x,
-                            child.y,
-                            0,
-                            0,
-                            windows::Win32::UI::WindowsAndMessaging::SWP_NOSIZE | windows::Win32::UI::WindowsAndMessaging::SWP_NOACTIVATE,
-                        ).ok();
-
-                        let _ = window.draw();
-                        windows.push(window);
-                    }
-                    Err(e) => log::error!("Failed to restore group {}: {}", id, e),
-                }
+            let children = settings.children.clone();
+            let ordered = settings.children_front_to_back();
+            drop(settings);
+            for (id, child) in &ordered {
+                windows.open_group(&engine, id, child);
             }
+            log_startup_summary(&windows, &children);
+            report_unopened_groups(&windows, &ordered);
         }
 
         loop {
             // 1. Win32 メッセージを全て処理する
             while PeekMessageW(&mut msg, None, 0, 0, PM_REMOVE).as_bool() {
                 if msg.message == windows::Win32::UI::WindowsAndMessaging::WM_QUIT {
+                    // 次に起動したときのために, 今の重なり順を覚えておくよ
+                    windows.record_z_order();
+                    api::hotkey::unregister(api::hotkey::HOTKEY_TOGGLE_VISIBILITY);
                     return Ok(());
                 }
 
+                // ホットキー: 全グループを隠す / 表示する
+                if msg.message == windows::Win32::UI::WindowsAndMessaging::WM_HOTKEY
+                    && msg.wParam.0 == api::hotkey::HOTKEY_TOGGLE_VISIBILITY as usize
+                {
+                    windows.toggle_all_hidden();
+                    continue;
+                }
+
                 // カスタムメッセージの処理: ウィンドウ削除通知
                 if msg.message == api::WM_REMOVE_WINDOW {
                     let target_hwnd = windows::Win32::Foundation::HWND(msg.wParam.0 as isize);
                     log::info!("Removing window from management list: {:?}", target_hwnd);
-                    windows.retain(|w| w.hwnd != target_hwnd);
+                    // 最後のグループが消えたら, 設定によってはアプリも終わるよ (既定ではトレイに残るね)
+                    if windows.remove_by_hwnd(target_hwnd)
+                        && windows.is_empty()
+                        && manager::get_settings_reader().app.exit_when_empty
+                    {
+                        log::info!("The last group was closed. Exiting (exit_when_empty).");
+                        windows::Win32::UI::WindowsAndMessaging::PostQuitMessage(0);
+                    }
                     if let Some(ref h) = help_window {
                         if h.hwnd == target_hwnd {
                             help_window = None;
                         }
                     }
+                    if launcher.as_ref().is_some_and(|l| l.hwnd == target_hwnd) {
+                        launcher = None;
+                        launcher_closed_at = Some(std::time::Instant::now());
+                    }
                 }
 
                 TranslateMessage(&msg);
                 DispatchMessageW(&msg);
             }
+            // アイコンが削除されていたら, 元に戻せるように預かっておくよ
+            windows.collect_deleted_icons();
+            // グループが増えたり減ったり大きさが変わったりしていたら, 端に並べ直すよ (auto_dock のときだけ)
+            windows.update_dock();
 
             // 2. トレイアイコンのイベントを処理する
             if let Ok(event) = tray_channel.try_recv() {
-                handle_tray_event(event);
+                handle_tray_event(event, &engine, &windows, &mut launcher, launcher_closed_at);
             }
 
             // 3. メニューのイベントを処理する
             if let Ok(event) = menu_channel.try_recv() {
                 handle_menu_event(event, &engine, &mut windows, &mut help_window);
+                // Reload Config で割り当てが作り直されていたら, 見張るキーとホットキーも入れ替えるよ
+                let current = Keymap::current();
+                if !std::sync::Arc::ptr_eq(&keymap, &current) {
+                    keymap = current;
+                    key_edges = keymap.keys().into_iter().map(KeyEdge::new).collect();
+                    api::hotkey::unregister(api::hotkey::HOTKEY_TOGGLE_VISIBILITY);
+                    api::hotkey::register(api::hotkey::HOTKEY_TOGGLE_VISIBILITY, keymap.toggle_all_groups);
+                }
             }
 
-            // 4. キー入力を監視
-            let ctrl_down = (GetKeyState(VK_CONTROL.0 as i32) as u16 & 0x8000) != 0;
-            let v_is_down = (GetAsyncKeyState(0x56) as u16 & 0x8000) != 0;
-
-            if ctrl_down && v_is_down && !v_was_down {
-                let mut pt = POINT::default();
-                if GetCursorPos(&mut pt).is_ok() {
-                    for window in &mut windows {
-                        let mut rect = RECT::default();
-                        if GetWindowRect(window.hwnd, &mut rect).is_ok() {
-                            if pt.x >= rect.left && pt.x <= rect.right && pt.y >= rect.top && pt.y <= rect.bottom {
-                                let _ = window.perform_action(InteractionAction::PasteColor);
-                                break;
-                            }
-                        }
-                    }
+            // 4. focus_follows_cursor が有効なら, カーソルの下のグループにフォーカスを移すよ
+            let mut cursor = POINT::default();
+            if GetCursorPos(&mut cursor).is_ok() {
+                let focus_follows_cursor = manager::get_settings_reader().app.focus_follows_cursor;
+                windows.update_focus(cursor, focus_follows_cursor);
+                windows.track_hover(cursor);
+            }
+
+            // 5. キー入力を監視 (押した瞬間を拾うために, 毎回すべてのキーの状態を更新するよ)
+            // タイトルの編集中は, 打った文字がショートカットにならないようにお休みするよ
+            let renaming = windows.iter().any(|w| w.is_renaming());
+            let modifiers = Modifiers::current();
+            let pressed: Vec<i32> = key_edges.iter_mut().filter_map(|k| k.pressed().then_some(k.vk)).collect();
+            if !renaming {
+                for action in pressed.into_iter().filter_map(|vk| keymap.action_for(modifiers, vk)) {
+                    handle_key_action(action, &engine, &mut windows);
+                }
+            }
+
+            // 6. 保存を待っている変更を (時間が来ていれば) 書き出して, 未保存の印を更新するよ
+            manager::flush_if_due();
+            let dirty = manager::is_dirty();
+            if dirty != shows_unsaved {
+                shows_unsaved = dirty;
+                let tooltip = if dirty { "Desktop Grouping \u{2022} (unsaved changes)" } else { "Desktop Grouping" };
+                if let Err(e) = tray.set_tooltip(Some(tooltip)) {
+                    log::warn!("Failed to update tray tooltip: {}", e);
                 }
             }
-            v_was_down = v_is_down;
 
             MsgWaitForMultipleObjectsEx(None, 10, QS_ALLINPUT, MWMO_INPUTAVAILABLE);
         }
     }
 }
 
-fn handle_tray_event(event: TrayIconEvent) {
+/// ショートカットキーの操作を, カーソルの下 (またはフォーカス中) のグループに対して行うよ。
+fn handle_key_action(action: KeyAction, engine: &Rc<GraphicsEngine>, windows: &mut WindowManager) {
+    let mut pt = POINT::default();
+    if unsafe { GetCursorPos(&mut pt) }.is_err() {
+        return;
+    }
+
+    let group_action = match action {
+        KeyAction::Paste => {
+            // 切り取ったアイコンがあれば, 貼り付けはそれの移動になるよ
+            if windows.move_pending_icons_to(pt) {
+                return;
+            }
+            InteractionAction::PasteColor
+        }
+        // 背景色の履歴を戻る / 進む
+        KeyAction::UndoColor => InteractionAction::StepColorHistory { forward: false },
+        KeyAction::RedoColor => InteractionAction::StepColorHistory { forward: true },
+        // 背景の塗り方を 単色 → グラデーション → 画像 の順に切り替える
+        KeyAction::CycleBackground => InteractionAction::CycleBackgroundMode,
+        // 大きさを size_presets の順に切り替える
+        KeyAction::CycleSizePreset => InteractionAction::CycleSizePreset,
+        KeyAction::Rename => InteractionAction::BeginRename,
+        KeyAction::ToggleTopmost => InteractionAction::ToggleTopmost,
+        KeyAction::ToggleCollapse => InteractionAction::ToggleCollapse,
+        KeyAction::ToggleLock => InteractionAction::ToggleLock,
+        KeyAction::FocusLeft => InteractionAction::MoveIconFocus { dx: -1, dy: 0 },
+        KeyAction::FocusRight => InteractionAction::MoveIconFocus { dx: 1, dy: 0 },
+        KeyAction::FocusUp => InteractionAction::MoveIconFocus { dx: 0, dy: -1 },
+        KeyAction::FocusDown => InteractionAction::MoveIconFocus { dx: 0, dy: 1 },
+        // 選択していなければ, 矢印キーでフォーカスしているアイコンを開くよ
+        KeyAction::OpenSelected => {
+            let Some(window) = windows.keyboard_target(pt) else { return };
+            let action = match window.model.focused_icon {
+                Some(index) if window.model.selected.is_empty() => InteractionAction::ExecuteIcon { index },
+                _ => InteractionAction::ExecuteSelected,
+            };
+            let _ = window.perform_action(action);
+            return;
+        }
+        KeyAction::OpenAll => InteractionAction::ExecuteAll,
+        // カーソルの下のアイコンを複製するよ
+        KeyAction::DuplicateIcon => {
+            let Some(window) = windows.keyboard_target(pt) else { return };
+            let Some(index) = window.model.hovered_index else { return };
+            let _ = window.perform_action(InteractionAction::DuplicateIcon { index });
+            return;
+        }
+        // 選択していなければ, カーソルの下のアイコンだけを削除するよ
+        KeyAction::DeleteIcon => {
+            let Some(window) = windows.keyboard_target(pt) else { return };
+            let action = match window.model.hovered_index {
+                Some(index) if window.model.selected.is_empty() => InteractionAction::DeleteIcon { index },
+                _ => InteractionAction::DeleteSelected,
+            };
+            let _ = window.perform_action(action);
+            return;
+        }
+        // 範囲選択したアイコンを切り取るよ (別のグループで貼り付けると移動)
+        KeyAction::Cut => {
+            windows.cut_selection_at(pt);
+            return;
+        }
+        // カーソルの下のグループだけを表示する (もう一度押すと元に戻す)
+        KeyAction::Solo => {
+            windows.toggle_solo(pt);
+            return;
+        }
+        // 直前に削除したグループかアイコンを元に戻す
+        // (他のアプリの「やり直し」を邪魔しないように, デスクトップ操作中だけ反応するよ)
+        KeyAction::RestoreGroup => {
+            if api::utils::is_desktop_foreground() {
+                windows.undo_last_delete(engine);
+            }
+            return;
+        }
+    };
+
+    if let Some(window) = windows.keyboard_target(pt) {
+        let _ = window.perform_action(group_action);
+    }
+}
+
+/// 開けなかったグループがあれば, まとめて1つのダイアログで知らせるよ。
+/// 設定には残っているから, 次に起動したときや設定を読み直したときにもう一度試せるね。
+fn report_unopened_groups(windows: &WindowManager, groups: &[(String, ChildSettings)]) {
+    let failed: Vec<String> = groups
+        .iter()
+        .filter(|(id, _)| !windows.contains(id))
+        .map(|(id, child)| if child.title.is_empty() { id.clone() } else { format!("{} ({})", child.title, id) })
+        .collect();
+    if failed.is_empty() {
+        return;
+    }
+    api::dialog::show_error(&format!(
+        "Failed to open {} group(s). The other groups are still running.\n\n{}\n\nThe groups are kept in the config and will be tried again next time. See the log for details.",
+        failed.len(),
+        failed.join("\n"),
+    ));
+}
+
+/// 起動時に何が読み込まれたかを, グループごとに1行ずつまとめてログに出すよ。
+/// 「グループが消えた」「位置がずれた」ときに, 原因をここだけ見て追えるようにするためなんだ。
+fn log_startup_summary(windows: &WindowManager, children: &std::collections::HashMap<String, ChildSettings>) {
+    let mut ids: Vec<&String> = children.keys().collect();
+    ids.sort();
+
+    let opened = ids.iter().filter(|id| windows.contains(id)).count();
+    log::info!("Startup summary: {} group(s) in settings, {} opened", children.len(), opened);
+
+    for id in ids {
+        let child = &children[id];
+        let Some(window) = windows.window_for(id) else {
+            log::info!("  {} \"{}\": NOT OPENED", id, child.title);
+            continue;
+        };
+
+        let center_x = child.x + child.width as i32 / 2;
+        let center_y = child.y + child.height as i32 / 2;
+        let (monitor, fell_back) = api::show_window::monitor_name_at(center_x, center_y);
+        let resolved = window.model.icons.iter().filter(|icon| icon.exists).count();
+        log::info!(
+            "  {} \"{}\": monitor {}{} at ({}, {}) {}x{}, icons {} ({} resolved, {} missing)",
+            id,
+            child.title,
+            monitor,
+            if fell_back { " (not on any monitor, fell back to primary)" } else { "" },
+            child.x,
+            child.y,
+            child.width,
+            child.height,
+            window.model.icons.len(),
+            resolved,
+            window.model.icons.len() - resolved,
+        );
+    }
+}
+
+/// キーが「押された瞬間」だけを拾うための小さな状態だよ。
+struct KeyEdge {
+    vk: i32,
+    was_down: bool,
+}
+
+impl KeyEdge {
+    fn new(vk: i32) -> Self {
+        Self { vk, was_down: false }
+    }
+
+    /// 前回は離されていて, 今は押されているときだけ true を返すよ。
+    fn pressed(&mut self) -> bool {
+        let is_down = unsafe { (GetAsyncKeyState(self.vk) as u16 & 0x8000) != 0 };
+        let pressed = is_down && !self.was_down;
+        self.was_down = is_down;
+        pressed
+    }
+}
+
+fn handle_tray_event(
+    event: TrayIconEvent,
+    engine: &Rc<GraphicsEngine>,
+    windows: &WindowManager,
+    launcher: &mut Option<Box<LauncherWindow>>,
+    launcher_closed_at: Option<std::time::Instant>,
+) {
     match event {
-        TrayIconEvent::Click { .. } => {
+        TrayIconEvent::Click { button: MouseButton::Left, button_state: MouseButtonState::Up, rect, .. } => {
             log::info!("Tray icon clicked!");
+
+            // 開いているなら閉じるよ。トレイをクリックした瞬間にフォーカスが外れて
+            // 閉じたばかりのときも, もう一度開かずにそのままにしておくね。
+            if let Some(l) = launcher.as_mut() {
+                l.close();
+                return;
+            }
+            if launcher_closed_at.is_some_and(|t| t.elapsed() < std::time::Duration::from_millis(300)) {
+                return;
+            }
+
+            let entries = windows
+                .iter()
+                .flat_map(|w| w.model.icons.iter())
+                .map(|icon| IconState { section: None, ..icon.clone() })
+                .collect();
+            let anchor = (
+                rect.position.x as i32 + rect.size.width as i32 / 2,
+                rect.position.y as i32,
+            );
+            match LauncherWindow::create(engine.clone(), entries, anchor) {
+                Ok(mut window) => {
+                    let _ = window.draw();
+                    *launcher = Some(window);
+                }
+                Err(e) => log::error!("Failed to create launcher window: {}", e),
+            }
         }
         _ => {}
     }
@@ -148,34 +374,43 @@ fn handle_tray_event(event: TrayIconEvent) {
 fn handle_menu_event(
     event: MenuEvent,
     engine: &Rc<GraphicsEngine>,
-    windows: &mut Vec<Box<GroupWindow>>,
+    windows: &mut WindowManager,
     help_window: &mut Option<Box<HelpWindow>>
 ) {
     match event.id.0.as_str() {
         "1001" => { // New Group
             let id = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_millis().to_string();
-            let title = "New Group".to_string();
-            let bg_color = "#000000".to_string();
-            let opacity = 0.5f32;
-            let icon_size = 48.0f32;
-            let width = 300u32;
-            let height = 200u32;
+            let title = String::new();
+            let mut child = ChildSettings {
+                x: 100, y: 100, width: 300, height: 200,
+                bg_color: "#000000".to_string(), opacity: 0.5,
+                ..Default::default()
+            };
+
+            // 今カーソルがあるところに作るよ (そのモニターからはみ出すなら内側にずらすね)
+            let mut cursor = POINT::default();
+            if unsafe { GetCursorPos(&mut cursor) }.is_ok() {
+                (child.x, child.y) = api::show_window::fit_in_work_area(cursor.x, cursor.y, child.width as i32, child.height as i32);
+            }
 
             {
                 let mut settings = manager::get_settings_writer();
-                settings.children.insert(id.clone(), ChildSettings {
-                    x: 100, y: 100, width, height, bg_color: bg_color.clone(), opacity, icon_size, ..Default::default()
-                });
+                child.icon_size = settings.app.icon_size;
+                child.z_order = settings.next_z_order();
+                settings.children.insert(id.clone(), child.clone());
                 drop(settings);
                 manager::save();
             }
 
-            match GroupWindow::create(engine.clone(), id, title, bg_color, opacity, icon_size, width, height, Vec::new()) {
+            match GroupWindow::create(engine.clone(), id, title, &child) {
                 Ok(mut window) => {
                     let _ = window.draw();
-                    windows.push(window);
+                    windows.insert(window);
+                }
+                Err(e) => {
+                    log::error!("Failed to create group window: {}", e);
+                    api::dialog::show_error(&format!("Failed to create a new group.\n\n{}", e));
                 }
-                Err(e) => log::error!("Failed to create group window: {}", e),
             }
         }
         "1003" => { // Help
@@ -191,13 +426,65 @@ fn handle_menu_event(
                 log::info!("Help window is already open.");
             }
         }
-        "1004" => { // Open Settings Location
-            if let Ok(path) = crate::settings::storage::get_config_path() {
-                if let Some(parent) = path.parent() {
-                    let _ = api::shell::open_file_location(parent);
+        "1014" => { // Settings
+            // 開いたファイルが今の状態になるように, 書き出していない変更は先に書き出すよ
+            windows.record_z_order();
+            manager::flush();
+            open_settings_file();
+        }
+        "1004" => { // Open Config Folder
+            open_config_folder();
+        }
+        "1005" => { // Refresh Icons
+            windows.refresh_all_icons();
+        }
+        "1006" => { // Tidy Groups
+            let ids: Vec<String> = windows.iter().map(|w| w.model.id.clone()).collect();
+            for id in ids {
+                windows.compact_group(&id, false);
+            }
+        }
+        "1012" => { // Dock Groups
+            windows.redock_all();
+        }
+        "1013" => { // Gather Groups
+            let gathered = windows.gather_offscreen();
+            if gathered == 0 {
+                api::dialog::show_info("All groups are already on screen.");
+            } else {
+                api::dialog::show_info(&format!("Moved {} off-screen group(s) to the primary monitor.", gathered));
+            }
+        }
+        "1007" => { // Find Duplicates
+            report_duplicate_icons(windows);
+        }
+        "1008" => { // Save Now
+            windows.record_z_order();
+            manager::flush();
+            // 書き出せなかったときは変更が残って, トレイも未保存の表示のままだよ
+            if !manager::is_dirty() {
+                log::info!("Settings saved (Save Now).");
+            }
+        }
+        "1009" => { // Reload Config
+            // 読めないファイルなら, 今の状態のままにしておくよ
+            match manager::reload() {
+                Ok(()) => {
+                    Keymap::reload();
+                    windows.reload_groups(engine);
+                    // ダイアログの間も設定を書けるように, 読み取りロックは先に外しておくよ
+                    let groups = manager::get_settings_reader().children_front_to_back();
+                    report_unopened_groups(windows, &groups);
                 }
+                Err(e) => log::error!("Failed to reload config: {}. Keeping the current settings.", e),
             }
         }
+        "1010" => { // Export Group
+            export_group(windows);
+        }
+        "1011" => { // Import Group
+            import_group(windows, engine);
+        }
         "1002" => { // Quit
             unsafe {
                 windows::Win32::UI::WindowsAndMessaging::PostQuitMessage(0);
@@ -206,3 +493,151 @@ fn handle_menu_event(
         _ => {}
     }
 }
+
+/// 複数のグループに入っているアイコンを報告して,
+/// グループの中での重複があれば確認してから取り除くよ。
+fn report_duplicate_icons(windows: &mut WindowManager) {
+    let duplicates = windows.find_duplicate_icons();
+    for (path, groups) in &duplicates {
+        log::info!("Duplicate icon {:?} in groups: {}", path, groups.join(", "));
+    }
+
+    let within: Vec<(String, usize)> = windows
+        .iter()
+        .map(|w| (w.model.id.clone(), w.model.duplicate_indices().len()))
+        .filter(|&(_, count)| count > 0)
+        .collect();
+    let within_total: usize = within.iter().map(|(_, count)| count).sum();
+
+    if duplicates.is_empty() && within_total == 0 {
+        api::dialog::show_info("No duplicate icons found.");
+        return;
+    }
+
+    let mut text = String::new();
+    if !duplicates.is_empty() {
+        text.push_str(&format!("{} icon(s) appear in more than one group:\n", duplicates.len()));
+        for (path, groups) in &duplicates {
+            let name = path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+            text.push_str(&format!("  {} ({})\n", name, groups.join(", ")));
+        }
+    }
+    if within_total == 0 {
+        api::dialog::show_info(&text);
+        return;
+    }
+
+    text.push_str(&format!("\n{} duplicate icon(s) found within the same group.\nRemove them?", within_total));
+    if api::dialog::confirm(&text) {
+        for (id, _) in within {
+            windows.dedupe_group(&id);
+        }
+    }
+}
+
+/// 最後にカーソルを乗せたグループを, 選んでもらったファイルに書き出すよ。
+fn export_group(windows: &WindowManager) {
+    let Some(window) = windows.last_hovered() else {
+        api::dialog::show_info("Hover over the group you want to export, then choose Export Group again.");
+        return;
+    };
+    let Some(child) = manager::get_settings_reader().children.get(&window.model.id).cloned() else {
+        return;
+    };
+
+    // ファイル名に使えない文字は _ にするよ
+    let name: String = child
+        .title
+        .chars()
+        .map(|c| if r#"\/:*?"<>|"#.contains(c) { '_' } else { c })
+        .collect();
+    let name = if name.trim().is_empty() { format!("group-{}", window.model.id) } else { name };
+    let Some(dest) = api::dialog::pick_save_file("Export Group", &format!("{}.toml", name), "toml") else {
+        return;
+    };
+    if let Err(e) = storage::export_group(&child, &dest) {
+        log::error!("Failed to export group {}: {}", window.model.id, e);
+        api::dialog::show_error(&format!("Failed to export the group.\n\n{}", e));
+    }
+}
+
+/// 書き出したグループのファイルを選んでもらって, 新しい ID のグループとして開くよ。
+/// この PC に無いアイコンもそのまま取り込むから, 後で付け直してね (リンク切れの印が出るよ)。
+fn import_group(windows: &mut WindowManager, engine: &Rc<GraphicsEngine>) {
+    let Some(src) = api::dialog::pick_file("Import Group", None) else {
+        return;
+    };
+    let mut child = match storage::import_group(&src) {
+        Ok(child) => child,
+        Err(e) => {
+            log::error!("Failed to import group from {:?}: {}", src, e);
+            api::dialog::show_error(&format!("Failed to import the group.\n\n{}", e));
+            return;
+        }
+    };
+
+    let id = crate::settings::generate_child_id();
+    {
+        let mut settings = manager::get_settings_writer();
+        child.z_order = settings.next_z_order();
+        settings.children.insert(id.clone(), child.clone());
+        drop(settings);
+        manager::save();
+    }
+    log::info!("Imported group {} from {:?}", id, src);
+    windows.open_group(engine, &id, &child);
+
+    let missing = child
+        .icons
+        .iter()
+        .filter(|info| info.url.is_none() && !crate::settings::env_path::expand(&info.path).exists())
+        .count();
+    if missing > 0 {
+        api::dialog::show_info(&format!("{} icon(s) in the imported group were not found on this PC.", missing));
+    }
+}
+
+/// 設定ファイル (config.toml) をエディタで開くよ (トレイの Settings)。
+/// 直したあとは, トレイの Reload Config で読み直してね。
+fn open_settings_file() {
+    let result = storage::get_config_path().map_err(|e| e.to_string()).and_then(|path| {
+        // まだ一度も保存していなければ, 今の設定でファイルを作っておくよ
+        if !path.exists() {
+            storage::save_settings(&manager::get_settings_reader())?;
+        }
+        api::shell::open_in_editor(&path).map_err(|e| e.to_string())?;
+        Ok(path)
+    });
+
+    match result {
+        Ok(path) => log::info!("Opened settings file: {:?}", path),
+        Err(e) => {
+            log::error!("Failed to open settings file: {}", e);
+            api::dialog::show_error(&format!("Failed to open the settings file.\n\n{}", e));
+        }
+    }
+}
+
+/// 設定フォルダ (config.toml のある場所) をエクスプローラーで開くよ！
+/// 開けなかったときは, 解決したパスをメッセージボックスで教えてあげるね。
+fn open_config_folder() {
+    let result = storage::get_config_path().and_then(|path| {
+        let dir = path.parent().map(|p| p.to_path_buf()).unwrap_or(path);
+        api::shell::open_folder(&dir).map_err(|e| std::io::Error::other(e.to_string()))?;
+        Ok(dir)
+    });
+
+    match result {
+        Ok(dir) => log::info!("Opened config folder: {:?}", dir),
+        Err(e) => {
+            let location = storage::resolve_settings_dir()
+                .map(|p| p.display().to_string())
+                .unwrap_or_else(|_| "(unknown)".to_string());
+            log::error!("Failed to open config folder ({}): {}", location, e);
+            api::dialog::show_error(&format!(
+                "Failed to open the config folder.\n\n{}\n\n{}",
+                location, e
+            ));
+        }
+    }
+}
@@ -4,8 +4,14 @@ pub mod register_class;
 pub mod show_window;
 pub mod utils;
 pub mod shell;
+pub mod dialog;
+pub mod hotkey;
+pub mod drop_target;
 
 pub const WM_REMOVE_WINDOW: u32 = windows::Win32::UI::WindowsAndMessaging::WM_APP + 1;
+pub const WM_ICONS_LOADED: u32 = windows::Win32::UI::WindowsAndMessaging::WM_APP + 2; // 別スレッドで取ったアイコンが届いたよ
+pub const WM_DRAG_HOVER: u32 = windows::Win32::UI::WindowsAndMessaging::WM_APP + 3; // ファイルのドラッグが上に来た (wparam = 1) / 離れた (0) よ
+pub const WM_DROP_FILES: u32 = windows::Win32::UI::WindowsAndMessaging::WM_APP + 4; // lparam の DroppedFiles が落とされたよ
 
 pub use create_window::*;
 pub use message_loop::*;
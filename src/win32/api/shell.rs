@@ -1,9 +1,20 @@
 use windows::core::{PCWSTR};
-use windows::Win32::UI::Shell::{SHGetFileInfoW, SHFILEINFOW, SHGFI_SYSICONINDEX, SHGetImageList, SHIL_EXTRALARGE, ShellExecuteW};
+use windows::Win32::Foundation::{HWND, LPARAM, SIZE, WPARAM};
+use windows::Win32::Graphics::Gdi::HBITMAP;
+use windows::Win32::UI::Shell::{
+    SHGetFileInfoW, SHFILEINFOW, SHGFI_SYSICONINDEX, SHGetImageList, SHIL_EXTRALARGE, ShellExecuteW,
+    IShellItemImageFactory, SHCreateItemFromParsingName, SIIGBF_BIGGERSIZEOK, SIIGBF_THUMBNAILONLY,
+};
 use windows::Win32::UI::Controls::IImageList;
 use windows::Win32::UI::WindowsAndMessaging::{HICON, SW_SHOWNORMAL};
 use crate::win32::api::utils::to_wide;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Sender;
+use std::time::SystemTime;
+
+const DRIVE_REMOTE: u32 = 4;
+const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
+const FILE_ATTRIBUTE_SYSTEM: u32 = 0x4;
 
 /// ファイルパスから 48x48 (SHIL_EXTRALARGE) のアイコン (HICON) を取得するよ！
 /// 取得した HICON は呼び出し側で DestroyIcon する必要があることに注意してね。
@@ -41,6 +52,99 @@ pub fn get_icon_for_path(path: &Path) -> Option<HICON> {
     None
 }
 
+/// UNC パス (\\server\share) やネットワークドライブ上のパスかどうかを調べるよ。
+/// こういう場所はアイコンの取得や存在確認が固まることがあるから, 特別扱いするんだ。
+pub fn is_network_path(path: &Path) -> bool {
+    use std::path::{Component, Prefix};
+
+    let Some(Component::Prefix(prefix)) = path.components().next() else {
+        return false;
+    };
+    match prefix.kind() {
+        Prefix::UNC(..) | Prefix::VerbatimUNC(..) => true,
+        Prefix::Disk(drive) | Prefix::VerbatimDisk(drive) => {
+            // ドライブ文字のときは, ネットワークドライブとして割り当てられているかを聞くよ
+            let root = to_wide(&format!("{}:\\", drive as char));
+            unsafe {
+                windows::Win32::Storage::FileSystem::GetDriveTypeW(PCWSTR::from_raw(root.as_ptr())) == DRIVE_REMOTE
+            }
+        }
+        _ => false,
+    }
+}
+
+/// 別スレッドで取ったアイコンだよ。HICON は受け取った側で DestroyIcon してね。
+pub struct LoadedIcon {
+    pub path: PathBuf,
+    pub exists: bool, // 取ったときにファイルがあったか (ネットワーク上のファイルの存在確認にも使うよ)
+    pub modified: Option<SystemTime>, // 取ったときのファイルの更新日時 (読めなければ None)
+    pub hicon: Option<HICON>, // 取れなかったら None
+}
+
+/// paths のアイコンを別スレッドで順番に取るよ。
+/// 1つ取れるたびに sender に送って, hwnd に WM_ICONS_LOADED を投げて知らせるね。
+/// 受け取る側がいなくなったら (グループを閉じたら), そこでやめるよ。
+pub fn load_icons_in_background(hwnd: HWND, paths: Vec<PathBuf>, sender: Sender<LoadedIcon>) {
+    std::thread::spawn(move || unsafe {
+        // SHGetFileInfoW を呼ぶスレッドでは COM を初期化しておく必要があるよ
+        let initialized = windows::Win32::System::Com::CoInitializeEx(
+            None,
+            windows::Win32::System::Com::COINIT_APARTMENTTHREADED,
+        );
+        if let Err(e) = &initialized {
+            log::warn!("Failed to initialize COM for the icon loader: {}", e);
+        }
+        for path in paths {
+            // あるかどうかと更新日時もこっちで調べておくよ (UI スレッドでファイルを見に行かないようにね)
+            // 応答しない共有フォルダでここが待たされても, UI は固まらないね
+            let exists = path.exists();
+            let modified = std::fs::metadata(&path).and_then(|m| m.modified()).ok();
+            let hicon = if exists { get_icon_for_path(&path) } else { None };
+            if exists && hicon.is_none() {
+                log::debug!("Failed to get the icon of {:?}", path);
+            }
+            if let Err(std::sync::mpsc::SendError(late)) = sender.send(LoadedIcon { path, exists, modified, hicon }) {
+                if let Some(hicon) = late.hicon {
+                    windows::Win32::UI::WindowsAndMessaging::DestroyIcon(hicon).ok();
+                }
+                break;
+            }
+            // ウィンドウがもう無ければ届かないだけだから, 失敗は気にしないよ
+            windows::Win32::UI::WindowsAndMessaging::PostMessageW(
+                hwnd, crate::win32::api::WM_ICONS_LOADED, WPARAM(0), LPARAM(0),
+            ).ok();
+        }
+        if initialized.is_ok() {
+            windows::Win32::System::Com::CoUninitialize();
+        }
+    });
+}
+
+/// サムネイルを出す価値のある (画像・動画) ファイルかどうかを拡張子で判定するよ。
+pub fn has_thumbnail(path: &Path) -> bool {
+    const EXTENSIONS: &[&str] = &[
+        "png", "jpg", "jpeg", "gif", "bmp", "webp", "heic", "tif", "tiff",
+        "mp4", "mov", "avi", "mkv", "wmv", "webm",
+    ];
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+}
+
+/// シェルのサムネイルプロバイダーから, 最大 size x size のサムネイル (HBITMAP) を取得するよ！
+/// サムネイルが作れないファイル (アイコンしかないもの) は None だよ。
+/// 取得した HBITMAP は呼び出し側で DeleteObject する必要があることに注意してね。
+pub fn get_thumbnail_for_path(path: &Path, size: i32) -> Option<HBITMAP> {
+    let wide_path = to_wide(&path.to_string_lossy());
+    unsafe {
+        let factory: IShellItemImageFactory =
+            SHCreateItemFromParsingName(PCWSTR::from_raw(wide_path.as_ptr()), None).ok()?;
+        factory
+            .GetImage(SIZE { cx: size, cy: size }, SIIGBF_THUMBNAILONLY | SIIGBF_BIGGERSIZEOK)
+            .ok()
+    }
+}
+
 /// 指定されたパスのファイルを実行 (開く) するよ！
 pub fn execute_path(path: &Path) -> Result<(), windows::core::Error> {
     let wide_path = to_wide(&path.to_string_lossy());
@@ -76,3 +180,95 @@ pub fn open_file_location(path: &Path) -> Result<(), windows::core::Error> {
     }
     Ok(())
 }
+
+/// 指定されたフォルダそのものをエクスプローラーで開くよ！
+/// ShellExecuteW の戻り値が 32 以下なら失敗扱いにするね。
+pub fn open_folder(path: &Path) -> Result<(), windows::core::Error> {
+    let wide_path = to_wide(&path.to_string_lossy());
+    let result = unsafe {
+        ShellExecuteW(
+            None,
+            windows::core::w!("explore"),
+            PCWSTR::from_raw(wide_path.as_ptr()),
+            None,
+            None,
+            SW_SHOWNORMAL,
+        )
+    };
+
+    if result.0 <= 32 {
+        return Err(windows::core::Error::from_win32());
+    }
+    Ok(())
+}
+
+/// ファイルを関連付けられたアプリで開くよ。関連付けが無いとき (.toml など) はメモ帳で開くね。
+pub fn open_in_editor(path: &Path) -> Result<(), windows::core::Error> {
+    let wide_path = to_wide(&path.to_string_lossy());
+    let result = unsafe {
+        ShellExecuteW(
+            None,
+            windows::core::w!("open"),
+            PCWSTR::from_raw(wide_path.as_ptr()),
+            None,
+            None,
+            SW_SHOWNORMAL,
+        )
+    };
+    if result.0 > 32 {
+        return Ok(());
+    }
+
+    let wide_arg = to_wide(&format!(r#""{}""#, path.to_string_lossy()));
+    let wide_notepad = to_wide("notepad.exe");
+    let result = unsafe {
+        ShellExecuteW(
+            None,
+            windows::core::w!("open"),
+            PCWSTR::from_raw(wide_notepad.as_ptr()),
+            PCWSTR::from_raw(wide_arg.as_ptr()),
+            None,
+            SW_SHOWNORMAL,
+        )
+    };
+
+    if result.0 <= 32 {
+        return Err(windows::core::Error::from_win32());
+    }
+    Ok(())
+}
+
+/// フォルダの直下にあるファイルとフォルダを, 名前順に最大 limit 個まで返すよ。
+/// 隠しファイルとシステムファイル (desktop.ini など) は飛ばすね。
+/// 2つ目の値は, limit を超えて切り捨てたものがあったかどうかだよ。
+pub fn list_folder(dir: &Path, limit: usize) -> std::io::Result<(Vec<PathBuf>, bool)> {
+    let mut entries: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .filter(is_visible_entry)
+        .map(|entry| entry.path())
+        .collect();
+    entries.sort_by_key(|path| path.file_name().map(|name| name.to_string_lossy().to_lowercase()));
+
+    let truncated = entries.len() > limit;
+    entries.truncate(limit);
+    Ok((entries, truncated))
+}
+
+/// フォルダの直下にあるファイルとフォルダの数を, limit 個まで数えるよ (list_folder と同じものを数えるね)。
+/// 並べ替えないし, limit で数えるのをやめるから, 大きなフォルダでも軽いよ。
+pub fn count_folder(dir: &Path, limit: usize) -> std::io::Result<usize> {
+    Ok(std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .filter(is_visible_entry)
+        .take(limit)
+        .count())
+}
+
+/// 隠しファイルとシステムファイルでなければ true だよ。
+fn is_visible_entry(entry: &std::fs::DirEntry) -> bool {
+    use std::os::windows::fs::MetadataExt;
+
+    entry
+        .metadata()
+        .is_ok_and(|meta| meta.file_attributes() & (FILE_ATTRIBUTE_HIDDEN | FILE_ATTRIBUTE_SYSTEM) == 0)
+}
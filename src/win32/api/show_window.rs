@@ -1,8 +1,13 @@
+use windows::core::{w, PCWSTR};
 use windows::Win32::{
-    Foundation::HWND,
-    Graphics::Gdi::UpdateWindow,
+    Foundation::{BOOL, COLORREF, FALSE, HWND, LPARAM, POINT, RECT, TRUE},
+    Graphics::Gdi::{
+        GetMonitorInfoW, MonitorFromPoint, MonitorFromRect, UpdateWindow, MONITORINFO, MONITORINFOEXW,
+        MONITOR_DEFAULTTONEAREST, MONITOR_DEFAULTTONULL, MONITOR_DEFAULTTOPRIMARY,
+    },
     UI::WindowsAndMessaging::{
-        SetWindowPos, ShowWindow, HWND_BOTTOM, SWP_NOACTIVATE, SWP_NOMOVE, SWP_NOSIZE, SW_SHOW,
+        EnumWindows, FindWindowExW, FindWindowW, IsWindowVisible, SetLayeredWindowAttributes, SetWindowLongPtrW, SetWindowPos, ShowWindow,
+        GWLP_HWNDPARENT, LWA_ALPHA, HWND_BOTTOM, HWND_NOTOPMOST, HWND_TOP, HWND_TOPMOST, SWP_NOACTIVATE, SWP_NOMOVE, SWP_NOSIZE, SW_HIDE, SW_SHOW, SW_SHOWNA,
     },
 };
 
@@ -14,6 +19,18 @@ pub fn show_window(hwnd: HWND) {
     }
 }
 
+/// ウィンドウを (フォーカスは奪わずに) 表示したり隠したりするよ！
+pub fn set_visible(hwnd: HWND, visible: bool) {
+    unsafe {
+        let _ = ShowWindow(hwnd, if visible { SW_SHOWNA } else { SW_HIDE });
+    }
+}
+
+/// ウィンドウが表示されているかどうかだよ。
+pub fn is_visible(hwnd: HWND) -> bool {
+    unsafe { IsWindowVisible(hwnd).as_bool() }
+}
+
 /// ウィンドウを最背面に移動させるよ！
 pub fn move_to_bottom(hwnd: HWND) {
     unsafe {
@@ -25,3 +42,139 @@ pub fn move_to_bottom(hwnd: HWND) {
         );
     }
 }
+
+/// ウィンドウを (フォーカスは奪わずに) 最前面に移動させるよ！
+pub fn move_to_top(hwnd: HWND) {
+    unsafe {
+        let _ = SetWindowPos(
+            hwnd,
+            HWND_TOP,
+            0, 0, 0, 0,
+            SWP_NOMOVE | SWP_NOSIZE | SWP_NOACTIVATE,
+        );
+    }
+}
+
+/// ウィンドウを常に最前面に置くか, 普通の層に戻すよ！
+pub fn set_topmost(hwnd: HWND, topmost: bool) {
+    unsafe {
+        let _ = SetWindowPos(
+            hwnd,
+            if topmost { HWND_TOPMOST } else { HWND_NOTOPMOST },
+            0, 0, 0, 0,
+            SWP_NOMOVE | SWP_NOSIZE | SWP_NOACTIVATE,
+        );
+    }
+}
+
+/// ウィンドウ全体 (背景もアイコンも文字も) の不透明度を変えるよ！ 0 で透明, 255 で不透明だよ。
+/// WS_EX_LAYERED のウィンドウにしか効かないから気をつけてね。
+pub fn set_window_opacity(hwnd: HWND, alpha: u8) {
+    unsafe {
+        let _ = SetLayeredWindowAttributes(hwnd, COLORREF(0), alpha, LWA_ALPHA);
+    }
+}
+
+/// 指定した点に一番近いモニターの作業領域 (タスクバーを除いた領域) を返すよ！
+pub fn work_area_at(x: i32, y: i32) -> RECT {
+    unsafe {
+        let monitor = MonitorFromPoint(POINT { x, y }, MONITOR_DEFAULTTONEAREST);
+        let mut info = MONITORINFO {
+            cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+            ..Default::default()
+        };
+        if GetMonitorInfoW(monitor, &mut info).as_bool() {
+            info.rcWork
+        } else {
+            RECT { left: 0, top: 0, right: 1920, bottom: 1080 }
+        }
+    }
+}
+
+/// 左上を (x, y) に置いた width x height のウィンドウが, その点に一番近いモニターの作業領域から
+/// はみ出さないようにずらした左上の位置を返すよ！作業領域より大きいときは左上をそろえるね。
+pub fn fit_in_work_area(x: i32, y: i32, width: i32, height: i32) -> (i32, i32) {
+    let work = work_area_at(x, y);
+    (
+        x.min(work.right - width).max(work.left),
+        y.min(work.bottom - height).max(work.top),
+    )
+}
+
+/// 矩形が少しでもどれかのモニターに重なっているかどうかだよ！
+/// モニターを外したりしてどこにも映らなくなったウィンドウは false になるね。
+pub fn is_on_screen(rect: &RECT) -> bool {
+    unsafe { !MonitorFromRect(rect, MONITOR_DEFAULTTONULL).is_invalid() }
+}
+
+/// 点 (x, y) を含むモニターのデバイス名 (\\.\DISPLAY1 など) を返すよ！
+/// どのモニターにも入っていなければ, プライマリモニターの名前と一緒に true (代わりに使った) を返すね。
+pub fn monitor_name_at(x: i32, y: i32) -> (String, bool) {
+    unsafe {
+        let mut monitor = MonitorFromPoint(POINT { x, y }, MONITOR_DEFAULTTONULL);
+        let fell_back = monitor.is_invalid();
+        if fell_back {
+            monitor = MonitorFromPoint(POINT { x, y }, MONITOR_DEFAULTTOPRIMARY);
+        }
+        let mut info = MONITORINFOEXW {
+            monitorInfo: MONITORINFO {
+                cbSize: std::mem::size_of::<MONITORINFOEXW>() as u32,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let name = if GetMonitorInfoW(monitor, &mut info as *mut MONITORINFOEXW as *mut MONITORINFO).as_bool() {
+            let len = info.szDevice.iter().position(|&c| c == 0).unwrap_or(info.szDevice.len());
+            String::from_utf16_lossy(&info.szDevice[..len])
+        } else {
+            "unknown".to_string()
+        };
+        (name, fell_back)
+    }
+}
+
+/// デスクトップアイコンを表示しているウィンドウ (SHELLDLL_DefView を持つ Progman か WorkerW) を探すよ！
+pub fn find_desktop_host() -> Option<HWND> {
+    unsafe {
+        let progman = FindWindowW(w!("Progman"), PCWSTR::null());
+        if progman.0 != 0 && FindWindowExW(progman, HWND(0), w!("SHELLDLL_DefView"), PCWSTR::null()).0 != 0 {
+            return Some(progman);
+        }
+
+        // 壁紙の切り替えなどで, アイコンが WorkerW 側に移っていることがあるよ
+        let mut found = HWND(0);
+        let _ = EnumWindows(Some(find_defview_host), LPARAM(&mut found as *mut HWND as isize));
+        (found.0 != 0).then_some(found)
+    }
+}
+
+unsafe extern "system" fn find_defview_host(hwnd: HWND, lparam: LPARAM) -> BOOL {
+    unsafe {
+        if FindWindowExW(hwnd, HWND(0), w!("SHELLDLL_DefView"), PCWSTR::null()).0 != 0 {
+            *(lparam.0 as *mut HWND) = hwnd;
+            return FALSE;
+        }
+    }
+    TRUE
+}
+
+/// ウィンドウの所有者をデスクトップにして, デスクトップの層に固定するよ！
+/// 所有されたウィンドウは所有者より必ず手前に来るから, アイコンより上・通常のウィンドウより下に落ち着くんだ。
+/// デスクトップが見つからなかったときは false を返すよ。
+pub fn pin_to_desktop(hwnd: HWND) -> bool {
+    let Some(host) = find_desktop_host() else {
+        return false;
+    };
+    unsafe {
+        SetWindowLongPtrW(hwnd, GWLP_HWNDPARENT, host.0);
+    }
+    move_to_bottom(hwnd);
+    true
+}
+
+/// pin_to_desktop で付けた所有者を外すよ。所有者がいると最前面には出られないからね。
+pub fn unpin_from_desktop(hwnd: HWND) {
+    unsafe {
+        SetWindowLongPtrW(hwnd, GWLP_HWNDPARENT, 0);
+    }
+}
@@ -1,11 +1,13 @@
 use std::ffi::OsStr;
 use std::os::windows::ffi::OsStrExt;
 use std::path::PathBuf;
-use windows::Win32::Foundation::HGLOBAL;
-use windows::Win32::System::DataExchange::{OpenClipboard, CloseClipboard, GetClipboardData};
-use windows::Win32::System::Memory::{GlobalLock, GlobalUnlock};
+use windows::Win32::Foundation::{GlobalFree, HANDLE, HGLOBAL};
+use windows::Win32::System::DataExchange::{OpenClipboard, CloseClipboard, EmptyClipboard, GetClipboardData, SetClipboardData};
+use windows::Win32::System::Memory::{GlobalAlloc, GlobalLock, GlobalUnlock, GMEM_MOVEABLE};
 use windows::Win32::System::Ole::CF_UNICODETEXT;
-use windows::Win32::UI::Shell::{DragQueryFileW, HDROP};
+use windows::Win32::Foundation::POINT;
+use windows::Win32::UI::Shell::{DragQueryFileW, DragQueryPoint, HDROP};
+use windows::Win32::UI::WindowsAndMessaging::{GetClassNameW, GetForegroundWindow};
 
 /// Rust の文字列を Windows API 用の null 終端ワイド文字列 (Vec<u16>) に変換するよ！
 pub fn to_wide(s: &str) -> Vec<u16> {
@@ -44,6 +46,34 @@ pub fn get_clipboard_text() -> Option<String> {
     }
 }
 
+/// クリップボードにテキストを入れるよ！ 入れられたら true だよ。
+pub fn set_clipboard_text(text: &str) -> bool {
+    let wide = to_wide(text);
+    unsafe {
+        if OpenClipboard(None).is_err() {
+            return false;
+        }
+
+        let mut done = false;
+        if let Ok(hglobal) = GlobalAlloc(GMEM_MOVEABLE, wide.len() * std::mem::size_of::<u16>()) {
+            let ptr = GlobalLock(hglobal);
+            if !ptr.is_null() {
+                std::ptr::copy_nonoverlapping(wide.as_ptr(), ptr as *mut u16, wide.len());
+                let _ = GlobalUnlock(hglobal);
+                // 渡せたらメモリの持ち主はクリップボードになるよ。失敗したときだけ自分で解放するね
+                done = EmptyClipboard().is_ok()
+                    && SetClipboardData(CF_UNICODETEXT.0 as u32, HANDLE(hglobal.0 as isize)).is_ok();
+            }
+            if !done {
+                let _ = GlobalFree(hglobal);
+            }
+        }
+
+        let _ = CloseClipboard();
+        done
+    }
+}
+
 /// HDROP ハンドルからファイルパスのリストを取得するよ！
 pub fn get_dropped_files(hdrop: HDROP) -> Vec<PathBuf> {
     unsafe {
@@ -62,3 +92,33 @@ pub fn get_dropped_files(hdrop: HDROP) -> Vec<PathBuf> {
         files
     }
 }
+
+/// HDROP ハンドルからドロップされた位置 (クライアント座標) を取得するよ！
+pub fn get_drop_point(hdrop: HDROP) -> Option<(f32, f32)> {
+    let mut pt = POINT::default();
+    unsafe {
+        if DragQueryPoint(hdrop, &mut pt).as_bool() {
+            Some((pt.x as f32, pt.y as f32))
+        } else {
+            None
+        }
+    }
+}
+
+/// 今アクティブなのがデスクトップ (またはどのウィンドウもアクティブでない状態) かどうかを調べるよ。
+/// グループはアクティブにならないから, デスクトップ操作中かどうかはこれで判断するんだ。
+pub fn is_desktop_foreground() -> bool {
+    unsafe {
+        let hwnd = GetForegroundWindow();
+        if hwnd.0 == 0 {
+            return true;
+        }
+        let mut buf = [0u16; 64];
+        let len = GetClassNameW(hwnd, &mut buf);
+        if len <= 0 {
+            return false;
+        }
+        let class_name = String::from_utf16_lossy(&buf[..len as usize]);
+        class_name == "Progman" || class_name == "WorkerW"
+    }
+}
@@ -1,19 +1,21 @@
 use windows::Win32::{
     Foundation::{HWND, LPARAM, LRESULT, WPARAM},
     UI::WindowsAndMessaging::{
-        DefWindowProcW, WM_DESTROY, WM_PAINT, WM_SIZE, WM_ERASEBKGND,
-        WM_LBUTTONDOWN, WM_MOUSEMOVE, WM_LBUTTONUP, WM_NCHITTEST, HTCLIENT,
+        DefWindowProcW, WM_CLOSE, WM_DESTROY, WM_PAINT, WM_SIZE, WM_ERASEBKGND,
+        WM_LBUTTONDOWN, WM_MOUSEMOVE, WM_LBUTTONUP, WM_MOUSEWHEEL, WM_NCHITTEST, HTCLIENT,
         WM_KEYDOWN, WM_DROPFILES, WM_LBUTTONDBLCLK, WM_RBUTTONDOWN, WM_RBUTTONUP,
         WM_CONTEXTMENU,
         WM_WINDOWPOSCHANGING, WM_MOUSEACTIVATE, MA_NOACTIVATE, WINDOWPOS, HWND_BOTTOM,
-        WM_TIMER,
+        WM_TIMER, WM_CHAR, WM_ACTIVATE, WA_INACTIVE, WM_MOVE,
         GetWindowLongPtrW, GWLP_USERDATA,
     },
     Graphics::Gdi::{BeginPaint, EndPaint, PAINTSTRUCT},
 };
 use windows::Win32::UI::Shell::{HDROP, DragFinish};
+use windows::Win32::UI::Controls::WM_MOUSELEAVE;
 use crate::ui::group::window::GroupWindow;
 use crate::ui::help::window::HelpWindow;
+use crate::ui::launcher::window::LauncherWindow;
 use crate::ui::WindowType;
 use crate::win32::api;
 
@@ -43,6 +45,10 @@ pub unsafe extern "system" fn window_proc(
                 let window = &mut *(ptr as *mut HelpWindow);
                 handle_help_msg(window, hwnd, msg, wparam, lparam)
             }
+            WindowType::Launcher => {
+                let window = &mut *(ptr as *mut LauncherWindow);
+                handle_launcher_msg(window, hwnd, msg, wparam, lparam)
+            }
         }
     }
 }
@@ -59,8 +65,11 @@ unsafe fn handle_group_msg(
             return LRESULT(HTCLIENT as isize);
         }
         WM_WINDOWPOSCHANGING => {
-            let window_pos = &mut *(lparam.0 as *mut WINDOWPOS);
-            window_pos.hwndInsertAfter = HWND_BOTTOM;
+            // 一時的に最前面に出しているとき (と keep_on_desktop が false のとき) 以外は, 常に最背面に置くよ
+            if window.stays_at_bottom() {
+                let window_pos = &mut *(lparam.0 as *mut WINDOWPOS);
+                window_pos.hwndInsertAfter = HWND_BOTTOM;
+            }
             return LRESULT(0);
         }
         WM_MOUSEACTIVATE => {
@@ -89,8 +98,14 @@ unsafe fn handle_group_msg(
             }
             return LRESULT(0);
         }
+        WM_MOVE => {
+            window.handle_moved();
+            return LRESULT(0);
+        }
         WM_LBUTTONDOWN => {
-            window.handle_lbutton_down();
+            if let Err(e) = window.handle_lbutton_down() {
+                log::error!("Left button down error: {}", e);
+            }
             return LRESULT(0);
         }
         WM_LBUTTONDBLCLK => {
@@ -120,10 +135,24 @@ unsafe fn handle_group_msg(
             }
             return LRESULT(0);
         }
+        WM_MOUSELEAVE => {
+            if let Err(e) = window.handle_mouse_leave() {
+                log::error!("Mouse leave error: {}", e);
+            }
+            return LRESULT(0);
+        }
         WM_LBUTTONUP => {
             window.handle_lbutton_up();
             return LRESULT(0);
         }
+        WM_MOUSEWHEEL => {
+            // 回した量は wparam の上位ワードに入っているよ (1目盛りで 120)
+            let delta = (wparam.0 >> 16) as u16 as i16;
+            if let Err(e) = window.handle_mouse_wheel(delta) {
+                log::error!("Mouse wheel error: {}", e);
+            }
+            return LRESULT(0);
+        }
         WM_KEYDOWN => {
             let vk = wparam.0 as u16;
             if let Err(e) = window.handle_keydown(vk) {
@@ -131,19 +160,60 @@ unsafe fn handle_group_msg(
             }
             return LRESULT(0);
         }
+        WM_CHAR => {
+            // タイトルの編集中だけ届くよ
+            if let Err(e) = window.handle_char(wparam.0 as u16) {
+                log::error!("Char error: {}", e);
+            }
+            return LRESULT(0);
+        }
+        WM_ACTIVATE => {
+            if (wparam.0 & 0xFFFF) as u32 == WA_INACTIVE && let Err(e) = window.handle_deactivate() {
+                log::error!("Deactivate error: {}", e);
+            }
+            return LRESULT(0);
+        }
         WM_DROPFILES => {
             let hdrop = HDROP(wparam.0 as isize);
             let files = api::utils::get_dropped_files(hdrop);
-            if let Err(e) = window.handle_drop_files(files) {
+            let drop_point = api::utils::get_drop_point(hdrop);
+            if let Err(e) = window.handle_drop_files(files, drop_point) {
                 log::error!("Drop files error: {}", e);
             }
             DragFinish(hdrop);
             return LRESULT(0);
         }
+        api::WM_DROP_FILES => {
+            let dropped = &*(lparam.0 as *const api::drop_target::DroppedFiles);
+            if let Err(e) = window.handle_drop_files(dropped.paths.clone(), dropped.point) {
+                log::error!("Drop files error: {}", e);
+            }
+            return LRESULT(0);
+        }
+        api::WM_DRAG_HOVER => {
+            if let Err(e) = window.handle_drag_hover(wparam.0 != 0) {
+                log::error!("Drag hover error: {}", e);
+            }
+            return LRESULT(0);
+        }
+        api::WM_ICONS_LOADED => {
+            if let Err(e) = window.handle_icons_loaded() {
+                log::error!("Icons loaded error: {}", e);
+            }
+            return LRESULT(0);
+        }
         WM_ERASEBKGND => {
             return LRESULT(1);
         }
+        WM_CLOSE => {
+            // そのまま閉じると設定や管理リストに残ってしまうから, 確認してグループの削除として扱うよ
+            if let Err(e) = window.handle_close() {
+                log::error!("Close error: {}", e);
+            }
+            return LRESULT(0);
+        }
         WM_DESTROY => {
+            api::drop_target::revoke(hwnd);
             return LRESULT(0);
         }
         _ => {}
@@ -219,3 +289,61 @@ unsafe fn handle_help_msg(
     }
     DefWindowProcW(hwnd, msg, wparam, lparam)
 }
+
+unsafe fn handle_launcher_msg(
+    window: &mut LauncherWindow,
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    match msg {
+        WM_NCHITTEST => {
+            return LRESULT(HTCLIENT as isize);
+        }
+        WM_ACTIVATE => {
+            // 他のウィンドウがアクティブになったらランチャーは閉じるよ
+            if (wparam.0 & 0xFFFF) as u32 == WA_INACTIVE {
+                window.handle_deactivate();
+            }
+            return LRESULT(0);
+        }
+        WM_PAINT => {
+            let mut ps = PAINTSTRUCT::default();
+            unsafe { BeginPaint(hwnd, &mut ps); }
+            if let Err(e) = window.draw() {
+                log::error!("Launcher Draw error: {}", e);
+            }
+            unsafe { EndPaint(hwnd, &ps); }
+            return LRESULT(0);
+        }
+        WM_MOUSEMOVE => {
+            let x = (lparam.0 & 0xFFFF) as i16 as i32;
+            let y = ((lparam.0 >> 16) & 0xFFFF) as i16 as i32;
+            if let Err(e) = window.handle_mouse_move(x, y) {
+                log::error!("Launcher Mouse move error: {}", e);
+            }
+            return LRESULT(0);
+        }
+        WM_LBUTTONUP => {
+            let x = (lparam.0 & 0xFFFF) as i16 as i32;
+            let y = ((lparam.0 >> 16) & 0xFFFF) as i16 as i32;
+            window.handle_lbutton_up(x, y);
+            return LRESULT(0);
+        }
+        WM_CHAR => {
+            if let Err(e) = window.handle_char(wparam.0 as u16) {
+                log::error!("Launcher Char error: {}", e);
+            }
+            return LRESULT(0);
+        }
+        WM_CONTEXTMENU => {
+            return LRESULT(0);
+        }
+        WM_ERASEBKGND => {
+            return LRESULT(1);
+        }
+        _ => {}
+    }
+    unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) }
+}